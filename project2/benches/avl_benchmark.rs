@@ -1,11 +1,5 @@
-#![allow(dead_code)]
-
-mod avl_tree {
-    include!("../src/avl_tree.rs");
-}
-
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
-use avl_tree::AVLTree;
+use project::avl_tree::AVLTree;
 
 fn insertion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("AVLTree");
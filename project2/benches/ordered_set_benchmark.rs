@@ -0,0 +1,118 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use project::ordered_set::OrderedSet;
+use project::llrb_tree::LLRBTree;
+use project::treap::Treap;
+use project::splay_tree::SplayTree;
+use project::scapegoat_tree::ScapegoatTree;
+use project::aa_tree::AATree;
+use project::wavl_tree::WAVLTree;
+use project::btree::BTree;
+use project::skip_list::SkipList;
+use project::sorted_vec_set::SortedVecSet;
+
+// same workloads as avl_benchmark and rb_benchmark, for any set behind the common interface
+fn insertion_benchmark<S: OrderedSet<i32>>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(name);
+    group.measurement_time(std::time::Duration::new(7, 0));
+    let tree_sizes = [10000, 40000, 70000, 100000, 130000];
+
+    for &size in &tree_sizes {
+        group.bench_with_input(BenchmarkId::new("Insert", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut tree = S::new();
+                for i in 0..size {
+                    tree.insert(black_box(i)); // insert elements in increasing order
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn search_benchmark<S: OrderedSet<i32>>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(name);
+    group.measurement_time(std::time::Duration::new(7, 0));
+    let tree_sizes = [10000, 40000, 70000, 100000, 130000];
+
+    for &size in &tree_sizes {
+        let mut tree = S::new();
+        for i in 0..size {
+            tree.insert(i);
+        }
+
+        group.bench_with_input(BenchmarkId::new("Search lowest", size/10), &size, |b, &size| {
+            b.iter(|| {
+                for i in 0..(size / 10) {
+                    black_box(tree.contains(i)); // Search for the lowest `size / 10` elements
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn llrb_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<LLRBTree<i32>>(c, "LLRBTree");
+    search_benchmark::<LLRBTree<i32>>(c, "LLRBTree");
+}
+
+fn treap_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<Treap<i32>>(c, "Treap");
+    search_benchmark::<Treap<i32>>(c, "Treap");
+}
+
+fn splay_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<SplayTree<i32>>(c, "SplayTree");
+    search_benchmark::<SplayTree<i32>>(c, "SplayTree");
+}
+
+fn scapegoat_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<ScapegoatTree<i32>>(c, "ScapegoatTree");
+    search_benchmark::<ScapegoatTree<i32>>(c, "ScapegoatTree");
+}
+
+fn aa_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<AATree<i32>>(c, "AATree");
+    search_benchmark::<AATree<i32>>(c, "AATree");
+}
+
+fn wavl_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<WAVLTree<i32>>(c, "WAVLTree");
+    search_benchmark::<WAVLTree<i32>>(c, "WAVLTree");
+}
+
+// a few orders, from 2-3-4 tree sized nodes up to nodes spanning several cache lines
+fn btree_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<BTree<i32, 2>>(c, "BTree(B=2)");
+    search_benchmark::<BTree<i32, 2>>(c, "BTree(B=2)");
+    insertion_benchmark::<BTree<i32, 8>>(c, "BTree(B=8)");
+    search_benchmark::<BTree<i32, 8>>(c, "BTree(B=8)");
+    insertion_benchmark::<BTree<i32, 32>>(c, "BTree(B=32)");
+    search_benchmark::<BTree<i32, 32>>(c, "BTree(B=32)");
+}
+
+fn skip_list_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<SkipList<i32>>(c, "SkipList");
+    search_benchmark::<SkipList<i32>>(c, "SkipList");
+}
+
+fn sorted_vec_benchmark(c: &mut Criterion) {
+    insertion_benchmark::<SortedVecSet<i32>>(c, "SortedVecSet");
+    search_benchmark::<SortedVecSet<i32>>(c, "SortedVecSet");
+}
+
+criterion_group!(
+    benches,
+    llrb_benchmark,
+    treap_benchmark,
+    splay_benchmark,
+    scapegoat_benchmark,
+    aa_benchmark,
+    wavl_benchmark,
+    btree_benchmark,
+    skip_list_benchmark,
+    sorted_vec_benchmark
+);
+criterion_main!(benches);
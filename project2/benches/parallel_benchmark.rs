@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use project::persistent_tree::PersistentAVLTree;
+
+// 1 thread runs the sequential code path, the others show the speedup
+const THREAD_COUNTS: [usize; 4] = [1, 2, 4, 8];
+
+fn from_sorted_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ParallelBulk");
+    group.measurement_time(std::time::Duration::new(7, 0));
+    let tree_sizes = [100000, 400000, 1000000];
+
+    for &size in &tree_sizes {
+        let values: Vec<i32> = (0..size).collect();
+        for &threads in &THREAD_COUNTS {
+            let name = format!("From sorted {} threads", threads);
+            group.bench_with_input(BenchmarkId::new(name, size), &values, |b, values| {
+                b.iter(|| black_box(PersistentAVLTree::par_from_sorted(values, threads)));
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn set_operation_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ParallelBulk");
+    group.measurement_time(std::time::Duration::new(7, 0));
+    let tree_sizes = [100000, 400000, 1000000];
+
+    for &size in &tree_sizes {
+        // even keys against multiples of three, so a third of each tree overlaps
+        let evens: Vec<i32> = (0..size).map(|i| i * 2).collect();
+        let threes: Vec<i32> = (0..size).map(|i| i * 3).collect();
+        let a = PersistentAVLTree::from_sorted(&evens);
+        let b = PersistentAVLTree::from_sorted(&threes);
+
+        for &threads in &THREAD_COUNTS {
+            let name = format!("Union {} threads", threads);
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |bench, _| {
+                bench.iter(|| black_box(a.par_union(&b, threads)));
+            });
+            let name = format!("Intersection {} threads", threads);
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |bench, _| {
+                bench.iter(|| black_box(a.par_intersection(&b, threads)));
+            });
+            let name = format!("Map {} threads", threads);
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |bench, _| {
+                bench.iter(|| black_box(a.par_map(threads, |key| key.wrapping_mul(31))));
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, from_sorted_benchmark, set_operation_benchmark);
+criterion_main!(benches);
@@ -1,12 +1,5 @@
-#![allow(dead_code)]
-
-mod redblack_tree {
-    include!("../src/redblack_tree.rs");
-}
-
-
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
-use redblack_tree::RedBlackTree;
+use project::redblack_tree::RedBlackTree;
 
 fn insertion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("RBTree");
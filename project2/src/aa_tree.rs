@@ -0,0 +1,312 @@
+use std::cmp::{max, min, Ordering};
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+
+type AALink<T> = Option<Box<AANode<T>>>;
+
+#[derive(Debug)]
+struct AANode<T> {
+    key: T,
+    // leaves sit at level 1, a missing child counts as level 0
+    level: u32,
+    left: AALink<T>,
+    right: AALink<T>,
+}
+
+// Andersson's AA tree, a red-black tree where only right children may be red. Instead of
+// colors every node keeps a level, and all rebalancing reduces to two operations: `skew`
+// removes a left horizontal link and `split` breaks up two right horizontal links in a row.
+#[derive(Debug)]
+pub struct AATree<T> {
+    root: AALink<T>,
+    len: usize,
+}
+
+impl<T: PartialOrd + Clone + Debug> AATree<T> {
+    pub fn new() -> Self {
+        AATree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.partial_cmp(&node.key) {
+                Some(Ordering::Less) => current = &node.left,
+                Some(Ordering::Greater) => current = &node.right,
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+        self.root = Some(Self::insert_node(self.root.take(), value));
+        self.len += 1;
+        true
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        self.root = Self::delete_node(self.root.take(), value);
+        self.len -= 1;
+        true
+    }
+
+    pub fn height(&self) -> usize {
+        fn height<T>(node: &AALink<T>) -> usize {
+            node.as_ref().map_or(0, |n| 1 + max(height(&n.left), height(&n.right)))
+        }
+        height(&self.root)
+    }
+
+    pub fn leaves(&self) -> usize {
+        fn leaves<T>(node: &AALink<T>) -> usize {
+            match node {
+                None => 0,
+                Some(n) if n.left.is_none() && n.right.is_none() => 1,
+                Some(n) => leaves(&n.left) + leaves(&n.right),
+            }
+        }
+        leaves(&self.root)
+    }
+
+    // keys in order
+    pub fn to_vec(&self) -> Vec<T> {
+        fn collect<T: Clone>(node: &AALink<T>, out: &mut Vec<T>) {
+            if let Some(n) = node {
+                collect(&n.left, out);
+                out.push(n.key.clone());
+                collect(&n.right, out);
+            }
+        }
+        let mut out = Vec::with_capacity(self.len);
+        collect(&self.root, &mut out);
+        out
+    }
+
+    pub fn print_struct(&self) {
+        fn print<T: Debug>(node: &AALink<T>, depth: usize, position: &str) {
+            if let Some(n) = node {
+                println!("{}{}{:?} (Level: {})", " ".repeat(depth * 2), position, n.key, n.level);
+                print(&n.left, depth + 1, "L: ");
+                print(&n.right, depth + 1, "R: ");
+            }
+        }
+        println!("AA Tree Structure:");
+        print(&self.root, 0, "Root: ");
+    }
+
+    // Report the first broken property: key order, leaves at level 1, left children one level
+    // down, right children at most one level down, no two right links at the same level and
+    // two children for every node above level 1.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let count = Self::check_node(&self.root, None, None)?;
+        if count != self.len {
+            return Err(format!("tree holds {} nodes but counts {}", count, self.len));
+        }
+        Ok(())
+    }
+
+    fn check_node(node: &AALink<T>, low: Option<&T>, high: Option<&T>) -> Result<usize, String> {
+        let n = match node {
+            Some(n) => n,
+            None => return Ok(0),
+        };
+        if low.is_some_and(|low| n.key <= *low) || high.is_some_and(|high| n.key >= *high) {
+            return Err(format!("key {:?} is out of order", n.key));
+        }
+        if n.left.is_none() && n.right.is_none() && n.level != 1 {
+            return Err(format!("leaf {:?} is at level {}", n.key, n.level));
+        }
+        if Self::level(&n.left) + 1 != n.level {
+            return Err(format!("left child of {:?} is not one level down", n.key));
+        }
+        let right = Self::level(&n.right);
+        if right != n.level && right + 1 != n.level {
+            return Err(format!("right child of {:?} is at level {} below level {}", n.key, right, n.level));
+        }
+        if n.right.as_ref().is_some_and(|r| Self::level(&r.right) == n.level) {
+            return Err(format!("key {:?} starts two horizontal links in a row", n.key));
+        }
+        if n.level > 1 && (n.left.is_none() || n.right.is_none()) {
+            return Err(format!("key {:?} at level {} misses a child", n.key, n.level));
+        }
+        let left_count = Self::check_node(&n.left, low, Some(&n.key))?;
+        let right_count = Self::check_node(&n.right, Some(&n.key), high)?;
+        Ok(left_count + right_count + 1)
+    }
+
+    fn level(node: &AALink<T>) -> u32 {
+        node.as_ref().map_or(0, |n| n.level)
+    }
+
+    // turn a left horizontal link into a right one
+    fn skew(mut h: Box<AANode<T>>) -> Box<AANode<T>> {
+        if Self::level(&h.left) == h.level {
+            let mut l = h.left.take().unwrap();
+            h.left = l.right.take();
+            l.right = Some(h);
+            l
+        } else {
+            h
+        }
+    }
+
+    // lift the middle node of two right horizontal links one level up
+    fn split(mut h: Box<AANode<T>>) -> Box<AANode<T>> {
+        if h.right.as_ref().is_some_and(|r| Self::level(&r.right) == h.level) {
+            let mut r = h.right.take().unwrap();
+            h.right = r.left.take();
+            r.left = Some(h);
+            r.level += 1;
+            r
+        } else {
+            h
+        }
+    }
+
+    fn insert_node(node: AALink<T>, value: T) -> Box<AANode<T>> {
+        let mut h = match node {
+            Some(h) => h,
+            None => return Box::new(AANode { key: value, level: 1, left: None, right: None }),
+        };
+        if value < h.key {
+            h.left = Some(Self::insert_node(h.left.take(), value));
+        } else {
+            h.right = Some(Self::insert_node(h.right.take(), value));
+        }
+        Self::split(Self::skew(h))
+    }
+
+    // `value` is known to be in the subtree
+    fn delete_node(node: AALink<T>, value: &T) -> AALink<T> {
+        let mut h = node?;
+        match value.partial_cmp(&h.key) {
+            Some(Ordering::Less) => h.left = Self::delete_node(h.left.take(), value),
+            Some(Ordering::Greater) => h.right = Self::delete_node(h.right.take(), value),
+            _ => {
+                if h.left.is_none() && h.right.is_none() {
+                    return None;
+                }
+                // swap in the neighbour from the side that exists and delete it down there
+                if h.left.is_none() {
+                    let successor = Self::min_key(h.right.as_ref().unwrap()).clone();
+                    h.right = Self::delete_node(h.right.take(), &successor);
+                    h.key = successor;
+                } else {
+                    let predecessor = Self::max_key(h.left.as_ref().unwrap()).clone();
+                    h.left = Self::delete_node(h.left.take(), &predecessor);
+                    h.key = predecessor;
+                }
+            },
+        }
+        Some(Self::rebalance_delete(h))
+    }
+
+    // lower the level where a child dropped by two, then skew and split along the right spine
+    fn rebalance_delete(mut h: Box<AANode<T>>) -> Box<AANode<T>> {
+        let should_be = min(Self::level(&h.left), Self::level(&h.right)) + 1;
+        if should_be < h.level {
+            h.level = should_be;
+            if let Some(r) = &mut h.right {
+                if should_be < r.level {
+                    r.level = should_be;
+                }
+            }
+        }
+        h = Self::skew(h);
+        if let Some(r) = h.right.take() {
+            let mut r = Self::skew(r);
+            if let Some(rr) = r.right.take() {
+                r.right = Some(Self::skew(rr));
+            }
+            h.right = Some(r);
+        }
+        h = Self::split(h);
+        if let Some(r) = h.right.take() {
+            h.right = Some(Self::split(r));
+        }
+        h
+    }
+
+    fn min_key(node: &AANode<T>) -> &T {
+        let mut current = node;
+        while let Some(left) = &current.left {
+            current = left;
+        }
+        &current.key
+    }
+
+    fn max_key(node: &AANode<T>) -> &T {
+        let mut current = node;
+        while let Some(right) = &current.right {
+            current = right;
+        }
+        &current.key
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> Default for AATree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> OrderedSet<T> for AATree<T> {
+    fn new() -> Self {
+        AATree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        AATree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        AATree::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        AATree::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        AATree::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        AATree::is_empty(self)
+    }
+
+    fn height(&self) -> usize {
+        AATree::height(self)
+    }
+
+    fn leaves(&self) -> usize {
+        AATree::leaves(self)
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        AATree::to_vec(self)
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        AATree::check_invariants(self)
+    }
+}
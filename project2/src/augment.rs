@@ -0,0 +1,94 @@
+use std::ops::Add;
+
+// Summary kept on every node of an augmented tree.
+// A node's summary is `left.combine(lift(node)).combine(right)`, so `combine` has to be
+// associative; the trees recompute it after every insert, delete and rotation.
+pub trait Summary<T>: Clone {
+    // summary of a single node holding `count` occurrences of `key`
+    fn lift(key: &T, count: usize) -> Self;
+    fn combine(&self, other: &Self) -> Self;
+
+    // combine optional summaries, a missing one stands for an empty subtree
+    fn combine_opt(left: Option<Self>, right: Option<Self>) -> Option<Self> {
+        match (left, right) {
+            (Some(left), Some(right)) => Some(left.combine(&right)),
+            (left, None) => left,
+            (None, right) => right,
+        }
+    }
+}
+
+// no augmentation, the default for both trees
+impl<T> Summary<T> for () {
+    fn lift(_key: &T, _count: usize) {}
+    fn combine(&self, _other: &Self) {}
+}
+
+// sum of the keys, repeated keys are added once per occurrence
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sum<T>(pub T);
+
+impl<T: Copy + Add<Output = T>> Summary<T> for Sum<T> {
+    // adds the key to itself by doubling, O(log count) additions rather than `count`
+    fn lift(key: &T, count: usize) -> Self {
+        let mut sum: Option<T> = None;
+        let mut power = *key;
+        let mut rest = count;
+        while rest > 0 {
+            if rest & 1 == 1 {
+                sum = Some(sum.map_or(power, |sum| sum + power));
+            }
+            rest >>= 1;
+            if rest > 0 {
+                power = power + power;
+            }
+        }
+        Sum(sum.unwrap_or(*key))
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Sum(self.0 + other.0)
+    }
+}
+
+// smallest key
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Min<T>(pub T);
+
+impl<T: Copy + PartialOrd> Summary<T> for Min<T> {
+    fn lift(key: &T, _count: usize) -> Self {
+        Min(*key)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        if other.0 < self.0 { *other } else { *self }
+    }
+}
+
+// largest key
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Max<T>(pub T);
+
+impl<T: Copy + PartialOrd> Summary<T> for Max<T> {
+    fn lift(key: &T, _count: usize) -> Self {
+        Max(*key)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        if other.0 > self.0 { *other } else { *self }
+    }
+}
+
+// number of occurrences
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Count(pub usize);
+
+impl<T> Summary<T> for Count {
+    fn lift(_key: &T, count: usize) -> Self {
+        Count(count)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Count(self.0 + other.0)
+    }
+}
@@ -2,39 +2,75 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::cmp::max;
 use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::str::FromStr;
+use std::collections::VecDeque;
+use crate::augment::Summary;
+use crate::bracket::{self, BracketNode};
+use crate::codec::{self, Codec, TreeKind};
+use crate::interchange::{self, Json, JsonTree};
+use crate::ordered_set::OrderedSet;
+use crate::dot::DotWriter;
+use crate::durable::SnapshotTree;
+use crate::observer::{Direction, ObserverRef, ObserverSlot};
+use crate::pretty::{self, Layout, PrettyNode};
+use crate::stats::{StatsCounter, TreeStats};
+use crate::svg::{self, SvgNode};
 
-type Link<T> = Rc<RefCell<TreeNode<T>>>; 
+type Link<T, S = ()> = Rc<RefCell<TreeNode<T, S>>>; 
 
 #[derive(Clone, Debug, PartialEq)] 
-pub struct TreeNode<T> {
-	pub key: T,
-    pub height: i32,
-	pub parent: Option<Link<T>>, 
-	pub left: Option<Link<T>>, 
-	pub right: Option<Link<T>>,
+pub struct TreeNode<T, S = ()> {
+	key: T,
+    height: i32,
+    count: usize,
+    // occurrences in the whole subtree, for rank
+    size: usize,
+	parent: Option<Link<T, S>>, 
+	left: Option<Link<T, S>>, 
+	right: Option<Link<T, S>>,
+    summary: S,
 }
 
 
-impl<T: PartialOrd + Copy + std::fmt::Debug> TreeNode<T> {
-	pub fn new(key: T) -> Link<T> {
+impl<T: PartialOrd + Clone + std::fmt::Debug, S: Summary<T>> TreeNode<T, S> {
+	pub fn new(key: T) -> Link<T, S> {
+        let summary = S::lift(&key, 1);
 		Rc::new(RefCell::new(TreeNode {
             key,
             parent: None,
             left: None,
             right: None,
             height: 1,
+            count: 1,
+            size: 1,
+            summary,
         }))
 	}
 	
 
     // get height of a node, could be self or others, return 0 if node is none
-    fn height(node: &Option<Link<T>>) -> i32 {
+    fn height(node: &Option<Link<T, S>>) -> i32 {
         node.as_ref().map_or(0, |n| n.borrow().height)
     }
 
     //update height after rotation, it's 1+max(left.height, right.height)
+    // the summary depends on the same children, so it is refreshed here as well
     fn update_height(&mut self) {
         self.height = 1 + max(Self::height(&self.left), Self::height(&self.right));
+        self.update_summary();
+    }
+
+    // summary and size of the subtree, left + this node + right
+    fn update_summary(&mut self) {
+        let left = self.left.as_ref().map(|n| n.borrow().summary.clone());
+        let right = self.right.as_ref().map(|n| n.borrow().summary.clone());
+        let size = |child: &Option<Link<T, S>>| child.as_ref().map_or(0, |n| n.borrow().size);
+        self.size = size(&self.left) + self.count + size(&self.right);
+        let own = S::lift(&self.key, self.count);
+        self.summary = S::combine_opt(S::combine_opt(left, Some(own)), right).unwrap();
     }
 
     // l - r, > 1 or < -1 requires rotation
@@ -42,57 +78,201 @@ impl<T: PartialOrd + Copy + std::fmt::Debug> TreeNode<T> {
         Self::height(&self.left) - Self::height(&self.right)
     }
 
-    // traverse tree inorder
-    fn in_order_traverse<F>(&self, visit: &F)
-    where
-        F: Fn(&T),
-    {
-        // left go first
-        if let Some(ref left) = self.left {
-            left.borrow().in_order_traverse(visit);
+    fn write_structure<W: fmt::Write>(out: &mut W, node: &Option<Link<T, S>>, depth: usize, position: &str) -> fmt::Result {
+        if let Some(rc_node) = node {
+            let borrowed_node = rc_node.borrow();
+
+            // Write the current node with its depth
+            writeln!(out, "{}{}{:?} (Height: {})", " ".repeat(depth * 2), position, borrowed_node.key, borrowed_node.height)?;
+
+            // Recursively write the left and right children, increasing the depth
+            Self::write_structure(out, &borrowed_node.left, depth + 1, "L: ")?;
+            Self::write_structure(out, &borrowed_node.right, depth + 1, "R: ")?;
         }
-        // visit self
-        visit(&self.key);
-        // visit right part last
-        if let Some(ref right) = self.right {
-            right.borrow().in_order_traverse(visit);
+        Ok(())
+    }
+
+    // key as the printers show it, `key*count` for a key stored more than once
+    fn label(&self) -> String {
+        if self.count > 1 {
+            format!("{:?}*{}", self.key, self.count)
+        } else {
+            format!("{:?}", self.key)
         }
     }
 
-    
+}
 
-    fn print_structure(node: &Option<Link<T>>, depth: usize, position: &str) {
-        if let Some(rc_node) = node {
-            let borrowed_node = rc_node.borrow();
 
-            // Print the current node with its depth
-            println!("{}{}{:?} (Height: {})", " ".repeat(depth * 2), position, borrowed_node.key, borrowed_node.height);
+#[derive(Clone, PartialEq)] 
+pub struct AVLTree<T, S = ()> {
+	root: Option<Link<T, S>>,
+	count: u32,
+    multiset: bool,
+    stats: StatsCounter,
+    observer: ObserverSlot<T>,
+}
+
+// in-order iterator, a key is yielded once per occurrence
+pub struct Iter<T, S = ()> {
+    stack: Vec<Link<T, S>>,
+    repeat: Option<(T, usize)>,
+}
+
+impl<T: Clone, S> Iter<T, S> {
+    fn new(root: &Option<Link<T, S>>) -> Self {
+        let mut iter = Iter { stack: Vec::new(), repeat: None };
+        iter.push_left(root.clone());
+        iter
+    }
+
+    // push a node and all of its left descendants
+    fn push_left(&mut self, mut node: Option<Link<T, S>>) {
+        while let Some(n) = node {
+            node = n.borrow().left.clone();
+            self.stack.push(n);
+        }
+    }
+}
+
+impl<T: Clone, S> Iterator for Iter<T, S> {
+    type Item = T;
 
-            // Recursively print the left and right children, increasing the depth
-            Self::print_structure(&borrowed_node.left, depth + 1, "L: ");
-            Self::print_structure(&borrowed_node.right, depth + 1, "R: ");
+    fn next(&mut self) -> Option<T> {
+        if let Some((key, left)) = self.repeat.take() {
+            if left > 1 {
+                self.repeat = Some((key.clone(), left - 1));
+            }
+            return Some(key);
         }
+        let node = self.stack.pop()?;
+        let (key, count, right) = {
+            let n = node.borrow();
+            (n.key.clone(), n.count, n.right.clone())
+        };
+        self.push_left(right);
+        if count > 1 {
+            self.repeat = Some((key.clone(), count - 1));
+        }
+        Some(key)
     }
+}
 
+// pre-order iterator, yields every node once together with its depth (the root is at depth 0)
+pub struct PreOrderIter<T, S = ()> {
+    stack: Vec<(Link<T, S>, usize)>,
 }
 
+impl<T: Clone, S> Iterator for PreOrderIter<T, S> {
+    type Item = (T, usize);
 
-#[derive(Clone, PartialEq)] 
-pub struct AVLTree<T> {
-	root: Option<Link<T>>,
-	count: u32
+    fn next(&mut self) -> Option<(T, usize)> {
+        let (node, depth) = self.stack.pop()?;
+        let n = node.borrow();
+        // right goes first so that the left subtree comes out first
+        if let Some(right) = &n.right {
+            self.stack.push((right.clone(), depth + 1));
+        }
+        if let Some(left) = &n.left {
+            self.stack.push((left.clone(), depth + 1));
+        }
+        Some((n.key.clone(), depth))
+    }
+}
+
+// post-order iterator, yields every node once together with its depth
+pub struct PostOrderIter<T, S = ()> {
+    // the flag tells whether the children of the node were already pushed
+    stack: Vec<(Link<T, S>, usize, bool)>,
+}
+
+impl<T: Clone, S> Iterator for PostOrderIter<T, S> {
+    type Item = (T, usize);
+
+    fn next(&mut self) -> Option<(T, usize)> {
+        while let Some((node, depth, expanded)) = self.stack.pop() {
+            if expanded {
+                let n = node.borrow();
+                return Some((n.key.clone(), depth));
+            }
+            let (left, right) = {
+                let n = node.borrow();
+                (n.left.clone(), n.right.clone())
+            };
+            self.stack.push((node, depth, true));
+            if let Some(right) = right {
+                self.stack.push((right, depth + 1, false));
+            }
+            if let Some(left) = left {
+                self.stack.push((left, depth + 1, false));
+            }
+        }
+        None
+    }
+}
+
+// level-order (breadth-first) iterator, yields every node once together with its depth
+pub struct LevelOrderIter<T, S = ()> {
+    queue: VecDeque<(Link<T, S>, usize)>,
+}
+
+impl<T: Clone, S> Iterator for LevelOrderIter<T, S> {
+    type Item = (T, usize);
+
+    fn next(&mut self) -> Option<(T, usize)> {
+        let (node, depth) = self.queue.pop_front()?;
+        let n = node.borrow();
+        if let Some(left) = &n.left {
+            self.queue.push_back((left.clone(), depth + 1));
+        }
+        if let Some(right) = &n.right {
+            self.queue.push_back((right.clone(), depth + 1));
+        }
+        Some((n.key.clone(), depth))
+    }
 }
 
-impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
 	pub fn new() -> Self {
+        Self::with_summary()
+    }
+
+    // multiset mode, inserting an existing key adds one more occurrence to its node
+    pub fn new_multiset() -> Self {
+        Self::multiset_with_summary()
+    }
+}
+
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> Default for AVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display, S: Summary<T>> AVLTree<T, S>  {
+    // augmented tree, every node keeps the summary `S` of its subtree
+    pub fn with_summary() -> Self {
+        AVLTree {
+            root: None,
+            count: 0,
+            multiset: false,
+            stats: StatsCounter::default(),
+            observer: ObserverSlot::default(),
+        }
+    }
+
+    pub fn multiset_with_summary() -> Self {
         AVLTree {
             root: None,
             count: 0,
+            multiset: true,
+            stats: StatsCounter::default(),
+            observer: ObserverSlot::default(),
         }
     }
 
     pub fn count_leaves(&self) -> usize {
-        fn count_leaves_recursive<T>(node: &Option<Link<T>>) -> usize {
+        fn count_leaves_recursive<T, S>(node: &Option<Link<T, S>>) -> usize {
             match node {
                 Some(n) => {
                     let n_borrow = n.borrow();
@@ -113,24 +293,22 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
         count_leaves_recursive(&self.root)
     }
 
-    pub fn display_in_order(&self) {
-        fn traverse_in_order<T: std::fmt::Display>(
-            node: &Option<Link<T>>,
-            visit: &mut impl FnMut(&T),
-        ) {
-            if let Some(rc_node) = node {
-                let borrowed_node = rc_node.borrow();
-                traverse_in_order(&borrowed_node.left, visit);
-                visit(&borrowed_node.key);
-                traverse_in_order(&borrowed_node.right, visit);
+    // keys in order separated by spaces
+    pub fn write_in_order<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        for (index, key) in self.iter().enumerate() {
+            if index > 0 {
+                out.write_char(' ')?;
             }
+            write!(out, "{}", key)?;
         }
+        Ok(())
+    }
 
-        let mut display = |key: &T| {
-            print!("{} ", key);
-        };
-        traverse_in_order(&self.root, &mut display);
-        println!();
+    pub fn in_order_string(&self) -> String {
+        let mut out = String::new();
+        // writing into a String cannot fail
+        self.write_in_order(&mut out).unwrap();
+        out
     }
 
     // check empty
@@ -147,27 +325,103 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
 
     // print tree in-order
     pub fn print_in_order(&self) {
-        print!("in-order: ");
-        self.root.as_ref().map(|node| {
-            node.borrow().in_order_traverse(&|key| {      // the node will visit value in order
-                print!("{:?} ", key);
-            });
-        });
-        println!();
+        println!("in-order: {}", self.in_order_string());
     }
 
     
     // print structure
     pub fn print_struct(&self) {
-        println!("AVL Tree Structure:");
-        TreeNode::print_structure(&self.root, 0, "Root: ");
+        print!("{}", self.structure_string());
+    }
+
+    // the indented structure print_struct shows, one node per line with its height
+    pub fn structure_string(&self) -> String {
+        let mut out = String::from("AVL Tree Structure:\n");
+        TreeNode::write_structure(&mut out, &self.root, 0, "Root: ").unwrap();
+        out
+    }
+
+    // the tree in one of the pretty-printing layouts
+    pub fn to_pretty_string(&self, layout: Layout) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, layout).unwrap();
+        out
+    }
+
+    pub fn write_pretty<W: fmt::Write>(&self, out: &mut W, layout: Layout) -> fmt::Result {
+        let root = Self::pretty_node(&self.root);
+        pretty::write_tree(out, root.as_deref(), layout)
+    }
+
+    pub fn write_pretty_io<W: io::Write>(&self, out: &mut W, layout: Layout) -> io::Result<()> {
+        out.write_all(self.to_pretty_string(layout).as_bytes())
+    }
+
+    fn pretty_node(node: &Option<Link<T, S>>) -> Option<Box<PrettyNode>> {
+        let node = node.as_ref()?.borrow();
+        Some(Box::new(PrettyNode {
+            label: node.label(),
+            left: Self::pretty_node(&node.left),
+            right: Self::pretty_node(&node.right),
+        }))
+    }
+
+    // Graphviz DOT document of the tree, every node shows its key, height and balance factor
+    pub fn to_dot(&self) -> String {
+        let mut dot = DotWriter::new("AVLTree");
+        if self.root.is_some() {
+            Self::dot_node(&self.root, &mut dot);
+        }
+        dot.finish()
+    }
+
+    // standalone SVG drawing of the tree, laid out as a tidy tree
+    pub fn to_svg(&self) -> String {
+        let root = Self::svg_node(&self.root);
+        svg::render(root.as_deref(), "AVL Tree")
+    }
+
+    fn svg_node(node: &Option<Link<T, S>>) -> Option<Box<SvgNode>> {
+        let node = node.as_ref()?.borrow();
+        Some(Box::new(SvgNode {
+            label: node.label(),
+            detail: Some(format!("h={} bf={}", node.height, node.balance_factor())),
+            fill: "#cfe2f3",
+            text_color: "#000000",
+            left: Self::svg_node(&node.left),
+            right: Self::svg_node(&node.right),
+        }))
+    }
+
+    // write the subtree and return the id of its top node
+    fn dot_node(node: &Option<Link<T, S>>, dot: &mut DotWriter) -> String {
+        let node = match node {
+            Some(node) => node.borrow(),
+            None => return dot.nil(),
+        };
+        let label = format!("{}\nh={} bf={}", node.label(), node.height, node.balance_factor());
+        let id = dot.node(&label, "");
+        for child in [&node.left, &node.right] {
+            let child_id = Self::dot_node(child, dot);
+            dot.edge(&id, &child_id);
+        }
+        id
     }
 
     pub fn insert(&mut self, value: T) {
-    	let new_node = TreeNode::new(value);
+        self.stats.record(|s| s.operations += 1);
+        if self.multiset {
+            if let Some(node) = self.search_link(&value) {
+                node.borrow_mut().count += 1;
+                Self::refresh_upward(node);
+                self.observer.notify(|o| o.on_insert(&value));
+                return;
+            }
+        }
+    	let new_node = TreeNode::new(value.clone());
         if let Some(root) = &self.root {
             // insert node recursively
-            Self::insert_recursive(&root, new_node);
+            self.insert_recursive(root, new_node);
             // deal root problem
             self.update_root();
             
@@ -176,6 +430,7 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
 
         }
     	self.count += 1;
+        self.observer.notify(|o| o.on_insert(&value));
     }
 
     fn update_root(&mut self) {
@@ -199,14 +454,15 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
         self.root = current;
     }
 
-    fn insert_recursive(cur_node: &Link<T>, new_node: Link<T>) {
+    fn insert_recursive(&self, cur_node: &Link<T, S>, new_node: Link<T, S>) {
         //let mut rotate_flag: bool = false;
+        self.stats.record(|s| s.nodes_visited += 1);
         
         let mut n = cur_node.borrow_mut();
-        if new_node.borrow().key < n.key {
+        if self.stats.compare(new_node.borrow().key < n.key) {
             if let Some(left) = &mut n.left.clone() {
                 drop(n);    // clear borrow mut
-                Self::insert_recursive(left, new_node); // Recursive call
+                self.insert_recursive(left, new_node); // Recursive call
             } else {
                 new_node.borrow_mut().parent = Some(Rc::clone(cur_node));
                 n.left = Some(new_node);
@@ -215,49 +471,52 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
         } else {
             if let Some(right) = &mut n.right.clone() {
                 drop(n);    // clear borrow mut
-                Self::insert_recursive(right, new_node); // Recursive call
+                self.insert_recursive(right, new_node); // Recursive call
             } else {
                 new_node.borrow_mut().parent = Some(Rc::clone(cur_node));
                 n.right = Some(new_node);
                 drop(n)
             }
         }
-        // re-declare n
-        let mut n = cur_node.borrow_mut();
         // Update the height of the node
-        n.update_height();
+        self.refresh_height(cur_node);
         // Balance the tree if necessary
-        let f_balance = n.balance_factor();
-        drop(n);
+        let f_balance = cur_node.borrow().balance_factor();
         // use rotation to balance the tree
         if f_balance > 1 {
             //rotate_flag = true;     // we do rotation
             // check childs balance_fact 3 2l 1ll or 3 1l 2lr (later need a left rotation first)
             let left = cur_node.borrow().left.clone();
+            let mut double = false;
             if let Some(old_left) =  left{ 
                 if old_left.borrow().balance_factor() < 0 { 
                         // do left rotation on left and its child
-                        Self::rotate_left(&old_left);
+                        self.rotate_left(&old_left);
+                        double = true;
                         // change left
                         //n.left = old_left.borrow().parent;   
                 }
             }
             // do right rotation on self and left child
-            Self::rotate_right(cur_node);
+            self.rotate_right(cur_node);
+            self.record_rotation(double);
         } 
         else if f_balance < -1 {
             //rotate_flag = true;     // we do rotation
             let right = cur_node.borrow().right.clone();
+            let mut double = false;
             if let Some(old_right) = right {
                 if old_right.borrow().balance_factor() > 0 {    
                     // do right rotation on right and its child
-                    Self::rotate_right(&old_right)
+                    self.rotate_right(&old_right);
+                    double = true;
                     // change right
                     //n.right = old_right.borrow().parent;
                 }
             }
             // do left rotation
-            Self::rotate_left(cur_node);
+            self.rotate_left(cur_node);
+            self.record_rotation(double);
         }
 
 
@@ -265,9 +524,27 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
         //rotate_flag
     }
 
+    // a double rotation is the inner rotation on the child followed by the one on the node
+    fn record_rotation(&self, double: bool) {
+        self.stats.record(|s| if double { s.double_rotations += 1 } else { s.single_rotations += 1 });
+    }
+
+    // update_height on a linked node, the observer hears about heights that moved
+    fn refresh_height(&self, node: &Link<T, S>) {
+        let mut n = node.borrow_mut();
+        let old = n.height;
+        n.update_height();
+        if n.height != old {
+            let (key, new) = (n.key.clone(), n.height);
+            drop(n);
+            self.observer.notify(|o| o.on_height_change(&key, old, new));
+        }
+    }
+
     // will borrow parent, currnet, right mut
     // 1 2r 3r -> 1l 2 3r
-    fn rotate_left(node: &Link<T>) {
+    fn rotate_left(&self, node: &Link<T, S>) {
+        self.observer.notify(|o| o.on_rotate(&node.borrow().key, Direction::Left));
         let node_right = node.borrow_mut().right.take().unwrap();
         let node_right_left = node_right.borrow_mut().left.take();
 
@@ -308,13 +585,14 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
         }
                 
         // Update heights
-        node.borrow_mut().update_height();
-        node_right.borrow_mut().update_height();
+        self.refresh_height(node);
+        self.refresh_height(&node_right);
         //println!("value: {}",node.borrow().parent.clone().unwrap().borrow().key);
 
     }
 
-    fn rotate_right(node: &Link<T>) {
+    fn rotate_right(&self, node: &Link<T, S>) {
+        self.observer.notify(|o| o.on_rotate(&node.borrow().key, Direction::Right));
         let node_left = node.borrow_mut().left.take().unwrap();
         let node_left_right = node_left.borrow_mut().right.take();
         // move baby to node
@@ -359,18 +637,20 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
         }
 
         // Update heights
-        node.borrow_mut().update_height();
-        node_left.borrow_mut().update_height();
+        self.refresh_height(node);
+        self.refresh_height(&node_left);
     }
 
 
     pub fn delete(&mut self, value: T) {
+        self.stats.record(|s| s.operations += 1);
         if let Some(root) = self.root.clone() {
-            let (new_root, deleted) = Self::delete_recursive(root, value);
+            let (new_root, removed) = self.delete_recursive(root, &value);
             self.root = new_root;
             
-            if deleted {
+            if removed > 0 {
                 self.count -= 1;
+                self.removed(value, removed);
             } else {
                 println!("node not found");
             }
@@ -380,34 +660,36 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
     }
 
 
-     fn delete_recursive(node: Link<T>, value: T) -> (Option<Link<T>>, bool) {
+    // the second value is the number of occurrences the removed node held, 0 if none was found
+     fn delete_recursive(&self, node: Link<T, S>, value: &T) -> (Option<Link<T, S>>, usize) {
         //println!("check {}", value);
         let mut node_borrow = node.borrow_mut();
-        let mut deleted = false;
+        let mut removed = 0;
+        self.stats.record(|s| s.nodes_visited += 1);
 
-        if value < node_borrow.key {
+        if self.stats.compare(*value < node_borrow.key) {
             //println!("left");
             if let Some(left) = node_borrow.left.clone() {
                 drop(node_borrow);
-                let (new_left, was_deleted) = Self::delete_recursive(left, value);
+                let (new_left, was_removed) = self.delete_recursive(left, value);
                 node.borrow_mut().left = new_left;
-                deleted = was_deleted;
+                removed = was_removed;
             } else {
                 drop(node_borrow);  // only to drop
             }
-        } else if value > node_borrow.key {
+        } else if self.stats.compare(*value > node_borrow.key) {
             //println!("right");
             if let Some(right) = node_borrow.right.clone() {
                 drop(node_borrow);
-                let (new_right, was_deleted) = Self::delete_recursive(right, value);
+                let (new_right, was_removed) = self.delete_recursive(right, value);
                 node.borrow_mut().right = new_right;
-                deleted = was_deleted;
+                removed = was_removed;
             } else {
                 drop(node_borrow); // only for drop
             }
         } else {
             //println!("found");
-            deleted = true;
+            removed = node_borrow.count;
             if node_borrow.left.is_none() || node_borrow.right.is_none() {
                 // Node with only one child or no child
                 //println!("check 3");
@@ -417,21 +699,22 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
                     right.borrow_mut().parent = node_borrow.parent.clone();
                 }
                 drop(node_borrow);
-                return (node.borrow().left.clone().or(node.borrow().right.clone()), true);
+                return (node.borrow().left.clone().or(node.borrow().right.clone()), removed);
             } else {
                 //println!("check 2");
                 let in_order_successor = Self::min_value_node(node_borrow.right.clone().unwrap());
-                let new_value = in_order_successor.borrow().key;
-                node_borrow.key = new_value;
+                let new_value = in_order_successor.borrow().key.clone();
+                node_borrow.key = new_value.clone();
+                node_borrow.count = in_order_successor.borrow().count;
                 drop(in_order_successor);
                 drop(node_borrow);
                 let old_right = node.borrow().right.clone().unwrap();
-                let (new_right, _) = Self::delete_recursive(old_right, new_value);
+                let (new_right, _) = self.delete_recursive(old_right, &new_value);
                 node.borrow_mut().right = new_right;
             }
         } 
         // Update the height of the node
-        node.borrow_mut().update_height();
+        self.refresh_height(&node);
         // Balance the tree
         let f_balance = node.borrow().balance_factor();
 
@@ -440,40 +723,46 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
         if f_balance > 1 {
             // check childs balance_fact 3 2l 1ll or 3 1l 2lr (later need a left rotation first)
             let left = node.borrow().left.clone();
+            let mut double = false;
             if let Some(old_left) =  left{ 
                 if old_left.borrow().balance_factor() < 0 { 
                         // do left rotation on left and its child
-                        Self::rotate_left(&old_left);
+                        self.rotate_left(&old_left);
+                        double = true;
                         // change left
                         //n.left = old_left.borrow().parent;   
                 }
             }
             // do right rotation on self and left child
-            Self::rotate_right(&node);
+            self.rotate_right(&node);
+            self.record_rotation(double);
             new_node = node.borrow().parent.clone().unwrap();
         } 
         else if f_balance < -1 {
             let right = node.borrow().right.clone();
+            let mut double = false;
             if let Some(old_right) = right {
                 if old_right.borrow().balance_factor() > 0 {    
                     // do right rotation on right and its child
-                    Self::rotate_right(&old_right)
+                    self.rotate_right(&old_right);
+                    double = true;
                     // change right
                     //n.right = old_right.borrow().parent;
                 }
             }
             // do left rotation
-            Self::rotate_left(&node);
+            self.rotate_left(&node);
+            self.record_rotation(double);
             new_node = node.borrow().parent.clone().unwrap();
         }
 
 
-        (Some(new_node), deleted)
+        (Some(new_node), removed)
     }
 
 
     // find the node with the minimum key value in a subtree
-    fn min_value_node(node: Link<T>) -> Link<T> {
+    fn min_value_node(node: Link<T, S>) -> Link<T, S> {
         let mut current = node.clone();
 
         loop {
@@ -489,10 +778,238 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
 
         current
     }
+    // number of occurrences of a key, 0 if absent
+    pub fn count(&self, value: T) -> usize {
+        self.search_link(&value).map_or(0, |node| node.borrow().count)
+    }
+
+    // remove a single occurrence, the node goes away with its last occurrence
+    pub fn remove_one(&mut self, value: T) -> bool {
+        match self.search_link(&value) {
+            Some(node) => {
+                if node.borrow().count > 1 {
+                    node.borrow_mut().count -= 1;
+                    Self::refresh_upward(node);
+                    self.removed(value, 1);
+                } else {
+                    self.delete(value);
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    // remove every occurrence of a key, return how many were removed
+    pub fn remove_all(&mut self, value: T) -> usize {
+        let removed = self.count(value.clone());
+        if removed > 0 {
+            self.delete(value);
+        }
+        removed
+    }
+
+    // Every removal ends here: the observer hears of each occurrence that went away, the
+    // way insert reports each occurrence added
+    fn removed(&self, value: T, occurrences: usize) {
+        self.observer.notify(|o| {
+            for _ in 0..occurrences {
+                o.on_delete(&value);
+            }
+        });
+    }
+
+    // recompute summaries from a node up to the root after its count changed
+    fn refresh_upward(node: Link<T, S>) {
+        let mut current = Some(node);
+        while let Some(n) = current {
+            n.borrow_mut().update_summary();
+            current = n.borrow().parent.clone();
+        }
+    }
+
+    // summary of the whole tree, None when it is empty
+    pub fn summary(&self) -> Option<S> {
+        self.root.as_ref().map(|root| root.borrow().summary.clone())
+    }
+
+    // summary of the keys in `low..high`, None when the range is empty
+    // only the two boundary paths are walked, whole subtrees in between use their stored summary
+    pub fn range_summary(&self, low: T, high: T) -> Option<S> {
+        Self::range_summary_node(&self.root, Some(low), Some(high))
+    }
+
+    // `low` is inclusive and `high` exclusive, None means unbounded on that side
+    fn range_summary_node(node: &Option<Link<T, S>>, low: Option<T>, high: Option<T>) -> Option<S> {
+        let node = node.as_ref()?;
+        let n = node.borrow();
+        if low.as_ref().is_some_and(|low| n.key < *low) {
+            return Self::range_summary_node(&n.right, low, high);
+        }
+        if high.as_ref().is_some_and(|high| n.key >= *high) {
+            return Self::range_summary_node(&n.left, low, high);
+        }
+        // the key is inside the range, so each side is only bounded from one end
+        let left = match low {
+            Some(_) => Self::range_summary_node(&n.left, low, None),
+            None => n.left.as_ref().map(|left| left.borrow().summary.clone()),
+        };
+        let right = match high {
+            Some(_) => Self::range_summary_node(&n.right, None, high),
+            None => n.right.as_ref().map(|right| right.borrow().summary.clone()),
+        };
+        S::combine_opt(S::combine_opt(left, Some(S::lift(&n.key, n.count))), right)
+    }
+
+    // keys inside `range` in order, repeated keys once per occurrence
+    // subtrees that lie wholly outside the range are skipped
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Vec<T> {
+        let mut keys = Vec::new();
+        Self::range_node(&self.root, &range, &mut keys);
+        keys
+    }
+
+    fn range_node<R: RangeBounds<T>>(node: &Option<Link<T, S>>, range: &R, keys: &mut Vec<T>) {
+        if let Some(node) = node {
+            let n = node.borrow();
+            let after_start = match range.start_bound() {
+                Bound::Included(start) => n.key >= *start,
+                Bound::Excluded(start) => n.key > *start,
+                Bound::Unbounded => true,
+            };
+            let before_end = match range.end_bound() {
+                Bound::Included(end) => n.key <= *end,
+                Bound::Excluded(end) => n.key < *end,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                Self::range_node(&n.left, range, keys);
+            }
+            if after_start && before_end {
+                keys.extend(std::iter::repeat_n(n.key.clone(), n.count));
+            }
+            if before_end {
+                Self::range_node(&n.right, range, keys);
+            }
+        }
+    }
+
+    // number of stored keys smaller than `key`, repeated keys once per occurrence
+    // one path down: a step right skips the left subtree and the node by their sizes
+    pub fn rank(&self, key: T) -> usize {
+        let mut rank = 0;
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let n = node.borrow();
+            if n.key < key {
+                rank += n.left.as_ref().map_or(0, |left| left.borrow().size) + n.count;
+                current = n.right.clone();
+            } else {
+                current = n.left.clone();
+            }
+        }
+        rank
+    }
+
+    // iterate keys in order, repeated keys are yielded once per occurrence
+    pub fn iter(&self) -> Iter<T, S> {
+        Iter::new(&self.root)
+    }
+
+    // walk the nodes root first, then the left and right subtrees
+    pub fn pre_order(&self) -> PreOrderIter<T, S> {
+        PreOrderIter { stack: self.root.iter().map(|root| (root.clone(), 0)).collect() }
+    }
+
+    // walk the nodes with both subtrees before the node itself
+    pub fn post_order(&self) -> PostOrderIter<T, S> {
+        PostOrderIter { stack: self.root.iter().map(|root| (root.clone(), 0, false)).collect() }
+    }
+
+    // walk the nodes level by level from the root down
+    pub fn level_order(&self) -> LevelOrderIter<T, S> {
+        LevelOrderIter { queue: self.root.iter().map(|root| (root.clone(), 0)).collect() }
+    }
+
+    // cursor on the first node whose key is not less than `value`
+    pub fn lower_bound_cursor(&mut self, value: T) -> CursorMut<'_, T, S> {
+        let mut current = self.root.clone();
+        let mut found = None;
+        while let Some(node) = current {
+            if node.borrow().key < value {
+                current = node.borrow().right.clone();
+            } else {
+                current = node.borrow().left.clone();
+                found = Some(node);
+            }
+        }
+        CursorMut { tree: self, current: found }
+    }
+
+    // find the node with the maximum key value in a subtree
+    fn max_value_node(node: Link<T, S>) -> Link<T, S> {
+        let mut current = node;
+        loop {
+            let right = current.borrow().right.clone();
+            match right {
+                Some(right_child) => current = right_child,
+                None => break,
+            }
+        }
+        current
+    }
+
+    // next node in order, walking up through parent pointers when there is no right subtree
+    fn successor(node: &Link<T, S>) -> Option<Link<T, S>> {
+        if let Some(right) = node.borrow().right.clone() {
+            return Some(Self::min_value_node(right));
+        }
+        let mut child = node.clone();
+        let mut parent = node.borrow().parent.clone();
+        while let Some(p) = parent {
+            let from_left = p.borrow().left.as_ref().is_some_and(|left| Rc::ptr_eq(left, &child));
+            if from_left {
+                return Some(p);
+            }
+            parent = p.borrow().parent.clone();
+            child = p;
+        }
+        None
+    }
+
+    // previous node in order, mirror of successor
+    fn predecessor(node: &Link<T, S>) -> Option<Link<T, S>> {
+        if let Some(left) = node.borrow().left.clone() {
+            return Some(Self::max_value_node(left));
+        }
+        let mut child = node.clone();
+        let mut parent = node.borrow().parent.clone();
+        while let Some(p) = parent {
+            let from_right = p.borrow().right.as_ref().is_some_and(|right| Rc::ptr_eq(right, &child));
+            if from_right {
+                return Some(p);
+            }
+            parent = p.borrow().parent.clone();
+            child = p;
+        }
+        None
+    }
+
+    // read-only handle on the node holding `value`
+    pub fn search(&self, value: T) -> Option<NodeRef<'_, T, S>> {
+        self.stats.record(|s| s.operations += 1);
+        self.search_link(&value).map(NodeRef::new)
+    }
+
+    // read-only handle on the root node
+    pub fn root_node(&self) -> Option<NodeRef<'_, T, S>> {
+        self.root.clone().map(NodeRef::new)
+    }
+
     // searching 
-    pub fn search(&self, value: T) -> Option<Link<T>> {
+    fn search_link(&self, value: &T) -> Option<Link<T, S>> {
         if let Some(node) = self.root.clone() {
-            match Self::find_node(node, value) {
+            match self.find_node(node, value) {
                 Some(n) => { return Some(n); },
                 None => {
                     //println!("Cannot find");
@@ -507,20 +1024,21 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
 
     }
     // find node recursively
-    fn find_node(node: Link<T>, value: T) ->Option<Link<T>>{
+    fn find_node(&self, node: Link<T, S>, value: &T) ->Option<Link<T, S>>{
+        self.stats.record(|s| s.nodes_visited += 1);
         let node_borrow = node.borrow();
-        if node_borrow.key < value {
+        if self.stats.compare(node_borrow.key < *value) {
             // on the right
             if let Some(right) = &node_borrow.right {
-                return Self::find_node(right.clone(), value);
+                return self.find_node(right.clone(), value);
             } else {
                 // no right child
                 return None;
             }
-        } else if node_borrow.key > value {
+        } else if self.stats.compare(node_borrow.key > *value) {
             // on the left
             if let Some(left) = &node_borrow.left {
-                return Self::find_node(left.clone(), value);
+                return self.find_node(left.clone(), value);
             } else {
                 // no left child
                 return None;
@@ -532,8 +1050,437 @@ impl<T: PartialOrd + Copy + std::fmt::Debug + std::fmt::Display> AVLTree<T>  {
         }
     }
 
+    // Start counting rotations, comparisons and visited nodes. Counting is off by default and
+    // keeps its totals while switched off, reset_stats clears them.
+    pub fn enable_stats(&self) {
+        self.stats.enable();
+    }
+
+    pub fn disable_stats(&self) {
+        self.stats.disable();
+    }
+
+    // totals since the tree was built or the counters were last reset
+    pub fn stats(&self) -> TreeStats {
+        self.stats.get()
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.reset();
+    }
+
+    // attach an observer for inserts, deletes, rotations and height changes, None detaches it
+    pub fn set_observer(&mut self, observer: Option<ObserverRef<T>>) {
+        self.observer.set(observer);
+    }
+
+    // Build the tree with exactly the shape written in bracket notation, e.g.
+    // `(4 (2 1 3) (6 _ 7))` (see `bracket::parse`). Heights come from the shape, so the result
+    // is checked with `check_invariants` and an unbalanced or unordered shape is reported as
+    // an error. A `value*count` node turns on multiset mode.
+    pub fn from_bracket(text: &str) -> Result<Self, String>
+    where
+        T: FromStr,
+    {
+        Self::from_shape(false, bracket::parse(text)?)
+    }
+
+    // the tree with exactly the shape of `root`, checked with `check_invariants`
+    fn from_shape(multiset: bool, root: Option<Box<BracketNode<T>>>) -> Result<Self, String> {
+        let mut tree = Self::with_summary();
+        tree.multiset = multiset;
+        if let Some(root) = root {
+            tree.root = Some(tree.build_node(*root, None)?);
+        }
+        tree.check_invariants().map_err(|error| format!("not a valid AVL tree: {}", error))?;
+        Ok(tree)
+    }
+
+    fn build_node(&mut self, parsed: BracketNode<T>, parent: Option<Link<T, S>>) -> Result<Link<T, S>, String> {
+        if parsed.red.is_some() {
+            return Err(format!("key {:?} has a color, AVL nodes have none", parsed.value));
+        }
+        let node = TreeNode::new(parsed.value);
+        self.count += 1;
+        self.multiset |= parsed.count > 1;
+        let left = match parsed.left {
+            Some(left) => Some(self.build_node(*left, Some(node.clone()))?),
+            None => None,
+        };
+        let right = match parsed.right {
+            Some(right) => Some(self.build_node(*right, Some(node.clone()))?),
+            None => None,
+        };
+        {
+            let mut n = node.borrow_mut();
+            n.count = parsed.count;
+            n.parent = parent;
+            n.left = left;
+            n.right = right;
+            n.update_height();
+        }
+        Ok(node)
+    }
+
+    // the tree in the binary format described in `codec`
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        T: Codec,
+    {
+        codec::encode_tree(TreeKind::Avl, self.multiset, self.iter())
+    }
+
+    // Load a tree written by `to_bytes`. The entries are stored sorted, so the tree is built
+    // directly from them in O(n) instead of inserting them one by one.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String>
+    where
+        T: Codec,
+    {
+        let (multiset, entries) = codec::decode_tree(TreeKind::Avl, bytes)?;
+        Ok(Self::from_entries(multiset, &entries))
+    }
+
+    // `[1, 2, 2, 5]`, the keys in order and repeated once per occurrence
+    pub fn to_json_array(&self) -> String {
+        interchange::values_to_json(self.iter())
+    }
+
+    // The exact shape as nested objects, missing children are null:
+    //     {"type": "avl", "multiset": false, "nodes": 3,
+    //      "root": {"value": 2, "count": 1, "height": 2, "left": {...}, "right": {...}}}
+    pub fn to_json_tree(&self) -> String {
+        Json::Object(vec![
+            ("type".to_string(), Json::String(TreeKind::Avl.id().to_string())),
+            ("multiset".to_string(), Json::Bool(self.multiset)),
+            ("nodes".to_string(), Json::Number(self.count.to_string())),
+            ("root".to_string(), Self::json_node(&self.root)),
+        ]).to_pretty_string()
+    }
+
+    fn json_node(node: &Option<Link<T, S>>) -> Json {
+        let node = match node {
+            Some(node) => node.borrow(),
+            None => return Json::Null,
+        };
+        Json::Object(vec![
+            ("value".to_string(), interchange::value_to_json(&node.key)),
+            ("count".to_string(), Json::Number(node.count.to_string())),
+            ("height".to_string(), Json::Number(node.height.to_string())),
+            ("left".to_string(), Self::json_node(&node.left)),
+            ("right".to_string(), Self::json_node(&node.right)),
+        ])
+    }
+
+    // `value,count` rows in key order
+    pub fn to_csv(&self) -> String {
+        interchange::values_to_csv(self.iter())
+    }
+
+    // Load either JSON form. A flat array has to be sorted and is built in O(n), repeated keys
+    // turn on multiset mode. A nested document keeps its shape and is checked like
+    // `from_bracket`, its heights are recomputed rather than read.
+    pub fn from_json(text: &str) -> Result<Self, String>
+    where
+        T: FromStr,
+    {
+        match interchange::parse_json_tree(text, TreeKind::Avl)? {
+            JsonTree::Values(entries) => Ok(Self::from_entries(entries.iter().any(|(_, count)| *count > 1), &entries)),
+            JsonTree::Shape { multiset, root } => Self::from_shape(multiset, root),
+        }
+    }
+
+    // load what `to_csv` wrote, the rows have to be sorted
+    pub fn from_csv(text: &str) -> Result<Self, String>
+    where
+        T: FromStr,
+    {
+        let entries = interchange::parse_csv_tree(text)?;
+        Ok(Self::from_entries(entries.iter().any(|(_, count)| *count > 1), &entries))
+    }
+
+    fn from_entries(multiset: bool, entries: &[(T, usize)]) -> Self {
+        let mut tree = Self::with_summary();
+        tree.multiset = multiset;
+        tree.count = entries.len() as u32;
+        tree.root = Self::build_sorted(entries, None);
+        tree
+    }
+
+    // balanced subtree from strictly increasing keys with their counts, the middle one is the root
+    fn build_sorted(entries: &[(T, usize)], parent: Option<&Link<T, S>>) -> Option<Link<T, S>> {
+        if entries.is_empty() {
+            return None;
+        }
+        let mid = entries.len() / 2;
+        let (key, count) = entries[mid].clone();
+        let node = TreeNode::new(key);
+        let left = Self::build_sorted(&entries[..mid], Some(&node));
+        let right = Self::build_sorted(&entries[mid + 1..], Some(&node));
+        {
+            let mut n = node.borrow_mut();
+            n.count = count;
+            n.parent = parent.cloned();
+            n.left = left;
+            n.right = right;
+            n.update_height();
+        }
+        Some(node)
+    }
+
+    // Walk the whole tree and report the first broken AVL property: key order, parent links,
+    // stored heights, balance factors, occurrence counts and the node count.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if let Some(root) = &self.root {
+            if root.borrow().parent.is_some() {
+                return Err(format!("root {:?} has a parent", root.borrow().key));
+            }
+        }
+        let (_, nodes) = Self::check_node(&self.root, None, None)?;
+        if nodes != self.count as usize {
+            return Err(format!("tree holds {} nodes but counts {}", nodes, self.count));
+        }
+        Ok(())
+    }
+
+    // height and node count of a subtree whose keys must lie strictly between `low` and `high`
+    fn check_node(node: &Option<Link<T, S>>, low: Option<T>, high: Option<T>) -> Result<(i32, usize), String> {
+        let node = match node {
+            Some(node) => node,
+            None => return Ok((0, 0)),
+        };
+        let n = node.borrow();
+        if low.as_ref().is_some_and(|low| n.key <= *low) || high.as_ref().is_some_and(|high| n.key >= *high) {
+            return Err(format!("key {:?} is out of order", n.key));
+        }
+        if n.count == 0 {
+            return Err(format!("key {:?} has no occurrences", n.key));
+        }
+        for child in [&n.left, &n.right].into_iter().flatten() {
+            let parent_ok = child.borrow().parent.as_ref().is_some_and(|p| Rc::ptr_eq(p, node));
+            if !parent_ok {
+                return Err(format!("child {:?} of {:?} has a wrong parent link", child.borrow().key, n.key));
+            }
+        }
+        let size = n.left.as_ref().map_or(0, |c| c.borrow().size) + n.count + n.right.as_ref().map_or(0, |c| c.borrow().size);
+        if n.size != size {
+            return Err(format!("key {:?} stores subtree size {} instead of {}", n.key, n.size, size));
+        }
+        let (left_height, left_nodes) = Self::check_node(&n.left, low, Some(n.key.clone()))?;
+        let (right_height, right_nodes) = Self::check_node(&n.right, Some(n.key.clone()), high)?;
+        let height = 1 + max(left_height, right_height);
+        if n.height != height {
+            return Err(format!("key {:?} stores height {} instead of {}", n.key, n.height, height));
+        }
+        if (left_height - right_height).abs() > 1 {
+            return Err(format!("key {:?} has balance factor {}", n.key, left_height - right_height));
+        }
+        Ok((height, left_nodes + right_nodes + 1))
+    }
+}
+
+// the horizontal layout, `to_pretty_string` gives the others
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display, S: Summary<T>> fmt::Display for AVLTree<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_pretty(f, Layout::Horizontal)
+    }
+}
+
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display, S: Summary<T>> OrderedSet<T> for AVLTree<T, S> {
+    fn new() -> Self {
+        Self::with_summary()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        if self.search_link(&value).is_some() {
+            self.stats.record(|s| s.operations += 1);
+            return false;
+        }
+        AVLTree::insert(self, value);
+        true
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        if self.search_link(&value).is_none() {
+            self.stats.record(|s| s.operations += 1);
+            return false;
+        }
+        AVLTree::delete(self, value);
+        true
+    }
+
+    fn contains(&self, value: T) -> bool {
+        self.stats.record(|s| s.operations += 1);
+        self.search_link(&value).is_some()
+    }
+
+    fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    fn is_empty(&self) -> bool {
+        self.check_empty()
+    }
+
+    fn height(&self) -> usize {
+        self.tree_height() as usize
+    }
+
+    fn leaves(&self) -> usize {
+        self.count_leaves()
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        AVLTree::check_invariants(self)
+    }
 }
 
+// lets `DurableTree` keep this tree on disk, snapshots use the binary format of `to_bytes`
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display + Codec, S: Summary<T>> SnapshotTree<T> for AVLTree<T, S> {
+    fn snapshot_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
 
+    fn restore(bytes: &[u8]) -> Result<Self, String> {
+        Self::from_bytes(bytes)
+    }
+}
+
+
+// read-only view of a node, it borrows the tree so the structure cannot change while it is held
+#[derive(Clone)]
+pub struct NodeRef<'a, T, S = ()> {
+    node: Link<T, S>,
+    tree: PhantomData<&'a AVLTree<T, S>>,
+}
 
+impl<'a, T: PartialOrd + Clone + std::fmt::Debug, S: Summary<T>> NodeRef<'a, T, S> {
+    fn new(node: Link<T, S>) -> Self {
+        NodeRef { node, tree: PhantomData }
+    }
+
+    pub fn key(&self) -> T {
+        self.node.borrow().key.clone()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.node.borrow().height
+    }
 
+    pub fn balance_factor(&self) -> i32 {
+        self.node.borrow().balance_factor()
+    }
+
+    // number of occurrences stored in the node, always 1 outside multiset mode
+    pub fn count(&self) -> usize {
+        self.node.borrow().count
+    }
+
+    // summary of the subtree rooted at this node
+    pub fn summary(&self) -> S {
+        self.node.borrow().summary.clone()
+    }
+
+    pub fn left(&self) -> Option<NodeRef<'a, T, S>> {
+        self.node.borrow().left.clone().map(NodeRef::new)
+    }
+
+    pub fn right(&self) -> Option<NodeRef<'a, T, S>> {
+        self.node.borrow().right.clone().map(NodeRef::new)
+    }
+
+    pub fn parent(&self) -> Option<NodeRef<'a, T, S>> {
+        self.node.borrow().parent.clone().map(NodeRef::new)
+    }
+}
+
+// mutable cursor over the nodes of an AVL tree
+// `current` is None on the ghost position that sits past the last and before the first node
+pub struct CursorMut<'a, T, S = ()> {
+    tree: &'a mut AVLTree<T, S>,
+    current: Option<Link<T, S>>,
+}
+
+impl<'a, T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display, S: Summary<T>> CursorMut<'a, T, S> {
+    // key under the cursor, None on the ghost position
+    pub fn peek(&self) -> Option<T> {
+        self.current.as_ref().map(|node| node.borrow().key.clone())
+    }
+
+    // node after the cursor, the first node when sitting on the ghost position
+    fn next_node(&self) -> Option<Link<T, S>> {
+        match &self.current {
+            Some(node) => AVLTree::successor(node),
+            None => self.tree.root.clone().map(AVLTree::min_value_node),
+        }
+    }
+
+    // node before the cursor, the last node when sitting on the ghost position
+    fn prev_node(&self) -> Option<Link<T, S>> {
+        match &self.current {
+            Some(node) => AVLTree::predecessor(node),
+            None => self.tree.root.clone().map(AVLTree::max_value_node),
+        }
+    }
+
+    // step to the next node, from the last node the cursor moves to the ghost position
+    pub fn move_next(&mut self) {
+        self.current = self.next_node();
+    }
+
+    // step to the previous node, from the first node the cursor moves to the ghost position
+    pub fn move_prev(&mut self) {
+        self.current = self.prev_node();
+    }
+
+    fn next_key(&self) -> Option<T> {
+        self.next_node().map(|node| node.borrow().key.clone())
+    }
+
+    fn prev_key(&self) -> Option<T> {
+        self.prev_node().map(|node| node.borrow().key.clone())
+    }
+
+    // check that `low < value < high`, equal keys are allowed in multiset mode
+    fn fits(&self, low: Option<T>, value: &T, high: Option<T>) -> bool {
+        let above = |bound: T| if self.tree.multiset { bound <= *value } else { bound < *value };
+        let below = |bound: T| if self.tree.multiset { *value <= bound } else { *value < bound };
+        low.is_none_or(above) && high.is_none_or(below)
+    }
+
+    // insert right before the cursor, fails if the key does not belong there
+    // the cursor keeps pointing at the same node
+    pub fn insert_before(&mut self, value: T) -> bool {
+        if !self.fits(self.prev_key(), &value, self.peek()) {
+            return false;
+        }
+        self.tree.insert(value);
+        true
+    }
+
+    // insert right after the cursor, fails if the key does not belong there
+    // the cursor keeps pointing at the same node
+    pub fn insert_after(&mut self, value: T) -> bool {
+        if !self.fits(self.peek(), &value, self.next_key()) {
+            return false;
+        }
+        self.tree.insert(value);
+        true
+    }
+
+    // remove the node under the cursor with all of its occurrences and move to the next node
+    pub fn remove_current(&mut self) -> Option<T> {
+        let key = self.peek()?;
+        let next = self.next_key();
+        self.tree.remove_all(key.clone());
+        self.current = next.and_then(|next| self.tree.search_link(&next));
+        Some(key)
+    }
+}
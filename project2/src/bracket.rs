@@ -0,0 +1,112 @@
+use std::str::FromStr;
+
+// A node read from the bracket notation, before it is turned into a tree node.
+pub struct BracketNode<T> {
+    pub value: T,
+    // occurrences of the value, more than 1 only in multiset trees
+    pub count: usize,
+    // Some(true) for red, Some(false) for black, None when no color was written
+    pub red: Option<bool>,
+    pub left: Option<Box<BracketNode<T>>>,
+    pub right: Option<Box<BracketNode<T>>>,
+}
+
+// Parse a tree written as
+//     tree := `_` | node | `(` node `)` | `(` node tree tree `)`
+//     node := [`R` | `B`] value [`*` count] [`:R` | `:B`]
+// so `(B4 (R2 B1 B3) B6)` is a red-black tree and `(4 (2 1 3) (6 _ 7))` an AVL tree; `_` is a
+// missing child and a node with a single child has to name the other one as `_`. The color may
+// also follow the value, which is how the bracket layout of `pretty` writes red-black trees.
+// `_` on its own is the empty tree. Errors give the column the problem was found at.
+pub fn parse<T: FromStr>(text: &str) -> Result<Option<Box<BracketNode<T>>>, String> {
+    let mut parser = Parser { text: text.chars().collect(), position: 0 };
+    let tree = parser.tree()?;
+    match parser.next_token() {
+        None => Ok(tree),
+        Some((column, token)) => Err(format!("column {}: unexpected `{}` after the tree", column, token)),
+    }
+}
+
+struct Parser {
+    text: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    // the next token with its 1-based column: `(`, `)` or a run of other non-space characters
+    fn next_token(&mut self) -> Option<(usize, String)> {
+        while self.text.get(self.position).is_some_and(|c| c.is_whitespace()) {
+            self.position += 1;
+        }
+        let start = self.position;
+        match self.text.get(start)? {
+            '(' | ')' => self.position += 1,
+            _ => {
+                while self.text.get(self.position).is_some_and(|c| !c.is_whitespace() && *c != '(' && *c != ')') {
+                    self.position += 1;
+                }
+            },
+        }
+        Some((start + 1, self.text[start..self.position].iter().collect()))
+    }
+
+    fn tree<T: FromStr>(&mut self) -> Result<Option<Box<BracketNode<T>>>, String> {
+        let (column, token) = self.next_token().ok_or("unexpected end of input, expected a tree")?;
+        match token.as_str() {
+            "_" => Ok(None),
+            ")" => Err(format!("column {}: unexpected `)`, expected a tree", column)),
+            "(" => {
+                let (column, token) = self.next_token().ok_or("unexpected end of input, expected a node")?;
+                if token == "(" || token == ")" || token == "_" {
+                    return Err(format!("column {}: expected a node, found `{}`", column, token));
+                }
+                let mut node = node(column, &token)?;
+                // a lone node in parentheses, `(B6)`
+                let save = self.position;
+                if let Some((_, token)) = self.next_token() {
+                    if token == ")" {
+                        return Ok(Some(Box::new(node)));
+                    }
+                }
+                self.position = save;
+                node.left = self.tree()?;
+                node.right = self.tree()?;
+                match self.next_token() {
+                    Some((_, token)) if token == ")" => Ok(Some(Box::new(node))),
+                    Some((column, token)) => Err(format!("column {}: expected `)` after the two children, found `{}`", column, token)),
+                    None => Err("unexpected end of input, expected `)`".to_string()),
+                }
+            },
+            _ => node(column, &token).map(|node| Some(Box::new(node))),
+        }
+    }
+}
+
+// split `token` into color, value and count
+fn node<T: FromStr>(column: usize, token: &str) -> Result<BracketNode<T>, String> {
+    let mut rest = token;
+    let mut red = None;
+    if let Some(stripped) = rest.strip_suffix(":R") {
+        red = Some(true);
+        rest = stripped;
+    } else if let Some(stripped) = rest.strip_suffix(":B") {
+        red = Some(false);
+        rest = stripped;
+    } else if rest.len() > 1 && (rest.starts_with('R') || rest.starts_with('B')) {
+        red = Some(rest.starts_with('R'));
+        rest = &rest[1..];
+    }
+
+    let (value, count) = match rest.split_once('*') {
+        Some((value, count)) => match count.parse::<usize>() {
+            Ok(count) if count > 0 => (value, count),
+            _ => return Err(format!("column {}: `{}` is not a valid count in `{}`", column, count, token)),
+        },
+        None => (rest, 1),
+    };
+    match value.parse() {
+        Ok(value) => Ok(BracketNode { value, count, red, left: None, right: None }),
+        Err(_) if value == token => Err(format!("column {}: `{}` is not a valid value", column, value)),
+        Err(_) => Err(format!("column {}: `{}` is not a valid value in `{}`", column, value, token)),
+    }
+}
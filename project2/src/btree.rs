@@ -0,0 +1,372 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+
+#[derive(Debug)]
+struct BTreeNode<T> {
+    keys: Vec<T>,
+    // empty for a leaf, one more than `keys` otherwise
+    children: Vec<BTreeNode<T>>,
+}
+
+impl<T: PartialOrd> BTreeNode<T> {
+    fn leaf() -> Self {
+        BTreeNode { keys: Vec::new(), children: Vec::new() }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    // index of the first key not smaller than `value`, and whether it is `value` itself
+    fn find(&self, value: &T) -> (usize, bool) {
+        let index = self.keys.partition_point(|key| key < value);
+        let found = self.keys.get(index).is_some_and(|key| key.partial_cmp(value) == Some(Ordering::Equal));
+        (index, found)
+    }
+}
+
+// B-tree of minimum degree `B`: every node but the root holds between B - 1 and 2B - 1 keys
+// in one contiguous Vec, and all leaves sit on the same level. Larger orders give shallower
+// trees with fewer, bigger nodes, which is what makes them friendly to caches.
+// Insert and delete are single top-down passes that split full nodes and refill thin ones on
+// the way down, so they never have to walk back up.
+#[derive(Debug)]
+pub struct BTree<T, const B: usize> {
+    root: BTreeNode<T>,
+    len: usize,
+}
+
+impl<T: PartialOrd + Clone + Debug, const B: usize> BTree<T, B> {
+    pub fn new() -> Self {
+        assert!(B >= 2, "a B-tree needs a minimum degree of at least 2");
+        BTree { root: BTreeNode::leaf(), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut node = &self.root;
+        loop {
+            let (index, found) = node.find(value);
+            if found {
+                return true;
+            }
+            match node.children.get(index) {
+                Some(child) => node = child,
+                None => return false,
+            }
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+        if self.root.keys.len() == 2 * B - 1 {
+            // the tree grows at the top, the old root becomes the first child of a new one
+            let old_root = std::mem::replace(&mut self.root, BTreeNode::leaf());
+            self.root.children.push(old_root);
+            Self::split_child(&mut self.root, 0);
+        }
+        Self::insert_non_full(&mut self.root, value);
+        self.len += 1;
+        true
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        Self::delete_from(&mut self.root, value);
+        if self.root.keys.is_empty() && !self.root.is_leaf() {
+            // the tree shrinks at the top
+            self.root = self.root.children.pop().unwrap();
+        }
+        self.len -= 1;
+        true
+    }
+
+    // number of levels, every leaf is at the same depth
+    pub fn height(&self) -> usize {
+        if self.len == 0 {
+            return 0;
+        }
+        let mut height = 1;
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+            height += 1;
+        }
+        height
+    }
+
+    // number of leaf nodes, each holding up to 2B - 1 keys
+    pub fn leaves(&self) -> usize {
+        fn leaves<T: PartialOrd>(node: &BTreeNode<T>) -> usize {
+            if node.is_leaf() {
+                1
+            } else {
+                node.children.iter().map(|child| leaves(child)).sum()
+            }
+        }
+        if self.len == 0 { 0 } else { leaves(&self.root) }
+    }
+
+    // keys in order
+    pub fn to_vec(&self) -> Vec<T> {
+        fn collect<T: Clone>(node: &BTreeNode<T>, out: &mut Vec<T>) {
+            for (index, key) in node.keys.iter().enumerate() {
+                if let Some(child) = node.children.get(index) {
+                    collect(child, out);
+                }
+                out.push(key.clone());
+            }
+            if let Some(last) = node.children.get(node.keys.len()) {
+                collect(last, out);
+            }
+        }
+        let mut out = Vec::with_capacity(self.len);
+        collect(&self.root, &mut out);
+        out
+    }
+
+    pub fn print_struct(&self) {
+        fn print<T: Debug>(node: &BTreeNode<T>, depth: usize, position: &str) {
+            println!("{}{}{:?}", " ".repeat(depth * 2), position, node.keys);
+            for (index, child) in node.children.iter().enumerate() {
+                print(child, depth + 1, &format!("{}: ", index));
+            }
+        }
+        println!("B-Tree Structure (B = {}):", B);
+        print(&self.root, 0, "Root: ");
+    }
+
+    // Report the first broken property: key order inside and across nodes, key counts between
+    // B - 1 and 2B - 1 outside the root, one child more than keys and all leaves on one level.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.len > 0 && self.root.keys.is_empty() {
+            return Err("the root of a non-empty tree has no keys".to_string());
+        }
+        let mut leaf_depth = None;
+        let count = Self::check_node(&self.root, None, None, true, 0, &mut leaf_depth)?;
+        if count != self.len {
+            return Err(format!("tree holds {} keys but counts {}", count, self.len));
+        }
+        Ok(())
+    }
+
+    fn check_node(
+        node: &BTreeNode<T>,
+        low: Option<&T>,
+        high: Option<&T>,
+        is_root: bool,
+        depth: usize,
+        leaf_depth: &mut Option<usize>,
+    ) -> Result<usize, String> {
+        let keys = &node.keys;
+        if keys.len() > 2 * B - 1 || (!is_root && keys.len() < B - 1) {
+            return Err(format!("node {:?} holds {} keys", keys, keys.len()));
+        }
+        if let Some(pair) = keys.windows(2).find(|pair| pair[0] >= pair[1]) {
+            return Err(format!("key {:?} is out of order in node {:?}", pair[1], keys));
+        }
+        if let (Some(low), Some(first)) = (low, keys.first()) {
+            if first <= low {
+                return Err(format!("key {:?} is out of order", first));
+            }
+        }
+        if let (Some(high), Some(last)) = (high, keys.last()) {
+            if last >= high {
+                return Err(format!("key {:?} is out of order", last));
+            }
+        }
+        if node.is_leaf() {
+            match *leaf_depth {
+                None => *leaf_depth = Some(depth),
+                Some(expected) if expected != depth => {
+                    return Err(format!("leaf {:?} is at depth {} instead of {}", keys, depth, expected));
+                },
+                _ => {},
+            }
+            return Ok(keys.len());
+        }
+        if node.children.len() != keys.len() + 1 {
+            return Err(format!("node {:?} has {} children", keys, node.children.len()));
+        }
+        let mut count = keys.len();
+        for (index, child) in node.children.iter().enumerate() {
+            let child_low = if index == 0 { low } else { keys.get(index - 1) };
+            let child_high = if index == keys.len() { high } else { keys.get(index) };
+            count += Self::check_node(child, child_low, child_high, false, depth + 1, leaf_depth)?;
+        }
+        Ok(count)
+    }
+
+    // split the full child `index` of `node` around its middle key, which moves up into `node`
+    fn split_child(node: &mut BTreeNode<T>, index: usize) {
+        let child = &mut node.children[index];
+        let right_keys = child.keys.split_off(B);
+        let middle = child.keys.pop().unwrap();
+        let right_children = if child.is_leaf() { Vec::new() } else { child.children.split_off(B) };
+        let right = BTreeNode { keys: right_keys, children: right_children };
+        node.keys.insert(index, middle);
+        node.children.insert(index + 1, right);
+    }
+
+    fn insert_non_full(node: &mut BTreeNode<T>, value: T) {
+        let (mut index, _) = node.find(&value);
+        if node.is_leaf() {
+            node.keys.insert(index, value);
+            return;
+        }
+        if node.children[index].keys.len() == 2 * B - 1 {
+            Self::split_child(node, index);
+            if value > node.keys[index] {
+                index += 1;
+            }
+        }
+        Self::insert_non_full(&mut node.children[index], value);
+    }
+
+    // `node` has at least B keys unless it is the root, `value` is in its subtree
+    fn delete_from(node: &mut BTreeNode<T>, value: &T) {
+        let (index, found) = node.find(value);
+        if node.is_leaf() {
+            node.keys.remove(index);
+            return;
+        }
+        if found {
+            if node.children[index].keys.len() >= B {
+                // replace the key with its predecessor
+                let predecessor = Self::max_key(&node.children[index]).clone();
+                Self::delete_from(&mut node.children[index], &predecessor);
+                node.keys[index] = predecessor;
+            } else if node.children[index + 1].keys.len() >= B {
+                let successor = Self::min_key(&node.children[index + 1]).clone();
+                Self::delete_from(&mut node.children[index + 1], &successor);
+                node.keys[index] = successor;
+            } else {
+                // both neighbours are thin, merge them around the key and delete it down there
+                Self::merge_children(node, index);
+                Self::delete_from(&mut node.children[index], value);
+            }
+        } else {
+            let index = Self::fill_child(node, index);
+            Self::delete_from(&mut node.children[index], value);
+        }
+    }
+
+    // Make sure child `index` has at least B keys before descending into it, borrowing a key
+    // through the parent from a sibling or merging with one. Returns where the child ended up.
+    fn fill_child(node: &mut BTreeNode<T>, index: usize) -> usize {
+        if node.children[index].keys.len() >= B {
+            return index;
+        }
+        if index > 0 && node.children[index - 1].keys.len() >= B {
+            let (before, after) = node.children.split_at_mut(index);
+            let (left, child) = (&mut before[index - 1], &mut after[0]);
+            let separator = std::mem::replace(&mut node.keys[index - 1], left.keys.pop().unwrap());
+            child.keys.insert(0, separator);
+            if let Some(grandchild) = left.children.pop() {
+                child.children.insert(0, grandchild);
+            }
+            index
+        } else if index + 1 < node.children.len() && node.children[index + 1].keys.len() >= B {
+            let (before, after) = node.children.split_at_mut(index + 1);
+            let (child, right) = (&mut before[index], &mut after[0]);
+            let separator = std::mem::replace(&mut node.keys[index], right.keys.remove(0));
+            child.keys.push(separator);
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+            index
+        } else if index + 1 < node.children.len() {
+            Self::merge_children(node, index);
+            index
+        } else {
+            Self::merge_children(node, index - 1);
+            index - 1
+        }
+    }
+
+    // merge child `index + 1` and the key between them into child `index`
+    fn merge_children(node: &mut BTreeNode<T>, index: usize) {
+        let right = node.children.remove(index + 1);
+        let separator = node.keys.remove(index);
+        let left = &mut node.children[index];
+        left.keys.push(separator);
+        left.keys.extend(right.keys);
+        left.children.extend(right.children);
+    }
+
+    fn min_key(node: &BTreeNode<T>) -> &T {
+        let mut current = node;
+        while let Some(first) = current.children.first() {
+            current = first;
+        }
+        &current.keys[0]
+    }
+
+    fn max_key(node: &BTreeNode<T>) -> &T {
+        let mut current = node;
+        while let Some(last) = current.children.last() {
+            current = last;
+        }
+        current.keys.last().unwrap()
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug, const B: usize> Default for BTree<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug, const B: usize> OrderedSet<T> for BTree<T, B> {
+    fn new() -> Self {
+        BTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        BTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        BTree::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        BTree::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn height(&self) -> usize {
+        BTree::height(self)
+    }
+
+    fn leaves(&self) -> usize {
+        BTree::leaves(self)
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        BTree::to_vec(self)
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        BTree::check_invariants(self)
+    }
+}
@@ -0,0 +1,235 @@
+use std::fmt::Display;
+use std::{fs, io};
+use std::path::PathBuf;
+use std::str::FromStr;
+use project::avl_tree::AVLTree;
+use crate::commands::{self, CommandTree, COMMANDS};
+use project::redblack_tree::RedBlackTree;
+use crate::repl;
+
+// Non-interactive mode, used when the program gets arguments:
+//     project --tree rb --type i64 --ops "insert 5 3 8; delete 3; height; print"
+//     project --tree avl --script tests/scripts/rotations.txt
+//     project --tree rb --type f32 --repl
+// Every command prints one JSON object on its own line to stdout, errors go to stderr;
+// the prompt of --repl answers in words instead, see repl.rs.
+
+// all commands ran
+pub const EXIT_OK: i32 = 0;
+// a command failed, e.g. `check` or an assertion; the commands before it ran
+pub const EXIT_FAILED: i32 = 1;
+// the arguments or the commands could not be understood, nothing ran
+pub const EXIT_USAGE: i32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeChoice {
+    Avl,
+    RedBlack,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    I32,
+    F32,
+    I64,
+    Str,
+}
+
+// where the commands come from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    Ops(String),
+    // a file with one command per line, `-` reads standard input
+    Script(String),
+    // typed at a prompt, the history is kept in the file
+    Repl(Option<PathBuf>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Args {
+    pub tree: TreeChoice,
+    pub value_type: ValueType,
+    pub source: Source,
+}
+
+pub fn usage() -> String {
+    let mut text = String::from(
+        "usage: project [--tree avl|rb] [--type i32|f32|i64|string] --ops \"COMMAND; COMMAND; ...\"\n\
+         \x20      project [--tree avl|rb] [--type i32|f32|i64|string] --script FILE\n\
+         \x20      project [--tree avl|rb] [--type i32|f32|i64|string] --repl [--history FILE]\n\
+         \n\
+         Without arguments the interactive menu starts.\n\
+         \n\
+         options:\n\
+         \x20 --tree avl|rb            the tree to use (default avl)\n\
+         \x20 --type TYPE              the type of the values: i32, f32, i64 or string (default i32)\n\
+         \x20 --ops COMMANDS           the commands to run, separated by `;`\n\
+         \x20 --script FILE            run the commands in FILE, one per line, `#` starts a comment;\n\
+         \x20                          `-` reads them from standard input\n\
+         \x20 --repl                   type the commands at a prompt, `help` lists them\n\
+         \x20 --history FILE           where --repl keeps the lines entered, `none` keeps them nowhere\n\
+         \x20                          (default ~/.balanced_tree_history)\n\
+         \x20 -h, --help               show this text\n\
+         \n\
+         commands:\n");
+    for (name, arguments, description) in COMMANDS {
+        text.push_str(&format!("  {:<24} {}\n", format!("{} {}", name, arguments).trim_end(), description));
+    }
+    text.push_str(&format!(
+        "\nEach command prints one JSON object per line. Exit status: {} when all commands ran,\n\
+         {} when a command failed, {} for invalid arguments or commands.\n",
+        EXIT_OK, EXIT_FAILED, EXIT_USAGE));
+    text
+}
+
+// Ok(None) asks for the help text
+pub fn parse_args(args: &[String]) -> Result<Option<Args>, String> {
+    let mut tree = TreeChoice::Avl;
+    let mut value_type = ValueType::I32;
+    let mut source = None;
+    let mut history = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        // `--tree=rb` and `--tree rb` are the same
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || inline.clone().or_else(|| args.next().cloned()).ok_or(format!("{} needs a value", flag));
+        match flag {
+            "-h" | "--help" => return Ok(None),
+            "--tree" => {
+                tree = match value()?.as_str() {
+                    "avl" => TreeChoice::Avl,
+                    "rb" | "red-black" => TreeChoice::RedBlack,
+                    other => return Err(format!("unknown tree `{}`, expected avl or rb", other)),
+                }
+            },
+            "--type" => {
+                value_type = match value()?.as_str() {
+                    "i32" => ValueType::I32,
+                    "f32" => ValueType::F32,
+                    "i64" => ValueType::I64,
+                    "string" => ValueType::Str,
+                    other => return Err(format!("unknown type `{}`, expected i32, f32, i64 or string", other)),
+                }
+            },
+            "--ops" | "--script" | "--repl" => {
+                if source.is_some() {
+                    return Err("give one of --ops, --script and --repl, once".to_string());
+                }
+                source = Some(match flag {
+                    "--ops" => Source::Ops(value()?),
+                    "--script" => Source::Script(value()?),
+                    _ if inline.is_some() => return Err("--repl takes no value".to_string()),
+                    _ => Source::Repl(None),
+                });
+            },
+            "--history" => history = Some(value()?),
+            other => return Err(format!("unknown argument `{}`", other)),
+        }
+    }
+    let source = match (source, history) {
+        (None, _) => return Err("--ops, --script or --repl is missing".to_string()),
+        (Some(Source::Repl(_)), Some(path)) if path == "none" => Source::Repl(None),
+        (Some(Source::Repl(_)), Some(path)) => Source::Repl(Some(PathBuf::from(path))),
+        (Some(Source::Repl(_)), None) => Source::Repl(Some(repl::default_history_path())),
+        (Some(_), Some(_)) => return Err("--history only goes with --repl".to_string()),
+        (Some(source), None) => source,
+    };
+    Ok(Some(Args { tree, value_type, source }))
+}
+
+// run the program with `args`, the arguments after the program name; returns the exit status
+pub fn run(args: &[String]) -> i32 {
+    let args = match parse_args(args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{}", usage());
+            return EXIT_OK;
+        },
+        Err(error) => {
+            eprintln!("error: {}", error);
+            eprintln!("run with --help for the usage");
+            return EXIT_USAGE;
+        },
+    };
+    if let Source::Repl(history) = args.source {
+        let label = match args.tree {
+            TreeChoice::Avl => "avl",
+            TreeChoice::RedBlack => "rb",
+        };
+        return match args.value_type {
+            ValueType::I32 if args.tree == TreeChoice::Avl => repl::run::<i32, AVLTree<i32>>(label, history),
+            ValueType::F32 if args.tree == TreeChoice::Avl => repl::run::<f32, AVLTree<f32>>(label, history),
+            ValueType::I64 if args.tree == TreeChoice::Avl => repl::run::<i64, AVLTree<i64>>(label, history),
+            ValueType::Str if args.tree == TreeChoice::Avl => repl::run::<String, AVLTree<String>>(label, history),
+            ValueType::I32 => repl::run::<i32, RedBlackTree<i32>>(label, history),
+            ValueType::F32 => repl::run::<f32, RedBlackTree<f32>>(label, history),
+            ValueType::I64 => repl::run::<i64, RedBlackTree<i64>>(label, history),
+            ValueType::Str => repl::run::<String, RedBlackTree<String>>(label, history),
+        };
+    }
+    let source = match &args.source {
+        Source::Ops(ops) => Ok((None, ops.clone())),
+        Source::Script(path) if path == "-" => io::read_to_string(io::stdin()).map(|text| (Some("<stdin>"), text)),
+        Source::Script(path) => fs::read_to_string(path).map(|text| (Some(path.as_str()), text)),
+        Source::Repl(_) => unreachable!("the prompt was started above"),
+    };
+    let (script, text) = match source {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("error: cannot read {}: {}", describe_source(&args.source), error);
+            return EXIT_USAGE;
+        },
+    };
+    match (args.tree, args.value_type) {
+        (TreeChoice::Avl, ValueType::I32) => run_text::<i32, AVLTree<i32>>(script, &text),
+        (TreeChoice::Avl, ValueType::F32) => run_text::<f32, AVLTree<f32>>(script, &text),
+        (TreeChoice::Avl, ValueType::I64) => run_text::<i64, AVLTree<i64>>(script, &text),
+        (TreeChoice::Avl, ValueType::Str) => run_text::<String, AVLTree<String>>(script, &text),
+        (TreeChoice::RedBlack, ValueType::I32) => run_text::<i32, RedBlackTree<i32>>(script, &text),
+        (TreeChoice::RedBlack, ValueType::F32) => run_text::<f32, RedBlackTree<f32>>(script, &text),
+        (TreeChoice::RedBlack, ValueType::I64) => run_text::<i64, RedBlackTree<i64>>(script, &text),
+        (TreeChoice::RedBlack, ValueType::Str) => run_text::<String, RedBlackTree<String>>(script, &text),
+    }
+}
+
+fn describe_source(source: &Source) -> &str {
+    match source {
+        Source::Ops(_) => "--ops",
+        Source::Script(path) if path == "-" => "standard input",
+        Source::Script(path) => path,
+        Source::Repl(_) => "the prompt",
+    }
+}
+
+// `script` names the script `text` came from, None for the --ops commands
+fn run_text<T, Tree>(script: Option<&str>, text: &str) -> i32
+where
+    T: Clone + Display + FromStr,
+    Tree: CommandTree<T>,
+{
+    let parsed = match script {
+        Some(name) => commands::parse_script::<T>(name, text),
+        None => commands::parse_commands::<T>(text),
+    };
+    let commands = match parsed {
+        Ok(commands) => commands,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return EXIT_USAGE;
+        },
+    };
+    let mut tree = Tree::new();
+    for (place, command) in &commands {
+        match commands::execute(&mut tree, command) {
+            Ok(output) => println!("{}", output.to_compact_string()),
+            Err(error) => {
+                eprintln!("error: {}: {} failed: {}", place, commands::command_name(command), error);
+                return EXIT_FAILED;
+            },
+        }
+    }
+    EXIT_OK
+}
@@ -0,0 +1,199 @@
+// Binary format shared by the AVL and red-black trees:
+//     magic  b"BTRE"
+//     u8     format version, VERSION
+//     u8     tree kind, see TreeKind
+//     u8     element encoding, the TAG of the element's Codec
+//     u8     flags, bit 0 set for a multiset
+//     u64    number of entries
+// followed by the entries in increasing order, each one an encoded element and, in a
+// multiset, a u64 occurrence count. All numbers are little-endian.
+
+pub const MAGIC: &[u8; 4] = b"BTRE";
+pub const VERSION: u8 = 1;
+
+const MULTISET_FLAG: u8 = 1;
+
+// How an element is written. `TAG` goes into the header so a file is only read back as the
+// type it was written with.
+pub trait Codec: Sized {
+    const TAG: u8;
+    fn encode(&self, out: &mut Vec<u8>);
+    // read one element from the front of `input` and advance it past the bytes used
+    fn decode(input: &mut &[u8]) -> Result<Self, String>;
+}
+
+// the first `n` bytes of `input`, `input` moves past them
+fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], String> {
+    if input.len() < n {
+        return Err(format!("unexpected end of data, {} more bytes needed", n - input.len()));
+    }
+    let (bytes, rest) = input.split_at(n);
+    *input = rest;
+    Ok(bytes)
+}
+
+// fixed-width numbers are their little-endian bytes
+macro_rules! number_codec {
+    ($($type:ty => $tag:expr),* $(,)?) => {
+        $(
+            impl Codec for $type {
+                const TAG: u8 = $tag;
+
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(input: &mut &[u8]) -> Result<Self, String> {
+                    let bytes = take(input, std::mem::size_of::<$type>())?;
+                    Ok(<$type>::from_le_bytes(bytes.try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+number_codec! {
+    i8 => 0x01, i16 => 0x02, i32 => 0x03, i64 => 0x04, i128 => 0x05,
+    u8 => 0x11, u16 => 0x12, u32 => 0x13, u64 => 0x14, u128 => 0x15,
+    f32 => 0x21, f64 => 0x22,
+}
+
+// pointer-sized integers are always written as 64 bits so files move between platforms
+impl Codec for isize {
+    const TAG: u8 = 0x06;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as i64).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, String> {
+        let value = i64::decode(input)?;
+        isize::try_from(value).map_err(|_| format!("{} does not fit in an isize", value))
+    }
+}
+
+impl Codec for usize {
+    const TAG: u8 = 0x16;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, String> {
+        let value = u64::decode(input)?;
+        usize::try_from(value).map_err(|_| format!("{} does not fit in a usize", value))
+    }
+}
+
+// a u32 byte length followed by the UTF-8 bytes
+impl Codec for String {
+    const TAG: u8 = 0x31;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, String> {
+        let len = u32::decode(input)? as usize;
+        let bytes = take(input, len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| "a string is not valid UTF-8".to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeKind {
+    Avl = 1,
+    RedBlack = 2,
+}
+
+impl TreeKind {
+    // short name used by the text formats
+    pub fn id(self) -> &'static str {
+        match self {
+            TreeKind::Avl => "avl",
+            TreeKind::RedBlack => "red-black",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TreeKind::Avl => "an AVL tree",
+            TreeKind::RedBlack => "a red-black tree",
+        }
+    }
+}
+
+// Write a tree whose in-order values are `values`; equal neighbours are grouped into one
+// entry with a count, so `values` has to be sorted.
+pub fn encode_tree<T: Codec + PartialEq>(kind: TreeKind, multiset: bool, values: impl Iterator<Item = T>) -> Vec<u8> {
+    let mut entries: Vec<(T, u64)> = Vec::new();
+    for value in values {
+        match entries.last_mut() {
+            Some((last, count)) if *last == value => *count += 1,
+            _ => entries.push((value, 1)),
+        }
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.push(kind as u8);
+    out.push(T::TAG);
+    out.push(if multiset { MULTISET_FLAG } else { 0 });
+    (entries.len() as u64).encode(&mut out);
+    for (value, count) in entries {
+        value.encode(&mut out);
+        if multiset {
+            count.encode(&mut out);
+        }
+    }
+    out
+}
+
+// Read what `encode_tree` wrote for a tree of `kind`. Returns the multiset flag and the
+// entries with their counts, checked to be strictly increasing.
+pub fn decode_tree<T: Codec + PartialOrd>(kind: TreeKind, bytes: &[u8]) -> Result<(bool, Vec<(T, usize)>), String> {
+    let mut input = bytes;
+    if take(&mut input, MAGIC.len()).ok() != Some(&MAGIC[..]) {
+        return Err("not a tree file, the header is missing".to_string());
+    }
+    let header = take(&mut input, 4)?;
+    let (version, file_kind, tag, flags) = (header[0], header[1], header[2], header[3]);
+    if version != VERSION {
+        return Err(format!("unsupported format version {}, expected {}", version, VERSION));
+    }
+    if file_kind != kind as u8 {
+        let found = [TreeKind::Avl, TreeKind::RedBlack].into_iter().find(|k| *k as u8 == file_kind);
+        return Err(match found {
+            Some(found) => format!("the file holds {}, not {}", found.name(), kind.name()),
+            None => format!("unknown tree kind {}", file_kind),
+        });
+    }
+    if tag != T::TAG {
+        return Err(format!("the elements are encoded with tag {:#04x}, expected {:#04x}", tag, T::TAG));
+    }
+    if flags & !MULTISET_FLAG != 0 {
+        return Err(format!("unknown flags {:#04x}", flags));
+    }
+    let multiset = flags & MULTISET_FLAG != 0;
+
+    let len = u64::decode(&mut input)?;
+    let mut entries: Vec<(T, usize)> = Vec::new();
+    for index in 0..len {
+        let value = T::decode(&mut input)?;
+        let count = if multiset { u64::decode(&mut input)? } else { 1 };
+        if count == 0 {
+            return Err(format!("entry {} has no occurrences", index));
+        }
+        if entries.last().is_some_and(|(last, _)| *last >= value) {
+            return Err(format!("entry {} is not larger than the one before it", index));
+        }
+        let count = usize::try_from(count).map_err(|_| format!("entry {} occurs too often", index))?;
+        entries.push((value, count));
+    }
+    if !input.is_empty() {
+        return Err(format!("{} unexpected bytes after the last entry", input.len()));
+    }
+    Ok((multiset, entries))
+}
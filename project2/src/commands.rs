@@ -0,0 +1,374 @@
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use project::avl_tree::AVLTree;
+use project::interchange::{self, Json};
+use project::ordered_set::OrderedSet;
+use project::pretty::Layout;
+use project::redblack_tree::RedBlackTree;
+
+// The operations the command-line mode, scripts and the menus share, e.g. `insert 1 2 3`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command<T> {
+    Insert(Vec<T>),
+    Delete(Vec<T>),
+    Contains(Vec<T>),
+    Height,
+    Leaves,
+    Len,
+    Empty,
+    InOrder,
+    // the values from the first to the second, both included
+    Range(T, T),
+    // how many values are smaller
+    Rank(T),
+    // the tree in the bracket layout, which `from_bracket` reads back
+    Print,
+    Check,
+    // the assertions fail the run when the tree does not match, for regression scripts
+    AssertHeight(usize),
+    AssertLeaves(usize),
+    AssertLen(usize),
+    AssertContains(Vec<T>),
+    AssertMissing(Vec<T>),
+    // the exact shape, as `print` shows it
+    AssertTree(String),
+}
+
+// What a command found out, shown as JSON by the command-line mode and as text by the menus.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome<T> {
+    Inserted { inserted: Vec<T>, existing: Vec<T> },
+    Deleted { deleted: Vec<T>, missing: Vec<T> },
+    Found { found: Vec<T>, missing: Vec<T> },
+    Height(usize),
+    Leaves(usize),
+    Len(usize),
+    Empty(bool),
+    Values(Vec<T>),
+    Range(Vec<T>),
+    Rank { value: T, rank: usize },
+    Tree(String),
+    // a check or an assertion held
+    Passed,
+}
+
+// name, arguments and what the command does, for the usage text
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    ("insert", "VALUE...", "add values, reports which were new and which were already there"),
+    ("delete", "VALUE...", "remove values, reports which were removed and which were missing"),
+    ("contains", "VALUE...", "reports which values are in the tree"),
+    ("height", "", "number of levels"),
+    ("leaves", "", "number of leaves"),
+    ("len", "", "number of stored values"),
+    ("empty", "", "whether the tree is empty"),
+    ("inorder", "", "the values in order"),
+    ("range", "LOW HIGH", "the values from LOW to HIGH, both included"),
+    ("rank", "VALUE", "how many values are smaller than VALUE"),
+    ("print", "", "the shape in bracket notation, e.g. (2 1 3)"),
+    ("check", "", "verify the tree invariants, fails when one is broken"),
+    ("assert-height", "N", "fail unless the tree has N levels"),
+    ("assert-leaves", "N", "fail unless the tree has N leaves"),
+    ("assert-len", "N", "fail unless the tree holds N values"),
+    ("assert-contains", "VALUE...", "fail unless all the values are in the tree"),
+    ("assert-missing", "VALUE...", "fail if any of the values is in the tree"),
+    ("assert-tree", "TREE", "fail unless `print` shows exactly TREE"),
+];
+
+// What the commands need from a tree besides the OrderedSet operations.
+pub trait CommandTree<T>: OrderedSet<T> {
+    fn render(&self, layout: Layout) -> String;
+    fn values_between(&self, low: T, high: T) -> Vec<T>;
+    fn count_below(&self, value: T) -> usize;
+}
+
+impl<T: PartialOrd + Clone + Debug + Display> CommandTree<T> for AVLTree<T> {
+    fn render(&self, layout: Layout) -> String {
+        self.to_pretty_string(layout)
+    }
+
+    fn values_between(&self, low: T, high: T) -> Vec<T> {
+        self.range(low..=high)
+    }
+
+    fn count_below(&self, value: T) -> usize {
+        self.rank(value)
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug + Display> CommandTree<T> for RedBlackTree<T> {
+    fn render(&self, layout: Layout) -> String {
+        self.to_pretty_string(layout)
+    }
+
+    fn values_between(&self, low: T, high: T) -> Vec<T> {
+        self.range(low..=high)
+    }
+
+    fn count_below(&self, value: T) -> usize {
+        self.rank(value)
+    }
+}
+
+// Parse one command such as `insert 1 2 3`. Values are separated by whitespace.
+pub fn parse_command<T: FromStr>(text: &str) -> Result<Command<T>, String> {
+    let mut words = text.split_whitespace();
+    let name = words.next().ok_or("empty command")?;
+    let arguments: Vec<&str> = words.collect();
+    let values = || -> Result<Vec<T>, String> {
+        if arguments.is_empty() {
+            return Err(format!("`{}` needs at least one value", name));
+        }
+        arguments.iter().map(|word| word.parse().map_err(|_| format!("`{}` is not a valid value", word))).collect()
+    };
+    // exactly `N` values
+    let fixed = |count: usize, what: &str| -> Result<Vec<T>, String> {
+        if arguments.len() != count {
+            return Err(format!("`{}` needs {}", name, what));
+        }
+        values()
+    };
+    let number = || -> Result<usize, String> {
+        match arguments[..] {
+            [word] => word.parse().map_err(|_| format!("`{}` is not a valid count", word)),
+            _ => Err(format!("`{}` needs exactly one count", name)),
+        }
+    };
+    let command = match name {
+        "insert" => Command::Insert(values()?),
+        "delete" => Command::Delete(values()?),
+        "contains" => Command::Contains(values()?),
+        "height" => Command::Height,
+        "leaves" => Command::Leaves,
+        "len" => Command::Len,
+        "empty" => Command::Empty,
+        "inorder" => Command::InOrder,
+        "range" => {
+            let mut bounds = fixed(2, "two values, LOW and HIGH")?.into_iter();
+            let low = bounds.next().unwrap();
+            Command::Range(low, bounds.next().unwrap())
+        },
+        "rank" => Command::Rank(fixed(1, "exactly one value")?.remove(0)),
+        "print" => Command::Print,
+        "check" => Command::Check,
+        "assert-height" => Command::AssertHeight(number()?),
+        "assert-leaves" => Command::AssertLeaves(number()?),
+        "assert-len" => Command::AssertLen(number()?),
+        "assert-contains" => Command::AssertContains(values()?),
+        "assert-missing" => Command::AssertMissing(values()?),
+        // the shape has spaces in it, it is the rest of the command
+        "assert-tree" if arguments.is_empty() => return Err("`assert-tree` needs the expected tree".to_string()),
+        "assert-tree" => Command::AssertTree(arguments.join(" ")),
+        _ => return Err(format!("unknown command `{}`", name)),
+    };
+    if takes_no_arguments(&command) && !arguments.is_empty() {
+        return Err(format!("`{}` takes no arguments", name));
+    }
+    Ok(command)
+}
+
+fn takes_no_arguments<T>(command: &Command<T>) -> bool {
+    matches!(command,
+        Command::Height | Command::Leaves | Command::Len | Command::Empty
+        | Command::InOrder | Command::Print | Command::Check)
+}
+
+// Parse `;`-separated commands, all of them before any is run so a typo does not leave
+// half of the work done. Empty commands between separators are skipped. Every command
+// comes with where it was, `command N`, for the error messages.
+pub fn parse_commands<T: FromStr>(text: &str) -> Result<Vec<(String, Command<T>)>, String> {
+    text.split(';')
+        .enumerate()
+        .filter(|(_, command)| !command.trim().is_empty())
+        .map(|(index, command)| {
+            let place = format!("command {}", index + 1);
+            match parse_command(command) {
+                Ok(command) => Ok((place, command)),
+                Err(error) => Err(format!("{}: {}", place, error)),
+            }
+        })
+        .collect()
+}
+
+// Parse a script with one command per line; blank lines and everything after a `#` are
+// skipped. Every command comes with where it was, `NAME:LINE`, for the error messages.
+pub fn parse_script<T: FromStr>(name: &str, text: &str) -> Result<Vec<(String, Command<T>)>, String> {
+    let mut commands = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        if line.trim().is_empty() {
+            continue;
+        }
+        let place = format!("{}:{}", name, index + 1);
+        match parse_command(line) {
+            Ok(command) => commands.push((place, command)),
+            Err(error) => return Err(format!("{}: {}", place, error)),
+        }
+    }
+    Ok(commands)
+}
+
+// Run `command` on `tree`. Err only when the command itself fails, which is `check` finding
+// a broken invariant or an assertion that does not hold; inserting a value that is already
+// there is a normal answer.
+pub fn run<T, Tree>(tree: &mut Tree, command: &Command<T>) -> Result<Outcome<T>, String>
+where
+    T: Clone + Display,
+    Tree: CommandTree<T>,
+{
+    let outcome = match command {
+        Command::Insert(input) => {
+            let (inserted, existing) = partition(input, |value| tree.insert(value));
+            Outcome::Inserted { inserted, existing }
+        },
+        Command::Delete(input) => {
+            let (deleted, missing) = partition(input, |value| tree.delete(value));
+            Outcome::Deleted { deleted, missing }
+        },
+        Command::Contains(input) => {
+            let (found, missing) = partition(input, |value| tree.contains(value));
+            Outcome::Found { found, missing }
+        },
+        Command::Height => Outcome::Height(tree.height()),
+        Command::Leaves => Outcome::Leaves(tree.leaves()),
+        Command::Len => Outcome::Len(tree.len()),
+        Command::Empty => Outcome::Empty(tree.is_empty()),
+        Command::InOrder => Outcome::Values(tree.to_vec()),
+        Command::Range(low, high) => Outcome::Range(tree.values_between(low.clone(), high.clone())),
+        Command::Rank(value) => Outcome::Rank { value: value.clone(), rank: tree.count_below(value.clone()) },
+        Command::Print => Outcome::Tree(tree.render(Layout::Bracket)),
+        Command::Check => {
+            tree.check_invariants()?;
+            Outcome::Passed
+        },
+        Command::AssertHeight(expected) => expect_count("height", *expected, tree.height())?,
+        Command::AssertLeaves(expected) => expect_count("leaves", *expected, tree.leaves())?,
+        Command::AssertLen(expected) => expect_count("len", *expected, tree.len())?,
+        Command::AssertContains(input) => {
+            let (_, missing) = partition(input, |value| tree.contains(value));
+            if !missing.is_empty() {
+                return Err(format!("{} not in the tree", join(&missing, ", ")));
+            }
+            Outcome::Passed
+        },
+        Command::AssertMissing(input) => {
+            let (found, _) = partition(input, |value| tree.contains(value));
+            if !found.is_empty() {
+                return Err(format!("{} in the tree", join(&found, ", ")));
+            }
+            Outcome::Passed
+        },
+        Command::AssertTree(expected) => {
+            let found = tree.render(Layout::Bracket);
+            if found != *expected {
+                return Err(format!("expected {}, found {}", expected, found));
+            }
+            Outcome::Passed
+        },
+    };
+    Ok(outcome)
+}
+
+// `run` with the outcome as a JSON object whose `op` member names the command
+pub fn execute<T, Tree>(tree: &mut Tree, command: &Command<T>) -> Result<Json, String>
+where
+    T: Clone + Display,
+    Tree: CommandTree<T>,
+{
+    run(tree, command).map(|outcome| outcome.to_json(command_name(command)))
+}
+
+// split `input` by what `apply` answers for each value
+fn partition<T: Clone>(input: &[T], mut apply: impl FnMut(T) -> bool) -> (Vec<T>, Vec<T>) {
+    let (mut yes, mut no) = (Vec::new(), Vec::new());
+    for value in input {
+        if apply(value.clone()) { yes.push(value.clone()) } else { no.push(value.clone()) }
+    }
+    (yes, no)
+}
+
+fn expect_count<T>(what: &str, expected: usize, found: usize) -> Result<Outcome<T>, String> {
+    if expected != found {
+        return Err(format!("expected {} {}, found {}", what, expected, found));
+    }
+    Ok(Outcome::Passed)
+}
+
+fn join<T: Display>(values: &[T], separator: &str) -> String {
+    values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(separator)
+}
+
+impl<T: Display> Outcome<T> {
+    pub fn to_json(&self, op: &str) -> Json {
+        let values = |values: &[T]| Json::Array(values.iter().map(interchange::value_to_json).collect());
+        let count = |count: &usize| Json::Number(count.to_string());
+        let members = match self {
+            Outcome::Inserted { inserted, existing } => vec![("inserted", values(inserted)), ("existing", values(existing))],
+            Outcome::Deleted { deleted, missing } => vec![("deleted", values(deleted)), ("missing", values(missing))],
+            Outcome::Found { found, missing } => vec![("found", values(found)), ("missing", values(missing))],
+            Outcome::Height(height) => vec![("height", count(height))],
+            Outcome::Leaves(leaves) => vec![("leaves", count(leaves))],
+            Outcome::Len(len) => vec![("len", count(len))],
+            Outcome::Empty(empty) => vec![("empty", Json::Bool(*empty))],
+            Outcome::Values(all) | Outcome::Range(all) => vec![("values", values(all))],
+            Outcome::Rank { value, rank } => vec![("value", interchange::value_to_json(value)), ("rank", count(rank))],
+            Outcome::Tree(tree) => vec![("tree", Json::String(tree.clone()))],
+            Outcome::Passed => vec![("ok", Json::Bool(true))],
+        };
+        let mut object = vec![("op".to_string(), Json::String(op.to_string()))];
+        object.extend(members.into_iter().map(|(key, value)| (key.to_string(), value)));
+        Json::Object(object)
+    }
+
+    // the lines the interactive menus print
+    pub fn describe(&self) -> Vec<String> {
+        match self {
+            Outcome::Inserted { inserted, existing } => {
+                let mut lines: Vec<String> = existing.iter().map(|value| format!("INSERT FAILED: Node({}) already exists!", value)).collect();
+                if !inserted.is_empty() {
+                    lines.push(format!("Insert [{}] successfully.", join(inserted, ", ")));
+                }
+                lines
+            },
+            Outcome::Deleted { deleted, missing } => deleted.iter().map(|value| format!("Delete {} succeed!", value))
+                .chain(missing.iter().map(|value| format!("Node {} doesn't exist!", value)))
+                .collect(),
+            Outcome::Found { found, missing } => found.iter().map(|value| format!("{} is in the tree.", value))
+                .chain(missing.iter().map(|value| format!("{} is not in the tree.", value)))
+                .collect(),
+            Outcome::Height(height) => vec![format!("The height of the tree is: {}", height)],
+            Outcome::Leaves(leaves) => vec![format!("The number of leaves is: {}", leaves)],
+            Outcome::Len(len) => vec![format!("The tree holds {} value(s).", len)],
+            Outcome::Empty(true) => vec!["This tree is empty".to_string()],
+            Outcome::Empty(false) => vec!["This tree is not empty".to_string()],
+            Outcome::Values(values) => vec!["In-order traversal: ".to_string(), join(values, " ")],
+            Outcome::Range(values) if values.is_empty() => vec!["No values in that range.".to_string()],
+            Outcome::Range(values) => vec![join(values, " ")],
+            Outcome::Rank { value, rank } => vec![format!("{} value(s) in the tree are smaller than {}.", rank, value)],
+            Outcome::Tree(tree) => vec![tree.clone()],
+            Outcome::Passed => vec!["OK".to_string()],
+        }
+    }
+}
+
+pub fn command_name<T>(command: &Command<T>) -> &'static str {
+    match command {
+        Command::Insert(_) => "insert",
+        Command::Delete(_) => "delete",
+        Command::Contains(_) => "contains",
+        Command::Height => "height",
+        Command::Leaves => "leaves",
+        Command::Len => "len",
+        Command::Empty => "empty",
+        Command::InOrder => "inorder",
+        Command::Range(..) => "range",
+        Command::Rank(_) => "rank",
+        Command::Print => "print",
+        Command::Check => "check",
+        Command::AssertHeight(_) => "assert-height",
+        Command::AssertLeaves(_) => "assert-leaves",
+        Command::AssertLen(_) => "assert-len",
+        Command::AssertContains(_) => "assert-contains",
+        Command::AssertMissing(_) => "assert-missing",
+        Command::AssertTree(_) => "assert-tree",
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt::Write;
+
+// Builds a Graphviz digraph for a binary tree. Nodes get generated ids, missing children
+// become small point-shaped placeholders so every node draws a left and a right edge and
+// `ordering=out` keeps the left child on the left.
+pub struct DotWriter {
+    out: String,
+    next_id: usize,
+}
+
+impl DotWriter {
+    pub fn new(name: &str) -> Self {
+        let mut out = String::new();
+        writeln!(out, "digraph {} {{", name).unwrap();
+        writeln!(out, "    graph [ordering=out];").unwrap();
+        writeln!(out, "    node [shape=circle, fontname=\"Helvetica\"];").unwrap();
+        DotWriter { out, next_id: 0 }
+    }
+
+    // add a node, `attributes` are extra `key=value` pairs; returns its id for the edges
+    pub fn node(&mut self, label: &str, attributes: &str) -> String {
+        let id = self.next_id();
+        let separator = if attributes.is_empty() { "" } else { ", " };
+        writeln!(self.out, "    {} [label=\"{}\"{}{}];", id, escape(label), separator, attributes).unwrap();
+        id
+    }
+
+    // placeholder for a missing child
+    pub fn nil(&mut self) -> String {
+        let id = self.next_id();
+        writeln!(self.out, "    {} [shape=point, label=\"\"];", id).unwrap();
+        id
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str) {
+        writeln!(self.out, "    {} -> {};", from, to).unwrap();
+    }
+
+    pub fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+
+    fn next_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("n{}", self.next_id - 1)
+    }
+}
+
+// quotes and backslashes would end the label early, line breaks become DOT's \n
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
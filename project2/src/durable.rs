@@ -0,0 +1,379 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use crate::codec::Codec;
+use crate::ordered_set::OrderedSet;
+
+// A tree kept on disk in a directory of its own:
+//     snapshot.bin  the whole tree at some point, in the binary format of `codec`
+//     wal.log       every insert and delete made since that snapshot
+// Each change is appended to the log before it is applied, and every `snapshot_every`
+// changes the tree is written out as a new snapshot and the log starts over. Opening the
+// directory loads the snapshot and replays the log on top of it.
+//
+// Both files are replaced by writing a temporary file and renaming it, so a crash leaves
+// either the old or the new version. The snapshot and the log carry a generation number: a
+// crash between writing a snapshot and starting its log leaves a log from the generation
+// before, which is then already part of the snapshot and is thrown away. A crash in the
+// middle of appending leaves a partial record at the end of the log; it fails its checksum
+// or its length, and the log is cut back to the last complete record.
+//
+// An append that fails while the program keeps running is cut back the same way at once, so
+// the records after it are not hidden behind a partial one. When that or starting a new log
+// fails too, the tree refuses further changes until a snapshot succeeds. A failed automatic
+// snapshot loses nothing, the change is already in the log; see `snapshot_error`.
+
+const SNAPSHOT_FILE: &str = "snapshot.bin";
+const LOG_FILE: &str = "wal.log";
+const SNAPSHOT_MAGIC: &[u8; 4] = b"BSNP";
+const LOG_MAGIC: &[u8; 4] = b"BWAL";
+const VERSION: u8 = 1;
+// magic, version, generation
+const LOG_HEADER_LEN: usize = 4 + 1 + 8;
+
+const OP_INSERT: u8 = 1;
+const OP_DELETE: u8 = 2;
+
+// What `DurableTree` needs from the tree it wraps, a way to save and load all of it.
+pub trait SnapshotTree<T>: OrderedSet<T> + Sized {
+    fn snapshot_bytes(&self) -> Vec<u8>;
+    fn restore(bytes: &[u8]) -> Result<Self, String>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DurableOptions {
+    // write a snapshot after this many logged changes, 0 only snapshots on request
+    pub snapshot_every: usize,
+    // flush every record to the disk before the change is applied; without it a crash of
+    // the machine (not just the program) can lose the last changes
+    pub sync: bool,
+}
+
+impl Default for DurableOptions {
+    fn default() -> Self {
+        DurableOptions { snapshot_every: 1000, sync: true }
+    }
+}
+
+// what opening the directory found
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Recovery {
+    // generation of the snapshot that was loaded, 0 when there was none
+    pub generation: u64,
+    // log records applied on top of the snapshot
+    pub replayed: usize,
+    // bytes of a partial record cut from the end of the log
+    pub truncated_bytes: u64,
+    // the log belonged to an older snapshot and was thrown away
+    pub stale_log: bool,
+}
+
+pub struct DurableTree<T, Tree> {
+    tree: Tree,
+    dir: PathBuf,
+    log: File,
+    // length of the log up to the end of its last complete record
+    log_len: u64,
+    generation: u64,
+    since_snapshot: usize,
+    options: DurableOptions,
+    recovery: Recovery,
+    // why the log can no longer be trusted to take records, cleared by a snapshot
+    poisoned: Option<String>,
+    // why the last automatic snapshot failed, cleared by a snapshot
+    snapshot_error: Option<String>,
+    values: PhantomData<T>,
+}
+
+impl<T: Codec + Clone, Tree: SnapshotTree<T>> DurableTree<T, Tree> {
+    // open `dir` with the default options, creating it when it does not exist
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, String> {
+        Self::open_with(dir, DurableOptions::default())
+    }
+
+    pub fn open_with(dir: impl AsRef<Path>, options: DurableOptions) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+        // leftovers of a replacement that did not finish
+        for file in [SNAPSHOT_FILE, LOG_FILE] {
+            let _ = fs::remove_file(dir.join(format!("{}.tmp", file)));
+        }
+
+        let mut recovery = Recovery::default();
+        let mut tree = match read_file(&dir.join(SNAPSHOT_FILE))? {
+            Some(bytes) => {
+                let (generation, payload) = parse_snapshot(&bytes)?;
+                recovery.generation = generation;
+                Tree::restore(payload).map_err(|e| format!("cannot load the snapshot: {}", e))?
+            },
+            None => Tree::new(),
+        };
+
+        let log_path = dir.join(LOG_FILE);
+        let mut replay_log = false;
+        let mut log_len = LOG_HEADER_LEN;
+        if let Some(bytes) = read_file(&log_path)? {
+            let generation = parse_log_header(&bytes)?;
+            if generation > recovery.generation {
+                return Err(format!("the log is from generation {} but the snapshot is from {}, the snapshot is missing", generation, recovery.generation));
+            }
+            if generation < recovery.generation {
+                recovery.stale_log = true;
+            } else {
+                let good_len = replay(&bytes[LOG_HEADER_LEN..], &mut tree, &mut recovery)? + LOG_HEADER_LEN;
+                if good_len < bytes.len() {
+                    recovery.truncated_bytes = (bytes.len() - good_len) as u64;
+                    let file = OpenOptions::new().write(true).open(&log_path).map_err(|e| io_error(&log_path, e))?;
+                    file.set_len(good_len as u64).map_err(|e| io_error(&log_path, e))?;
+                    file.sync_all().map_err(|e| io_error(&log_path, e))?;
+                }
+                replay_log = true;
+                log_len = good_len;
+            }
+        }
+        if !replay_log {
+            write_atomically(&dir, LOG_FILE, &log_header(recovery.generation))?;
+        }
+
+        let log = OpenOptions::new().append(true).open(&log_path).map_err(|e| io_error(&log_path, e))?;
+        Ok(DurableTree {
+            tree,
+            dir,
+            log,
+            log_len: log_len as u64,
+            generation: recovery.generation,
+            since_snapshot: recovery.replayed,
+            options,
+            recovery,
+            poisoned: None,
+            snapshot_error: None,
+            values: PhantomData,
+        })
+    }
+
+    // the wrapped tree, changes have to go through `insert` and `delete`
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    pub fn recovery(&self) -> Recovery {
+        self.recovery
+    }
+
+    // generation of the latest snapshot
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // why the last automatic snapshot failed; the change that asked for it was still made and
+    // logged, and the snapshot is tried again after the next change
+    pub fn snapshot_error(&self) -> Option<&str> {
+        self.snapshot_error.as_deref()
+    }
+
+    // add `value`, false if it was already there; the change is logged before it is made
+    pub fn insert(&mut self, value: T) -> Result<bool, String> {
+        if self.tree.contains(value.clone()) {
+            return Ok(false);
+        }
+        self.append(OP_INSERT, &value)?;
+        self.tree.insert(value);
+        self.after_change();
+        Ok(true)
+    }
+
+    // remove `value`, false if it was not there
+    pub fn delete(&mut self, value: T) -> Result<bool, String> {
+        if !self.tree.contains(value.clone()) {
+            return Ok(false);
+        }
+        self.append(OP_DELETE, &value)?;
+        self.tree.delete(value);
+        self.after_change();
+        Ok(true)
+    }
+
+    // Write the whole tree as the next generation's snapshot and start an empty log.
+    // Succeeding lets a tree take changes again after a failed append.
+    pub fn snapshot(&mut self) -> Result<(), String> {
+        let generation = self.generation + 1;
+        let payload = self.tree.snapshot_bytes();
+        let mut bytes = Vec::with_capacity(payload.len() + 25);
+        bytes.extend_from_slice(SNAPSHOT_MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&generation.to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&crc32(&payload).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        write_atomically(&self.dir, SNAPSHOT_FILE, &bytes)?;
+
+        // from here on the old log is stale, a crash before the new one is in place is fine;
+        // records appended to the old one would be thrown away on the next open though
+        let log_path = self.dir.join(LOG_FILE);
+        let log = write_atomically(&self.dir, LOG_FILE, &log_header(generation))
+            .and_then(|()| OpenOptions::new().append(true).open(&log_path).map_err(|e| io_error(&log_path, e)));
+        let log = match log {
+            Ok(log) => log,
+            Err(error) => {
+                self.poisoned = Some(format!("the snapshot was written but the new log was not: {}", error));
+                return Err(error);
+            },
+        };
+        self.log = log;
+        self.log_len = LOG_HEADER_LEN as u64;
+        self.generation = generation;
+        self.since_snapshot = 0;
+        self.poisoned = None;
+        self.snapshot_error = None;
+        Ok(())
+    }
+
+    // one record: operation, payload length, payload, and a CRC-32 of all three
+    fn append(&mut self, op: u8, value: &T) -> Result<(), String> {
+        if let Some(reason) = &self.poisoned {
+            return Err(format!("the log takes no more changes until a snapshot succeeds: {}", reason));
+        }
+        let mut payload = Vec::new();
+        value.encode(&mut payload);
+        let mut record = Vec::with_capacity(payload.len() + 9);
+        record.push(op);
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(&payload);
+        let checksum = crc32(&record);
+        record.extend_from_slice(&checksum.to_le_bytes());
+
+        let log_path = self.dir.join(LOG_FILE);
+        let written = self.log.write_all(&record)
+            .and_then(|()| if self.options.sync { self.log.sync_data() } else { Ok(()) });
+        if let Err(error) = written {
+            // part of the record may be in the log, and every record after it would be lost
+            let error = io_error(&log_path, error);
+            if let Err(cut) = self.log.set_len(self.log_len) {
+                self.poisoned = Some(format!("{}, and cutting the log back failed: {}", error, io_error(&log_path, cut)));
+            }
+            return Err(error);
+        }
+        self.log_len += record.len() as u64;
+        Ok(())
+    }
+
+    fn after_change(&mut self) {
+        self.since_snapshot += 1;
+        if self.options.snapshot_every > 0 && self.since_snapshot >= self.options.snapshot_every {
+            if let Err(error) = self.snapshot() {
+                self.snapshot_error = Some(error);
+            }
+        }
+    }
+}
+
+// Apply the records in `records` to `tree` and return how many bytes of them were complete.
+// Reading stops at the first record that is cut off or fails its checksum.
+fn replay<T: Codec, Tree: SnapshotTree<T>>(records: &[u8], tree: &mut Tree, recovery: &mut Recovery) -> Result<usize, String> {
+    let mut offset = 0;
+    while records.len() - offset >= 5 {
+        let op = records[offset];
+        let len = u32::from_le_bytes(records[offset + 1..offset + 5].try_into().unwrap()) as usize;
+        let end = match (offset + 5).checked_add(len).and_then(|end| end.checked_add(4)) {
+            Some(end) if end <= records.len() => end,
+            _ => break,
+        };
+        let stored = u32::from_le_bytes(records[end - 4..end].try_into().unwrap());
+        if crc32(&records[offset..end - 4]) != stored {
+            break;
+        }
+        let mut payload = &records[offset + 5..end - 4];
+        let value = T::decode(&mut payload).map_err(|e| format!("log record at byte {}: {}", offset + LOG_HEADER_LEN, e))?;
+        match op {
+            OP_INSERT => { tree.insert(value); },
+            OP_DELETE => { tree.delete(value); },
+            _ => return Err(format!("log record at byte {} has the unknown operation {}", offset + LOG_HEADER_LEN, op)),
+        }
+        recovery.replayed += 1;
+        offset = end;
+    }
+    Ok(offset)
+}
+
+fn log_header(generation: u64) -> Vec<u8> {
+    let mut header = Vec::with_capacity(LOG_HEADER_LEN);
+    header.extend_from_slice(LOG_MAGIC);
+    header.push(VERSION);
+    header.extend_from_slice(&generation.to_le_bytes());
+    header
+}
+
+fn parse_log_header(bytes: &[u8]) -> Result<u64, String> {
+    if bytes.len() < LOG_HEADER_LEN || &bytes[..4] != LOG_MAGIC {
+        return Err(format!("{} is not a tree log", LOG_FILE));
+    }
+    if bytes[4] != VERSION {
+        return Err(format!("{} has the unsupported version {}", LOG_FILE, bytes[4]));
+    }
+    Ok(u64::from_le_bytes(bytes[5..13].try_into().unwrap()))
+}
+
+// the generation and the tree bytes of a snapshot file
+fn parse_snapshot(bytes: &[u8]) -> Result<(u64, &[u8]), String> {
+    // magic, version, generation, length, checksum
+    const HEADER_LEN: usize = 4 + 1 + 8 + 8 + 4;
+    if bytes.len() < HEADER_LEN || &bytes[..4] != SNAPSHOT_MAGIC {
+        return Err(format!("{} is not a tree snapshot", SNAPSHOT_FILE));
+    }
+    if bytes[4] != VERSION {
+        return Err(format!("{} has the unsupported version {}", SNAPSHOT_FILE, bytes[4]));
+    }
+    let generation = u64::from_le_bytes(bytes[5..13].try_into().unwrap());
+    let len = u64::from_le_bytes(bytes[13..21].try_into().unwrap());
+    let checksum = u32::from_le_bytes(bytes[21..25].try_into().unwrap());
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() as u64 != len || crc32(payload) != checksum {
+        return Err(format!("{} is damaged", SNAPSHOT_FILE));
+    }
+    Ok((generation, payload))
+}
+
+// the whole file, None when it does not exist
+fn read_file(path: &Path) -> Result<Option<Vec<u8>>, String> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(io_error(path, e)),
+    };
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| io_error(path, e))?;
+    Ok(Some(bytes))
+}
+
+// replace `dir/name` with `bytes` through a temporary file, so readers see the old or the new
+// contents but never a mix
+fn write_atomically(dir: &Path, name: &str, bytes: &[u8]) -> Result<(), String> {
+    let tmp = dir.join(format!("{}.tmp", name));
+    let path = dir.join(name);
+    let mut file = File::create(&tmp).map_err(|e| io_error(&tmp, e))?;
+    file.write_all(bytes).map_err(|e| io_error(&tmp, e))?;
+    file.sync_all().map_err(|e| io_error(&tmp, e))?;
+    fs::rename(&tmp, &path).map_err(|e| io_error(&path, e))?;
+    // make the rename itself durable, not every platform can open a directory for this
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> String {
+    format!("{}: {}", path.display(), error)
+}
+
+// CRC-32 as used by zip and PNG, bit by bit since the records are small
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
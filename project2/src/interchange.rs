@@ -0,0 +1,591 @@
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+use crate::bracket::BracketNode;
+use crate::codec::TreeKind;
+
+// Text formats for the contents of a tree, written and read by hand so nothing outside the
+// standard library is needed:
+//   - a JSON array of the values in order, `[1, 2, 2, 5]`, repeated once per occurrence
+//   - a nested JSON object with the exact shape, see `AVLTree::to_json_tree` and
+//     `RedBlackTree::to_json_tree`
+//   - CSV with a `value,count` header and one row per distinct value in order
+// Values go through Display and FromStr; one that prints as a JSON number is written as a
+// number, anything else as a string.
+
+// A parsed JSON document. Numbers keep their text so the element type can parse them itself.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    // members in the order they were written
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = JsonParser { text: text.chars().collect(), position: 0, line: 1, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position < parser.text.len() {
+            return Err(parser.error("unexpected text after the document"));
+        }
+        Ok(value)
+    }
+
+    // the member `key` of an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    // Two-space indented text. Arrays that hold no arrays or objects stay on one line.
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out.push('\n');
+        out
+    }
+
+    // everything on one line, for line-oriented output
+    pub fn to_compact_string(&self) -> String {
+        let mut out = String::new();
+        self.write_compact(&mut out);
+        out
+    }
+
+    fn write_compact(&self, out: &mut String) {
+        match self {
+            Json::Array(items) => {
+                out.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        out.push(',');
+                    }
+                    item.write_compact(out);
+                }
+                out.push(']');
+            },
+            Json::Object(members) => {
+                out.push('{');
+                for (index, (key, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        out.push(',');
+                    }
+                    write_json_string(out, key);
+                    out.push(':');
+                    value.write_compact(out);
+                }
+                out.push('}');
+            },
+            scalar => scalar.write_pretty(out, 0),
+        }
+    }
+
+    fn write_pretty(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(value) => write!(out, "{}", value).unwrap(),
+            Json::Number(text) => out.push_str(text),
+            Json::String(text) => write_json_string(out, text),
+            Json::Array(items) if items.iter().all(|item| !matches!(item, Json::Array(_) | Json::Object(_))) => {
+                out.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(", ");
+                    }
+                    item.write_pretty(out, indent);
+                }
+                out.push(']');
+            },
+            Json::Array(items) => {
+                out.push_str("[\n");
+                for (index, item) in items.iter().enumerate() {
+                    out.push_str(&"  ".repeat(indent + 1));
+                    item.write_pretty(out, indent + 1);
+                    out.push_str(if index + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&"  ".repeat(indent));
+                out.push(']');
+            },
+            Json::Object(members) if members.is_empty() => out.push_str("{}"),
+            Json::Object(members) => {
+                out.push_str("{\n");
+                for (index, (key, value)) in members.iter().enumerate() {
+                    out.push_str(&"  ".repeat(indent + 1));
+                    write_json_string(out, key);
+                    out.push_str(": ");
+                    value.write_pretty(out, indent + 1);
+                    out.push_str(if index + 1 < members.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&"  ".repeat(indent));
+                out.push('}');
+            },
+        }
+    }
+}
+
+fn write_json_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// -?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?
+fn is_json_number(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while *i < bytes.len() && bytes[*i].is_ascii_digit() {
+            *i += 1;
+        }
+        *i - start
+    };
+    if bytes.get(i) == Some(&b'-') {
+        i += 1;
+    }
+    match bytes.get(i) {
+        Some(b'0') => i += 1,
+        Some(b'1'..=b'9') => { digits(&mut i); },
+        _ => return false,
+    }
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        if digits(&mut i) == 0 {
+            return false;
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        if digits(&mut i) == 0 {
+            return false;
+        }
+    }
+    i == bytes.len()
+}
+
+// Arrays and objects nested deeper than this are turned down, the parser and everything that
+// walks the result recurse once per level. A tree document needs one level per node on the
+// longest path, far less than this for any balanced tree.
+const MAX_DEPTH: usize = 512;
+
+struct JsonParser {
+    text: Vec<char>,
+    position: usize,
+    line: usize,
+    // arrays and objects open around the position
+    depth: usize,
+}
+
+impl JsonParser {
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&c) = self.text.get(self.position) {
+            if !c.is_whitespace() {
+                break;
+            }
+            if c == '\n' {
+                self.line += 1;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.text.get(self.position).copied();
+        self.position += 1;
+        c
+    }
+
+    fn expect_word(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(self.error(&format!("expected `{}`", word)));
+            }
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.text.get(self.position) {
+            None => Err(self.error("unexpected end of input, expected a value")),
+            Some('n') => self.expect_word("null", Json::Null),
+            Some('t') => self.expect_word("true", Json::Bool(true)),
+            Some('f') => self.expect_word("false", Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') | Some('{') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error(&format!("arrays and objects nested deeper than {} levels", MAX_DEPTH)));
+                }
+                self.depth += 1;
+                let value = if self.next() == Some('[') { self.array() } else { self.object() };
+                self.depth -= 1;
+                value
+            },
+            Some(_) => {
+                let start = self.position;
+                while self.text.get(self.position).is_some_and(|c| matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9')) {
+                    self.position += 1;
+                }
+                let text: String = self.text[start..self.position].iter().collect();
+                if is_json_number(&text) {
+                    Ok(Json::Number(text))
+                } else {
+                    Err(self.error("expected a value"))
+                }
+            },
+        }
+    }
+
+    // the position is past the opening `[`
+    fn array(&mut self) -> Result<Json, String> {
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.text.get(self.position) == Some(&']') {
+            self.position += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => return Err(self.error("expected `,` or `]` in an array")),
+            }
+        }
+    }
+
+    // the position is past the opening `{`
+    fn object(&mut self) -> Result<Json, String> {
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.text.get(self.position) == Some(&'}') {
+            self.position += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.text.get(self.position) != Some(&'"') {
+                return Err(self.error("expected a member name"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if self.next() != Some(':') {
+                return Err(self.error("expected `:` after a member name"));
+            }
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(members)),
+                _ => return Err(self.error("expected `,` or `}` in an object")),
+            }
+        }
+    }
+
+    // a string literal, the position is on the opening quote
+    fn string(&mut self) -> Result<String, String> {
+        self.position += 1;
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => match self.next() {
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some('/') => out.push('/'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some('u') => {
+                        let high = self.hex4()?;
+                        let code = if (0xD800..0xDC00).contains(&high) {
+                            // a surrogate pair, the low half has to follow right away
+                            if self.next() != Some('\\') || self.next() != Some('u') {
+                                return Err(self.error("unpaired surrogate in a string"));
+                            }
+                            let low = self.hex4()?;
+                            if !(0xDC00..0xE000).contains(&low) {
+                                return Err(self.error("unpaired surrogate in a string"));
+                            }
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                        } else {
+                            high
+                        };
+                        out.push(char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))?);
+                    },
+                    _ => return Err(self.error("invalid escape in a string")),
+                },
+                Some('\n') => return Err(self.error("line break inside a string")),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.next().and_then(|c| c.to_digit(16)).ok_or_else(|| self.error("invalid \\u escape"))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+}
+
+// a tree value as JSON, a number when its text is one and a string otherwise
+pub fn value_to_json<T: Display>(value: &T) -> Json {
+    let text = value.to_string();
+    if is_json_number(&text) {
+        Json::Number(text)
+    } else {
+        Json::String(text)
+    }
+}
+
+pub fn value_from_json<T: FromStr>(json: &Json) -> Result<T, String> {
+    match json {
+        Json::Number(text) | Json::String(text) => text.parse().map_err(|_| format!("`{}` is not a valid value", text)),
+        other => Err(format!("expected a value, found {}", describe(other))),
+    }
+}
+
+fn describe(json: &Json) -> &'static str {
+    match json {
+        Json::Null => "null",
+        Json::Bool(_) => "a boolean",
+        Json::Number(_) => "a number",
+        Json::String(_) => "a string",
+        Json::Array(_) => "an array",
+        Json::Object(_) => "an object",
+    }
+}
+
+// `[1, 2, 2, 5]`, the values in order with repeats
+pub fn values_to_json<T: Display>(values: impl Iterator<Item = T>) -> String {
+    Json::Array(values.map(|value| value_to_json(&value)).collect()).to_pretty_string()
+}
+
+// Group sorted values into entries with their occurrence counts; fails at the first value
+// smaller than the one before it.
+pub fn group_sorted<T: PartialOrd>(values: impl Iterator<Item = T>) -> Result<Vec<(T, usize)>, String> {
+    let mut entries: Vec<(T, usize)> = Vec::new();
+    for (index, value) in values.enumerate() {
+        match entries.last_mut() {
+            Some((last, count)) if *last == value => *count += 1,
+            Some((last, _)) if *last > value => return Err(format!("item {} is smaller than the one before it", index + 1)),
+            _ => entries.push((value, 1)),
+        }
+    }
+    Ok(entries)
+}
+
+// what a JSON document holds
+pub enum JsonTree<T> {
+    // a flat array, the sorted values grouped into entries
+    Values(Vec<(T, usize)>),
+    // a nested object, the exact shape to rebuild
+    Shape { multiset: bool, root: Option<Box<BracketNode<T>>> },
+}
+
+// Read a document written by `values_to_json` or a tree's `to_json_tree`. A nested document
+// has to be for a tree of `kind`; its stored heights are not read, the tree works them out.
+pub fn parse_json_tree<T: FromStr + PartialOrd>(text: &str, kind: TreeKind) -> Result<JsonTree<T>, String> {
+    match Json::parse(text)? {
+        Json::Array(items) => {
+            let values = items.iter().map(value_from_json).collect::<Result<Vec<T>, String>>()?;
+            group_sorted(values.into_iter()).map(JsonTree::Values)
+        },
+        json @ Json::Object(_) => {
+            match json.get("type") {
+                Some(Json::String(name)) if name == kind.id() => {},
+                Some(Json::String(name)) => return Err(format!("the document holds a `{}` tree, not `{}`", name, kind.id())),
+                _ => return Err("the document has no `type` member".to_string()),
+            }
+            let multiset = match json.get("multiset") {
+                None => false,
+                Some(Json::Bool(multiset)) => *multiset,
+                Some(other) => return Err(format!("`multiset` should be a boolean, found {}", describe(other))),
+            };
+            let root = match json.get("root") {
+                Some(root) => json_node(root)?,
+                None => return Err("the document has no `root` member".to_string()),
+            };
+            Ok(JsonTree::Shape { multiset, root })
+        },
+        other => Err(format!("expected an array or an object, found {}", describe(&other))),
+    }
+}
+
+// one node of a nested document, null for a missing child; the recursion is bounded by the
+// nesting limit of the parser
+fn json_node<T: FromStr>(json: &Json) -> Result<Option<Box<BracketNode<T>>>, String> {
+    if *json == Json::Null {
+        return Ok(None);
+    }
+    if !matches!(json, Json::Object(_)) {
+        return Err(format!("expected a node object or null, found {}", describe(json)));
+    }
+    let value = value_from_json(json.get("value").ok_or("a node has no `value` member")?)?;
+    let count = match json.get("count") {
+        None => 1,
+        Some(Json::Number(text)) => match text.parse::<usize>() {
+            Ok(count) if count > 0 => count,
+            _ => return Err(format!("`{}` is not a valid count", text)),
+        },
+        Some(other) => return Err(format!("`count` should be a number, found {}", describe(other))),
+    };
+    let red = match json.get("color") {
+        None => None,
+        Some(Json::String(color)) if color == "red" => Some(true),
+        Some(Json::String(color)) if color == "black" => Some(false),
+        Some(other) => return Err(format!("`color` should be \"red\" or \"black\", found {:?}", other)),
+    };
+    let left = json.get("left").map_or(Ok(None), json_node)?;
+    let right = json.get("right").map_or(Ok(None), json_node)?;
+    Ok(Some(Box::new(BracketNode { value, count, red, left, right })))
+}
+
+// `value,count` followed by one row per distinct value
+pub fn values_to_csv<T: Display + PartialEq>(values: impl Iterator<Item = T>) -> String {
+    let mut out = String::from("value,count\n");
+    let mut entries: Vec<(T, usize)> = Vec::new();
+    for value in values {
+        match entries.last_mut() {
+            Some((last, count)) if *last == value => *count += 1,
+            _ => entries.push((value, 1)),
+        }
+    }
+    for (value, count) in entries {
+        write_csv_field(&mut out, &value.to_string());
+        writeln!(out, ",{}", count).unwrap();
+    }
+    out
+}
+
+fn write_csv_field(out: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) || field.trim() != field {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
+}
+
+// Read what `values_to_csv` wrote. The header is required, the count column may be left out
+// and then every row is one occurrence. Rows have to be in increasing order; a value on
+// several rows adds up its counts.
+pub fn parse_csv_tree<T: FromStr + PartialOrd>(text: &str) -> Result<Vec<(T, usize)>, String> {
+    let rows = parse_csv(text)?;
+    let mut rows = rows.into_iter();
+    let (_, header) = rows.next().ok_or("the file is empty, expected a `value,count` header")?;
+    let with_count = match header.iter().map(|h| h.trim()).collect::<Vec<_>>()[..] {
+        ["value"] => false,
+        ["value", "count"] => true,
+        _ => return Err(format!("line 1: expected a `value,count` header, found `{}`", header.join(","))),
+    };
+    let mut entries: Vec<(T, usize)> = Vec::new();
+    for (line, row) in rows {
+        if row.len() != header.len() {
+            return Err(format!("line {}: expected {} fields, found {}", line, header.len(), row.len()));
+        }
+        let value: T = row[0].parse().map_err(|_| format!("line {}: `{}` is not a valid value", line, row[0]))?;
+        let count = if with_count {
+            match row[1].trim().parse::<usize>() {
+                Ok(count) if count > 0 => count,
+                _ => return Err(format!("line {}: `{}` is not a valid count", line, row[1])),
+            }
+        } else {
+            1
+        };
+        match entries.last_mut() {
+            Some((last, total)) if *last == value => *total += count,
+            Some((last, _)) if *last > value => return Err(format!("line {}: the value is smaller than the one before it", line)),
+            _ => entries.push((value, count)),
+        }
+    }
+    Ok(entries)
+}
+
+// records with the line they start on, quoted fields may hold commas, quotes and line breaks;
+// blank lines are skipped
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start = line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        loop {
+            match chars.next() {
+                None => {
+                    fields.push(std::mem::take(&mut field));
+                    break;
+                },
+                Some('"') if field.is_empty() && !quoted => {
+                    quoted = true;
+                    loop {
+                        match chars.next() {
+                            None => return Err(format!("line {}: unterminated quoted field", start)),
+                            Some('"') if chars.peek() == Some(&'"') => {
+                                chars.next();
+                                field.push('"');
+                            },
+                            Some('"') => break,
+                            Some(c) => {
+                                if c == '\n' {
+                                    line += 1;
+                                }
+                                field.push(c);
+                            },
+                        }
+                    }
+                },
+                Some(',') => {
+                    fields.push(std::mem::take(&mut field));
+                    quoted = false;
+                },
+                Some('\r') if chars.peek() == Some(&'\n') => {},
+                Some('\n') => {
+                    line += 1;
+                    fields.push(std::mem::take(&mut field));
+                    break;
+                },
+                Some(_) if quoted => return Err(format!("line {}: text after a closing quote", line)),
+                Some(c) => field.push(c),
+            }
+        }
+        if fields.len() > 1 || !fields[0].is_empty() || quoted {
+            records.push((start, fields));
+        }
+    }
+    Ok(records)
+}
@@ -0,0 +1,176 @@
+use std::cmp::Ordering;
+use std::fmt;
+use crate::augment::Summary;
+use crate::redblack_tree::{NodeRef, RedBlackTree};
+
+// half-open interval [start, end) with its value, ordered by (start, end) only
+// `value` is None only for the probes used to look an interval up
+#[derive(Clone)]
+struct IntervalEntry<K, V> {
+    start: K,
+    end: K,
+    value: Option<V>,
+}
+
+impl<K: PartialEq, V> PartialEq for IntervalEntry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start && self.end == other.end
+    }
+}
+
+impl<K: PartialOrd, V> PartialOrd for IntervalEntry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.start.partial_cmp(&other.start)? {
+            Ordering::Equal => self.end.partial_cmp(&other.end),
+            ordering => Some(ordering),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V> fmt::Debug for IntervalEntry<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{:?}, {:?})", self.start, self.end)
+    }
+}
+
+impl<K: fmt::Display, V> fmt::Display for IntervalEntry<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+// largest end point in a subtree, lets the queries skip subtrees that end too early
+#[derive(Clone)]
+struct MaxEnd<K>(K);
+
+impl<K: PartialOrd + Clone, V> Summary<IntervalEntry<K, V>> for MaxEnd<K> {
+    fn lift(entry: &IntervalEntry<K, V>, _count: usize) -> Self {
+        MaxEnd(entry.end.clone())
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        if other.0 > self.0 { other.clone() } else { self.clone() }
+    }
+}
+
+type Node<'a, K, V> = NodeRef<'a, IntervalEntry<K, V>, MaxEnd<K>>;
+
+// Interval tree on top of the red-black tree, every node also keeps the largest end point
+// of its subtree. Overlap queries run in O(log n + k) for k reported intervals.
+pub struct IntervalTree<K, V> where K: Clone, V: Clone {
+    tree: RedBlackTree<IntervalEntry<K, V>, MaxEnd<K>>,
+    len: usize,
+}
+
+impl<K, V> IntervalTree<K, V>
+where
+    K: PartialOrd + Clone + fmt::Debug + fmt::Display,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        IntervalTree {
+            tree: RedBlackTree::with_summary(),
+            len: 0,
+        }
+    }
+
+    fn probe(start: K, end: K) -> IntervalEntry<K, V> {
+        IntervalEntry { start, end, value: None }
+    }
+
+    // insert [start, end), fails for empty intervals and for intervals that are already stored
+    pub fn insert(&mut self, start: K, end: K, value: V) -> bool {
+        if start >= end {
+            return false;
+        }
+        let inserted = self.tree.insert(IntervalEntry { start, end, value: Some(value) });
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+
+    // remove [start, end) and hand back its value
+    pub fn remove(&mut self, start: K, end: K) -> Option<V> {
+        let probe = Self::probe(start, end);
+        let value = self.tree.try_get_node(probe.clone())?.value().value;
+        self.tree.delete(probe);
+        self.len -= 1;
+        value
+    }
+
+    // value stored for exactly [start, end)
+    pub fn get(&self, start: K, end: K) -> Option<V> {
+        self.tree.try_get_node(Self::probe(start, end)).and_then(|node| node.value().value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // all intervals that contain `point`, ordered by start
+    pub fn containing(&self, point: K) -> Vec<(K, K, V)> {
+        let mut found = Vec::new();
+        Self::collect(self.tree.root_node(), &point, None, &mut found);
+        found
+    }
+
+    // all intervals that overlap [start, end), ordered by start
+    pub fn overlapping(&self, start: K, end: K) -> Vec<(K, K, V)> {
+        let mut found = Vec::new();
+        if start < end {
+            Self::collect(self.tree.root_node(), &start, Some(&end), &mut found);
+        }
+        found
+    }
+
+    // every stored interval ordered by start
+    pub fn intervals(&self) -> Vec<(K, K, V)> {
+        self.tree.iter().map(Self::unpack).collect()
+    }
+
+    fn unpack(entry: IntervalEntry<K, V>) -> (K, K, V) {
+        (entry.start, entry.end, entry.value.expect("stored intervals always carry a value"))
+    }
+
+    // With `high` set this looks for overlaps with [low, high), without it for intervals that
+    // contain the point `low`. A subtree whose largest end is not past `low` is skipped, and
+    // nothing right of a node starting at or after the upper bound can match.
+    fn collect(node: Option<Node<K, V>>, low: &K, high: Option<&K>, found: &mut Vec<(K, K, V)>) {
+        let node = match node {
+            Some(node) => node,
+            None => return,
+        };
+        if node.summary().0 <= *low {
+            return;
+        }
+        Self::collect(node.left(), low, high, found);
+
+        let entry = node.value();
+        let past_upper = match high {
+            Some(high) => entry.start >= *high,
+            None => entry.start > *low,
+        };
+        if past_upper {
+            return;
+        }
+        if entry.end > *low {
+            found.push(Self::unpack(entry));
+        }
+        Self::collect(node.right(), low, high, found);
+    }
+}
+
+impl<K, V> Default for IntervalTree<K, V>
+where
+    K: PartialOrd + Clone + fmt::Debug + fmt::Display,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
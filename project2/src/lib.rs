@@ -0,0 +1,28 @@
+// The trees and everything built around them. The program in main.rs drives the AVL and
+// red-black trees through a menu and a command line; the tests and benchmarks use the rest.
+pub mod aa_tree;
+pub mod augment;
+pub mod avl_tree;
+pub mod bracket;
+pub mod btree;
+pub mod codec;
+pub mod dot;
+pub mod durable;
+pub mod interchange;
+pub mod interval_tree;
+pub mod llrb_tree;
+pub mod observer;
+pub mod ordered_set;
+pub mod persistent_tree;
+pub mod pretty;
+pub mod redblack_tree;
+pub mod rng;
+pub mod scapegoat_tree;
+pub mod shared_tree;
+pub mod skip_list;
+pub mod sorted_vec_set;
+pub mod splay_tree;
+pub mod stats;
+pub mod svg;
+pub mod treap;
+pub mod wavl_tree;
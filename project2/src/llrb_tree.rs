@@ -0,0 +1,350 @@
+use std::cmp::{max, Ordering};
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+
+type LLRBLink<T> = Option<Box<LLRBNode<T>>>;
+
+#[derive(Debug)]
+struct LLRBNode<T> {
+    key: T,
+    // color of the link from the parent, red links always lean left
+    red: bool,
+    left: LLRBLink<T>,
+    right: LLRBLink<T>,
+}
+
+// Sedgewick's left-leaning red-black tree. A red link glues a node to its parent like the keys
+// of one 2-3 tree node, so insert and delete only need three local fix-ups on the way back up
+// instead of the case analysis of `RedBlackTree`.
+#[derive(Debug)]
+pub struct LLRBTree<T> {
+    root: LLRBLink<T>,
+    len: usize,
+}
+
+// in-order iterator
+pub struct LLRBIter<'a, T> {
+    stack: Vec<&'a LLRBNode<T>>,
+}
+
+impl<'a, T> LLRBIter<'a, T> {
+    fn push_left(&mut self, mut node: &'a LLRBLink<T>) {
+        while let Some(n) = node {
+            self.stack.push(n);
+            node = &n.left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for LLRBIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+        Some(&node.key)
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> LLRBTree<T> {
+    pub fn new() -> Self {
+        LLRBTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.partial_cmp(&node.key) {
+                Some(Ordering::Less) => current = &node.left,
+                Some(Ordering::Greater) => current = &node.right,
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+        let mut root = Self::insert_node(self.root.take(), value);
+        root.red = false;
+        self.root = Some(root);
+        self.len += 1;
+        true
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        let mut root = self.root.take().unwrap();
+        // a red root lets the descent borrow from it
+        if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+            root.red = true;
+        }
+        self.root = Self::delete_node(root, value);
+        if let Some(root) = &mut self.root {
+            root.red = false;
+        }
+        self.len -= 1;
+        true
+    }
+
+    // number of nodes on the longest path, red links included
+    pub fn height(&self) -> usize {
+        fn height<T>(node: &LLRBLink<T>) -> usize {
+            node.as_ref().map_or(0, |n| 1 + max(height(&n.left), height(&n.right)))
+        }
+        height(&self.root)
+    }
+
+    pub fn leaves(&self) -> usize {
+        fn leaves<T>(node: &LLRBLink<T>) -> usize {
+            match node {
+                None => 0,
+                Some(n) if n.left.is_none() && n.right.is_none() => 1,
+                Some(n) => leaves(&n.left) + leaves(&n.right),
+            }
+        }
+        leaves(&self.root)
+    }
+
+    pub fn iter(&self) -> LLRBIter<'_, T> {
+        let mut iter = LLRBIter { stack: Vec::new() };
+        iter.push_left(&self.root);
+        iter
+    }
+
+    pub fn print_struct(&self) {
+        fn print<T: Debug>(node: &LLRBLink<T>, depth: usize, position: &str) {
+            if let Some(n) = node {
+                let color = if n.red { "Red" } else { "Black" };
+                println!("{}{}{:?} ({})", " ".repeat(depth * 2), position, n.key, color);
+                print(&n.left, depth + 1, "L: ");
+                print(&n.right, depth + 1, "R: ");
+            }
+        }
+        println!("Left-Leaning Red-Black Tree Structure:");
+        print(&self.root, 0, "Root: ");
+    }
+
+    // Report the first broken property: a black root, key order, red links leaning left, no two
+    // red links in a row and the same number of black links on every path.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if let Some(root) = &self.root {
+            if root.red {
+                return Err(format!("root {:?} is red", root.key));
+            }
+        }
+        let count = Self::check_node(&self.root, None, None)?.1;
+        if count != self.len {
+            return Err(format!("tree holds {} nodes but counts {}", count, self.len));
+        }
+        Ok(())
+    }
+
+    // black height and node count of a subtree whose keys lie strictly between `low` and `high`
+    fn check_node(node: &LLRBLink<T>, low: Option<&T>, high: Option<&T>) -> Result<(usize, usize), String> {
+        let n = match node {
+            Some(n) => n,
+            None => return Ok((0, 0)),
+        };
+        if low.is_some_and(|low| n.key <= *low) || high.is_some_and(|high| n.key >= *high) {
+            return Err(format!("key {:?} is out of order", n.key));
+        }
+        if Self::is_red(&n.right) {
+            return Err(format!("key {:?} has a right-leaning red link", n.key));
+        }
+        if n.red && Self::is_red(&n.left) {
+            return Err(format!("key {:?} has two red links in a row", n.key));
+        }
+        let (left_black, left_count) = Self::check_node(&n.left, low, Some(&n.key))?;
+        let (right_black, right_count) = Self::check_node(&n.right, Some(&n.key), high)?;
+        if left_black != right_black {
+            return Err(format!("key {:?} has black heights {} and {} below it", n.key, left_black, right_black));
+        }
+        let black = left_black + if n.red { 0 } else { 1 };
+        Ok((black, left_count + right_count + 1))
+    }
+
+    fn is_red(node: &LLRBLink<T>) -> bool {
+        node.as_ref().is_some_and(|n| n.red)
+    }
+
+    fn insert_node(node: LLRBLink<T>, value: T) -> Box<LLRBNode<T>> {
+        let mut h = match node {
+            Some(h) => h,
+            None => return Box::new(LLRBNode { key: value, red: true, left: None, right: None }),
+        };
+        if value < h.key {
+            h.left = Some(Self::insert_node(h.left.take(), value));
+        } else {
+            h.right = Some(Self::insert_node(h.right.take(), value));
+        }
+        Self::fix_up(h)
+    }
+
+    // `value` is known to be in the subtree
+    fn delete_node(mut h: Box<LLRBNode<T>>, value: &T) -> LLRBLink<T> {
+        if *value < h.key {
+            if !Self::is_red(&h.left) && !h.left.as_ref().is_some_and(|l| Self::is_red(&l.left)) {
+                h = Self::move_red_left(h);
+            }
+            h.left = Self::delete_node(h.left.take().unwrap(), value);
+        } else {
+            if Self::is_red(&h.left) {
+                h = Self::rotate_right(h);
+            }
+            if *value == h.key && h.right.is_none() {
+                return None;
+            }
+            if !Self::is_red(&h.right) && !h.right.as_ref().is_some_and(|r| Self::is_red(&r.left)) {
+                h = Self::move_red_right(h);
+            }
+            if *value == h.key {
+                // replace the key with its successor
+                let (min, rest) = Self::delete_min(h.right.take().unwrap());
+                h.key = min;
+                h.right = rest;
+            } else {
+                h.right = Self::delete_node(h.right.take().unwrap(), value);
+            }
+        }
+        Some(Self::fix_up(h))
+    }
+
+    // smallest key of a subtree and the subtree without it
+    fn delete_min(mut h: Box<LLRBNode<T>>) -> (T, LLRBLink<T>) {
+        if h.left.is_none() {
+            // a left-leaning node without a left child has no right child either
+            return (h.key, None);
+        }
+        if !Self::is_red(&h.left) && !h.left.as_ref().is_some_and(|l| Self::is_red(&l.left)) {
+            h = Self::move_red_left(h);
+        }
+        let (min, left) = Self::delete_min(h.left.take().unwrap());
+        h.left = left;
+        (min, Some(Self::fix_up(h)))
+    }
+
+    fn rotate_left(mut h: Box<LLRBNode<T>>) -> Box<LLRBNode<T>> {
+        let mut x = h.right.take().unwrap();
+        h.right = x.left.take();
+        x.red = h.red;
+        h.red = true;
+        x.left = Some(h);
+        x
+    }
+
+    fn rotate_right(mut h: Box<LLRBNode<T>>) -> Box<LLRBNode<T>> {
+        let mut x = h.left.take().unwrap();
+        h.left = x.right.take();
+        x.red = h.red;
+        h.red = true;
+        x.right = Some(h);
+        x
+    }
+
+    // split or merge a temporary 4-node
+    fn flip_colors(h: &mut LLRBNode<T>) {
+        h.red = !h.red;
+        for child in [&mut h.left, &mut h.right].into_iter().flatten() {
+            child.red = !child.red;
+        }
+    }
+
+    // make h.left or one of its children red so the descent to the left never ends on a 2-node
+    fn move_red_left(mut h: Box<LLRBNode<T>>) -> Box<LLRBNode<T>> {
+        Self::flip_colors(&mut h);
+        if h.right.as_ref().is_some_and(|r| Self::is_red(&r.left)) {
+            h.right = Some(Self::rotate_right(h.right.take().unwrap()));
+            h = Self::rotate_left(h);
+            Self::flip_colors(&mut h);
+        }
+        h
+    }
+
+    fn move_red_right(mut h: Box<LLRBNode<T>>) -> Box<LLRBNode<T>> {
+        Self::flip_colors(&mut h);
+        if h.left.as_ref().is_some_and(|l| Self::is_red(&l.left)) {
+            h = Self::rotate_right(h);
+            Self::flip_colors(&mut h);
+        }
+        h
+    }
+
+    // restore the left-leaning shape on the way back up
+    fn fix_up(mut h: Box<LLRBNode<T>>) -> Box<LLRBNode<T>> {
+        if Self::is_red(&h.right) && !Self::is_red(&h.left) {
+            h = Self::rotate_left(h);
+        }
+        if Self::is_red(&h.left) && h.left.as_ref().is_some_and(|l| Self::is_red(&l.left)) {
+            h = Self::rotate_right(h);
+        }
+        if Self::is_red(&h.left) && Self::is_red(&h.right) {
+            Self::flip_colors(&mut h);
+        }
+        h
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> Default for LLRBTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> OrderedSet<T> for LLRBTree<T> {
+    fn new() -> Self {
+        LLRBTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        LLRBTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        LLRBTree::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        LLRBTree::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        LLRBTree::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        LLRBTree::is_empty(self)
+    }
+
+    fn height(&self) -> usize {
+        LLRBTree::height(self)
+    }
+
+    fn leaves(&self) -> usize {
+        LLRBTree::leaves(self)
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        self.iter().cloned().collect()
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        LLRBTree::check_invariants(self)
+    }
+}
@@ -1,10 +1,19 @@
-mod avl_tree;
-mod redblack_tree;
+mod cli;
+mod commands;
+mod repl;
 
 use std::str::FromStr;
 use std::{io, fmt::{Debug, Display}};
-use redblack_tree::RedBlackTree;
-use avl_tree::AVLTree;
+use project::redblack_tree::RedBlackTree;
+use project::avl_tree::AVLTree;
+use commands::{Command, CommandTree};
+use project::aa_tree::AATree;
+use project::llrb_tree::LLRBTree;
+use project::ordered_set::OrderedSet;
+use project::scapegoat_tree::ScapegoatTree;
+use project::splay_tree::SplayTree;
+use project::treap::Treap;
+use project::wavl_tree::WAVLTree;
 
 
 fn handle_input() -> Option<i32> {
@@ -56,6 +65,73 @@ impl GetExample for f32 {
     fn get_example_delete(&self) -> String{return "3.2 1.5".to_string();}
 }
 
+// run one menu operation through the dispatch the command-line mode uses and print the outcome
+fn run_menu_command<T: Clone + Display, Tree: CommandTree<T>>(tree: &mut Tree, command: Command<T>) {
+    match commands::run(tree, &command) {
+        Ok(outcome) => {
+            for line in outcome.describe() {
+                println!("{}", line);
+            }
+        },
+        Err(error) => println!("{}", error),
+    }
+}
+
+// ask for a file name and write `contents` there, an empty answer keeps `default_path`
+fn export_to_file(contents: &str, default_path: &str) {
+    println!("Please input the file to write the tree to (press enter for {}):", default_path);
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Cannot read input!");
+    let path = if input.trim().is_empty() { default_path } else { input.trim() };
+    match std::fs::write(path, contents) {
+        Ok(()) => println!("The tree was written to {}.", path),
+        Err(error) => println!("Cannot write {}: {}", path, error),
+    }
+}
+
+// the text formats a tree can be saved in
+enum SaveFormat {
+    JsonArray,
+    JsonTree,
+    Csv,
+}
+
+fn choose_save_format() -> SaveFormat {
+    loop {
+        println!("Please choose the format to save the tree in");
+        println!("1. JSON array of the values in order");
+        println!("2. JSON with the whole tree structure");
+        println!("3. CSV");
+        match handle_input() {
+            Some(1) => return SaveFormat::JsonArray,
+            Some(2) => return SaveFormat::JsonTree,
+            Some(3) => return SaveFormat::Csv,
+            Some(_) => println!("Please choose between 1 and 3."),
+            None => println!("No valid integer was entered."),
+        }
+    }
+}
+
+// ask for a file to load a tree from, returns its path and contents
+fn read_from_file(default_path: &str) -> Option<(String, String)> {
+    println!("Please input the file to load the tree from (press enter for {}):", default_path);
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Cannot read input!");
+    let path = if input.trim().is_empty() { default_path } else { input.trim() };
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Some((path.to_string(), contents)),
+        Err(error) => {
+            println!("Cannot read {}: {}", path, error);
+            None
+        },
+    }
+}
+
+// files ending in .csv are read as CSV, everything else as JSON
+fn is_csv(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".csv")
+}
+
 fn avl_tree_interface<T: GetExample + Copy + Clone + Debug + PartialOrd + Display + FromStr>(type_value:T){
     println!("----------------------------------------");
     println!("Welcome for using AVL Tree!");
@@ -74,7 +150,11 @@ fn avl_tree_interface<T: GetExample + Copy + Clone + Debug + PartialOrd + Displa
         println!("5. Print in-order traversal of the tree.");
         println!("6. Check if the tree is empty.");
         println!("7. Print the tree showing its colors and structure.");
-        println!("8. Quit.");
+        println!("8. Export the tree to a Graphviz DOT file.");
+        println!("9. Export the tree to an SVG file.");
+        println!("10. Save the tree to a JSON or CSV file.");
+        println!("11. Load the tree from a JSON or CSV file.");
+        println!("12. Quit.");
         println!("----------------------------------------");
         
         let user_choice = handle_input();
@@ -82,64 +162,29 @@ fn avl_tree_interface<T: GetExample + Copy + Clone + Debug + PartialOrd + Displa
             Some(1) => {
                 println!("----------------------------------------");
                 println!("Please input the value(s) of the node(s) that you want to insert: Separate by one whitespace. e.g. {}", type_value.get_example_insert());
-                let input = convert_input().unwrap();
-                let mut output = Vec::new();
-                for i in input.clone() {
-                    let n = m_avl_tree.search(i);
-                    let mut result = false;
-                    if let Some(_) = n {
-                        result = true;
-                    };
-                    if !result {
-                        m_avl_tree.insert(i);
-                        output.push(i);
-                    } else {
-                        println!("INSERT FAILED: Node({:?}) already exists!", i);
-                    };
-                };
-                if output.len() != 0 {
-                    println!("Insert {:?} successfully.", output);
-                };
+                run_menu_command(&mut m_avl_tree, Command::Insert(convert_input().unwrap()));
             },
             Some(2) => {
                 println!("----------------------------------------");
                 println!("Current tree contains values above {:#?}", m_avl_tree.print_in_order());
                 println!("Please input the value(s) of the node(s) that you want to delete: Separate by one whitespace in decending order. e.g. {}", type_value.get_example_delete());
-                let input = convert_input().unwrap();
-                for i in input.clone() {
-                    let n = m_avl_tree.search(i);
-                    let mut result = false;
-                    if let Some(_) = n {
-                        result = true;
-                    };
-                    if result {
-                        m_avl_tree.delete(i);
-                        println!("Delete {} succeed!", i);
-                    }else {
-                        println!("Node {} doesn't exist!", i);
-                    };
-                };
+                run_menu_command(&mut m_avl_tree, Command::Delete(convert_input().unwrap()));
             },
             Some(3) => {
                 println!("----------------------------------------");
-                println!("The number of leaves is: {}", m_avl_tree.count_leaves());
+                run_menu_command(&mut m_avl_tree, Command::Leaves);
             },
             Some(4) => {
                 println!("----------------------------------------");
-                println!("The height of the tree is: {}", m_avl_tree.tree_height());
+                run_menu_command(&mut m_avl_tree, Command::Height);
             },
             Some(5) => {
                 println!("----------------------------------------");
-                println!("In-order traversal: ");
-                m_avl_tree.display_in_order();
+                run_menu_command(&mut m_avl_tree, Command::InOrder);
             },
             Some(6) => {
                 println!("----------------------------------------");
-                if m_avl_tree.check_empty() {
-                    println!("This tree is empty");
-                } else {
-                    println!("This tree is not empty");
-                }
+                run_menu_command(&mut m_avl_tree, Command::Empty);
             },
             Some(7) => {
                 println!("----------------------------------------");
@@ -147,6 +192,36 @@ fn avl_tree_interface<T: GetExample + Copy + Clone + Debug + PartialOrd + Displa
                 m_avl_tree.print_struct();
             },
             Some(8) => {
+                println!("----------------------------------------");
+                export_to_file(&m_avl_tree.to_dot(), "avl_tree.dot");
+            },
+            Some(9) => {
+                println!("----------------------------------------");
+                export_to_file(&m_avl_tree.to_svg(), "avl_tree.svg");
+            },
+            Some(10) => {
+                println!("----------------------------------------");
+                let (contents, path) = match choose_save_format() {
+                    SaveFormat::JsonArray => (m_avl_tree.to_json_array(), "avl_tree.json"),
+                    SaveFormat::JsonTree => (m_avl_tree.to_json_tree(), "avl_tree.json"),
+                    SaveFormat::Csv => (m_avl_tree.to_csv(), "avl_tree.csv"),
+                };
+                export_to_file(&contents, path);
+            },
+            Some(11) => {
+                println!("----------------------------------------");
+                if let Some((path, contents)) = read_from_file("avl_tree.json") {
+                    let loaded = if is_csv(&path) { AVLTree::from_csv(&contents) } else { AVLTree::from_json(&contents) };
+                    match loaded {
+                        Ok(tree) => {
+                            m_avl_tree = tree;
+                            println!("The tree was loaded from {}.", path);
+                        },
+                        Err(error) => println!("Cannot load {}: {}", path, error),
+                    }
+                }
+            },
+            Some(12) => {
                 println!("----------------------------------------");
                 println!("Thank you for using!");
                 break;
@@ -163,7 +238,6 @@ fn rb_tree_interface<T: GetExample + Clone + Debug + Display + PartialOrd + From
 
     let convert_input: Box<dyn Fn() -> Result<Vec<T>, ()>> = Box::new(input_to_vec);
     let mut m_rb_tree: RedBlackTree<T> = RedBlackTree::new();
-    let mut output = Vec::new(); // Define output here
 
     loop {
         println!("----------------------------------------");
@@ -175,27 +249,18 @@ fn rb_tree_interface<T: GetExample + Clone + Debug + Display + PartialOrd + From
         println!("5. Print in-order traversal of the tree.");
         println!("6. Check if the tree is empty.");
         println!("7. Print the tree showing its colors and structure.");
-        println!("8. Quit.");
+        println!("8. Export the tree to a Graphviz DOT file.");
+        println!("9. Export the tree to an SVG file.");
+        println!("10. Save the tree to a JSON or CSV file.");
+        println!("11. Load the tree from a JSON or CSV file.");
+        println!("12. Quit.");
         println!("----------------------------------------");
         let user_choice = handle_input();
         match user_choice {
             Some(1) => {
                 println!("----------------------------------------");
                 println!("Please input the value(s) of the node(s) that you want to insert: Separate by one whitespace. e.g. {}", type_value.get_example_insert());
-                let input = convert_input().unwrap();
-                // let mut output = Vec::new();
-                for i in input.clone() {
-                    if !m_rb_tree.search_node(i) {
-                        m_rb_tree.insert(i);
-                        output.push(i);
-                    } else {
-                        println!("INSERT FAILED: Node({:?}) already exists!", i);
-                    };
-                };
-                if output.len() != 0 {
-                    println!("Insert {:?} successfully.", output);
-                    println!("");
-                };
+                run_menu_command(&mut m_rb_tree, Command::Insert(convert_input().unwrap()));
             },
             Some(2) => {
                 println!("----------------------------------------");
@@ -203,119 +268,220 @@ fn rb_tree_interface<T: GetExample + Clone + Debug + Display + PartialOrd + From
                 println!();
                 println!("Current tree contains values above");
                 println!("Please input the value(s) of the node(s) that you want to delete: Separate by one whitespace in decending order. e.g. {}", type_value.get_example_delete());
-                let input = convert_input().unwrap();
-                for i in input.clone() {
-                    if m_rb_tree.search_node(i) {
-                        // Remove the element from output
-                        output.retain(|x| x != &i);
-                        m_rb_tree.clear();
-                        for value in &output {
-                            if !m_rb_tree.search_node(*value) {
-                                m_rb_tree.insert(*value);
-                            }
-                        }
+                run_menu_command(&mut m_rb_tree, Command::Delete(convert_input().unwrap()));
+            },
+            Some(3) => {
+                println!("----------------------------------------");
+                run_menu_command(&mut m_rb_tree, Command::Leaves);
+            },
+            Some(4) => {
+                println!("----------------------------------------");
+                run_menu_command(&mut m_rb_tree, Command::Height);
+            },
+            Some(5) => {
+                println!("----------------------------------------");
+                run_menu_command(&mut m_rb_tree, Command::InOrder);
+            },
+            Some(6) => {
+                println!("----------------------------------------");
+                run_menu_command(&mut m_rb_tree, Command::Empty);
+            },
+            Some(7) => {
+                println!("----------------------------------------");
+                println!("The tree structure is:");
+                m_rb_tree.print_tree();
+            },
+            Some(8) => {
+                println!("----------------------------------------");
+                export_to_file(&m_rb_tree.to_dot(), "rb_tree.dot");
+            },
+            Some(9) => {
+                println!("----------------------------------------");
+                export_to_file(&m_rb_tree.to_svg(), "rb_tree.svg");
+            },
+            Some(10) => {
+                println!("----------------------------------------");
+                let (contents, path) = match choose_save_format() {
+                    SaveFormat::JsonArray => (m_rb_tree.to_json_array(), "rb_tree.json"),
+                    SaveFormat::JsonTree => (m_rb_tree.to_json_tree(), "rb_tree.json"),
+                    SaveFormat::Csv => (m_rb_tree.to_csv(), "rb_tree.csv"),
+                };
+                export_to_file(&contents, path);
+            },
+            Some(11) => {
+                println!("----------------------------------------");
+                if let Some((path, contents)) = read_from_file("rb_tree.json") {
+                    let loaded = if is_csv(&path) { RedBlackTree::from_csv(&contents) } else { RedBlackTree::from_json(&contents) };
+                    match loaded {
+                        Ok(tree) => {
+                            m_rb_tree = tree;
+                            println!("The tree was loaded from {}.", path);
+                        },
+                        Err(error) => println!("Cannot load {}: {}", path, error),
+                    }
+                }
+            },
+            
+            Some(12) => {
+                println!("----------------------------------------");
+                println!("Thank you for using!");
+                break;
+            },
+            _ => println!("Wrong input! Input should be a number from the list, please try again..."),
+        };
+    };
+}
+
+
+
+// menu for the trees that are only driven through the common `OrderedSet` interface
+fn ordered_set_interface<T, S>(type_value: T, name: &str)
+where
+    T: GetExample + Copy + Debug + Display + PartialOrd + FromStr,
+    S: OrderedSet<T>,
+{
+    println!("----------------------------------------");
+    println!("Welcome for using {}!", name);
+    println!("----------------------------------------");
+    let mut tree = S::new();
+
+    loop {
+        println!("----------------------------------------");
+        println!("Please choose the operation you want to do: (input corresponding number)");
+        println!("1. Insert node(s) to the tree.");
+        println!("2. Delete node(s) from the tree.");
+        println!("3. Count the number of leaves in a tree.");
+        println!("4. Return the height of a tree.");
+        println!("5. Print in-order traversal of the tree.");
+        println!("6. Check if the tree is empty.");
+        println!("7. Print the tree structure.");
+        println!("8. Quit.");
+        println!("----------------------------------------");
+
+        match handle_input() {
+            Some(1) => {
+                println!("----------------------------------------");
+                println!("Please input the value(s) of the node(s) that you want to insert: Separate by one whitespace. e.g. {}", type_value.get_example_insert());
+                let mut output = Vec::new();
+                for i in input_to_vec::<T>().unwrap() {
+                    if tree.insert(i) {
+                        output.push(i);
+                    } else {
+                        println!("INSERT FAILED: Node({:?}) already exists!", i);
+                    }
+                }
+                if !output.is_empty() {
+                    println!("Insert {:?} successfully.", output);
+                }
+            },
+            Some(2) => {
+                println!("----------------------------------------");
+                println!("Current tree contains values {:?}", tree.to_vec());
+                println!("Please input the value(s) of the node(s) that you want to delete: Separate by one whitespace. e.g. {}", type_value.get_example_delete());
+                for i in input_to_vec::<T>().unwrap() {
+                    if tree.delete(i) {
                         println!("Delete {} succeed!", i);
-                    }else{
+                    } else {
                         println!("Node {} doesn't exist!", i);
-                    };
-                };
+                    }
+                }
             },
             Some(3) => {
                 println!("----------------------------------------");
-                println!("The number of leaves is: {}", m_rb_tree.get_leaves_number());
+                println!("The number of leaves is: {}", tree.leaves());
             },
             Some(4) => {
                 println!("----------------------------------------");
-                println!("The height of the tree is: {}", m_rb_tree.get_height());
+                println!("The height of the tree is: {}", tree.height());
             },
             Some(5) => {
                 println!("----------------------------------------");
                 println!("In-order traversal: ");
-                m_rb_tree.show_in_order_traversal(&|&x: &T| print!("{} \t", x));
-                println!("");
+                for value in tree.to_vec() {
+                    print!("{} \t", value);
+                }
+                println!();
             },
             Some(6) => {
                 println!("----------------------------------------");
-                if m_rb_tree.is_empty() {
+                if tree.is_empty() {
                     println!("This tree is empty");
                 } else {
                     println!("This tree is not empty");
-                };
+                }
             },
             Some(7) => {
                 println!("----------------------------------------");
                 println!("The tree structure is:");
-                m_rb_tree.print_tree();
+                tree.print_structure();
             },
-            
             Some(8) => {
                 println!("----------------------------------------");
                 println!("Thank you for using!");
                 break;
             },
             _ => println!("Wrong input! Input should be a number from the list, please try again..."),
+        }
+    }
+}
+
+// ask for the type of the stored values, 1 for integers and 2 for floating-point numbers
+fn choose_value_type() -> i32 {
+    loop {
+        println!("Please choose the type of value you want to add");
+        println!("1. Integer");
+        println!("2. Floating-point number");
+        println!();
+        match handle_input() {
+            Some(choice @ 1..=2) => return choice,
+            Some(_) => println!("Please choose between 1 and 2."),
+            None => println!("No valid integer was entered."),
         };
-    };
+    }
 }
 
+// open the menu of the tree picked in the tree-selection menu
+fn tree_interface<T: GetExample + Copy + Debug + Display + PartialOrd + FromStr>(tree: i32, type_value: T) {
+    match tree {
+        1 => avl_tree_interface(type_value),
+        2 => rb_tree_interface(type_value),
+        3 => ordered_set_interface::<T, LLRBTree<T>>(type_value, "Left-Leaning Red-Black Tree"),
+        4 => ordered_set_interface::<T, Treap<T>>(type_value, "Treap"),
+        5 => ordered_set_interface::<T, SplayTree<T>>(type_value, "Splay Tree"),
+        6 => ordered_set_interface::<T, ScapegoatTree<T>>(type_value, "Scapegoat Tree"),
+        7 => ordered_set_interface::<T, AATree<T>>(type_value, "AA Tree"),
+        _ => ordered_set_interface::<T, WAVLTree<T>>(type_value, "WAVL Tree"),
+    }
+}
 
 fn main() {
-    let mut user_choice;
-    println!("=========Welcome for using AVL and Red-Black tree!=========");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+    println!("=========Welcome for using the balanced tree collection!=========");
+    println!("(Run with --repl to type commands such as `insert 1 2 3` instead, or --help for more.)");
     loop {
         println!("Please choose the type of tree you want to use");
         println!("1. AVL Tree");
         println!("2. Red-Black Tree");
-        println!("");
-        user_choice = handle_input();
-        match user_choice { // Continue only if the input is either 1 or 2
-                Some(1) => {
-                loop {
-                    println!("Please choose the type of value you want to add");
-                    println!("1. Integer");
-                    println!("2. Floating-point number");
-                    println!("");
-                    let user_choice = handle_input();
-                    match user_choice {
-                        Some(1) => {
-                            avl_tree_interface::<i32>(1);
-                            break;
-                        },
-                        Some(2) => {
-                            avl_tree_interface::<f32>(1.0);
-                            break;
-                        },
-                        Some(_) => println!("Please choose between 1 and 2."),
-                        None => println!("No valid integer was entered."),
-                    };
-                };
-                break;
-            },
-            Some(2) => {
-                loop {
-                    println!("Please choose the type of value you want to add");
-                    println!("1. Integer");
-                    println!("2. Floating-point number");
-                    println!("");
-                    let user_choice = handle_input();
-                    match user_choice {
-                        Some(1) => {
-                            rb_tree_interface::<i32>(1);
-                            break;
-                        },
-                        Some(2) => {
-                            rb_tree_interface::<f32>(1.0);
-                            break;
-                        },
-                        Some(_) => println!("Please choose between 1 and 2."),
-                        None => println!("No valid integer was entered."),
-                    };
+        println!("3. Left-Leaning Red-Black Tree");
+        println!("4. Treap");
+        println!("5. Splay Tree");
+        println!("6. Scapegoat Tree");
+        println!("7. AA Tree");
+        println!("8. WAVL Tree");
+        println!();
+        match handle_input() {
+            Some(tree @ 1..=8) => {
+                match choose_value_type() {
+                    1 => tree_interface::<i32>(tree, 1),
+                    _ => tree_interface::<f32>(tree, 1.0),
                 };
                 break;
             },
-
-            Some(_) => println!("Please choose between 1 and 2."),
+            Some(_) => println!("Please choose a number between 1 and 8."),
             None => println!("No valid integer was entered."),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,76 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+// Callbacks for the structural events of the AVL and red-black trees. Every method has an
+// empty default, an observer only implements what it cares about. The trees call them in the
+// middle of an operation, so an observer sees the keys involved but cannot look at the tree.
+pub trait TreeObserver<T> {
+    // `key` was added, also called for a new occurrence in multiset mode
+    fn on_insert(&mut self, _key: &T) {}
+    // `key` was removed, called once per occurrence: removing a node that holds three
+    // occurrences reports three deletes, whether by delete, remove_all or a cursor
+    fn on_delete(&mut self, _key: &T) {}
+    // `pivot` is the key of the node that moves down, a double rotation reports two rotations
+    fn on_rotate(&mut self, _pivot: &T, _direction: Direction) {}
+    // red-black trees only, `red` is the new color
+    fn on_recolor(&mut self, _key: &T, _red: bool) {}
+    // AVL trees only, the stored height of the node holding `key` changed
+    fn on_height_change(&mut self, _key: &T, _old: i32, _new: i32) {}
+}
+
+// shared so the caller can keep a handle and read what the observer collected
+pub type ObserverRef<T> = Rc<RefCell<dyn TreeObserver<T>>>;
+
+// The optional observer a tree carries. Clones of a tree share the observer.
+pub struct ObserverSlot<T> {
+    observer: Option<ObserverRef<T>>,
+}
+
+impl<T> ObserverSlot<T> {
+    pub fn set(&mut self, observer: Option<ObserverRef<T>>) {
+        self.observer = observer;
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    // hand the observer to `event`, nothing happens when none is attached
+    pub fn notify(&self, event: impl FnOnce(&mut dyn TreeObserver<T>)) {
+        if let Some(observer) = &self.observer {
+            event(&mut *observer.borrow_mut());
+        }
+    }
+}
+
+impl<T> Default for ObserverSlot<T> {
+    fn default() -> Self {
+        ObserverSlot { observer: None }
+    }
+}
+
+impl<T> Clone for ObserverSlot<T> {
+    fn clone(&self) -> Self {
+        ObserverSlot { observer: self.observer.clone() }
+    }
+}
+
+impl<T> fmt::Debug for ObserverSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObserverSlot").field("attached", &self.is_attached()).finish()
+    }
+}
+
+// like the counters, the observer does not take part in comparing two trees
+impl<T> PartialEq for ObserverSlot<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
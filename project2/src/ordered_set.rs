@@ -0,0 +1,31 @@
+// Interface shared by every set implementation in the crate, so the benches and the CLI
+// can drive them the same way. Each tree keeps its own richer inherent API next to it.
+pub trait OrderedSet<T> {
+    fn new() -> Self where Self: Sized;
+
+    // add `value`, false if it was already there
+    fn insert(&mut self, value: T) -> bool;
+    // remove `value`, false if it was not there
+    fn delete(&mut self, value: T) -> bool;
+    fn contains(&self, value: T) -> bool;
+
+    // number of stored values
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // nodes on the longest root-to-leaf path, or the number of levels for the structures
+    // that are not binary trees; 0 for an empty set
+    fn height(&self) -> usize;
+    fn leaves(&self) -> usize;
+
+    // values in increasing order
+    fn to_vec(&self) -> Vec<T>;
+
+    // print the shape of the structure to stdout
+    fn print_structure(&self);
+
+    // Err describes the first broken structural invariant found
+    fn check_invariants(&self) -> Result<(), String>;
+}
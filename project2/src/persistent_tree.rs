@@ -0,0 +1,508 @@
+use std::cmp::{max, Ordering};
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::thread;
+
+type PersistentLink<T> = Option<Arc<PersistentNode<T>>>;
+
+// nodes are never modified after creation, so every version can share them
+#[derive(Debug)]
+struct PersistentNode<T> {
+    key: T,
+    height: i32,
+    size: usize,
+    left: PersistentLink<T>,
+    right: PersistentLink<T>,
+}
+
+// Persistent AVL tree. `insert` and `remove` leave `self` untouched and return a new version
+// that copies only the nodes on the search path, every other subtree is shared through `Arc`.
+// Cloning a version is O(1), and versions can be handed to other threads.
+#[derive(Debug)]
+pub struct PersistentAVLTree<T> {
+    root: PersistentLink<T>,
+}
+
+impl<T> Clone for PersistentAVLTree<T> {
+    fn clone(&self) -> Self {
+        PersistentAVLTree { root: self.root.clone() }
+    }
+}
+
+impl<T: PartialOrd + Clone> PersistentAVLTree<T> {
+    pub fn new() -> Self {
+        PersistentAVLTree { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn tree_height(&self) -> i32 {
+        Self::height(&self.root)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.partial_cmp(&node.key) {
+                Some(Ordering::Less) => current = &node.left,
+                Some(Ordering::Greater) => current = &node.right,
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    // new version with `value` added, the same version again if it was already there
+    pub fn insert(&self, value: T) -> Self {
+        match &self.root {
+            None => PersistentAVLTree { root: Some(Self::make(value, None, None)) },
+            Some(root) => match Self::insert_node(root, value) {
+                Some(new_root) => PersistentAVLTree { root: Some(new_root) },
+                None => self.clone(),
+            },
+        }
+    }
+
+    // new version without `value`, the same version again if it was not there
+    pub fn remove(&self, value: &T) -> Self {
+        match &self.root {
+            None => self.clone(),
+            Some(root) => match Self::remove_node(root, value) {
+                Some(new_root) => PersistentAVLTree { root: new_root },
+                None => self.clone(),
+            },
+        }
+    }
+
+    // true when both versions hang off the very same root node
+    pub fn shares_root_with(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    // keys in order
+    pub fn iter(&self) -> PersistentIter<T> {
+        let mut iter = PersistentIter { stack: Vec::new() };
+        iter.push_left(&self.root);
+        iter
+    }
+
+    fn height(node: &PersistentLink<T>) -> i32 {
+        node.as_ref().map_or(0, |n| n.height)
+    }
+
+    fn size(node: &PersistentLink<T>) -> usize {
+        node.as_ref().map_or(0, |n| n.size)
+    }
+
+    fn make(key: T, left: PersistentLink<T>, right: PersistentLink<T>) -> Arc<PersistentNode<T>> {
+        let height = 1 + max(Self::height(&left), Self::height(&right));
+        let size = 1 + Self::size(&left) + Self::size(&right);
+        Arc::new(PersistentNode { key, height, size, left, right })
+    }
+
+    // build a node from two subtrees whose heights differ by at most two, rotating copies if needed
+    fn balance(key: T, left: PersistentLink<T>, right: PersistentLink<T>) -> Arc<PersistentNode<T>> {
+        let factor = Self::height(&left) - Self::height(&right);
+        if factor > 1 {
+            let l = left.unwrap();
+            if Self::height(&l.left) >= Self::height(&l.right) {
+                // single right rotation
+                let new_right = Self::make(key, l.right.clone(), right);
+                Self::make(l.key.clone(), l.left.clone(), Some(new_right))
+            } else {
+                // left-right double rotation
+                let lr = l.right.clone().unwrap();
+                let new_left = Self::make(l.key.clone(), l.left.clone(), lr.left.clone());
+                let new_right = Self::make(key, lr.right.clone(), right);
+                Self::make(lr.key.clone(), Some(new_left), Some(new_right))
+            }
+        } else if factor < -1 {
+            let r = right.unwrap();
+            if Self::height(&r.right) >= Self::height(&r.left) {
+                // single left rotation
+                let new_left = Self::make(key, left, r.left.clone());
+                Self::make(r.key.clone(), Some(new_left), r.right.clone())
+            } else {
+                // right-left double rotation
+                let rl = r.left.clone().unwrap();
+                let new_left = Self::make(key, left, rl.left.clone());
+                let new_right = Self::make(r.key.clone(), rl.right.clone(), r.right.clone());
+                Self::make(rl.key.clone(), Some(new_left), Some(new_right))
+            }
+        } else {
+            Self::make(key, left, right)
+        }
+    }
+
+    // copy of the subtree with `value` inserted, None if the key already exists
+    fn insert_node(node: &Arc<PersistentNode<T>>, value: T) -> Option<Arc<PersistentNode<T>>> {
+        match value.partial_cmp(&node.key) {
+            Some(Ordering::Less) => {
+                let new_left = match &node.left {
+                    Some(left) => Self::insert_node(left, value)?,
+                    None => Self::make(value, None, None),
+                };
+                Some(Self::balance(node.key.clone(), Some(new_left), node.right.clone()))
+            },
+            Some(Ordering::Greater) => {
+                let new_right = match &node.right {
+                    Some(right) => Self::insert_node(right, value)?,
+                    None => Self::make(value, None, None),
+                };
+                Some(Self::balance(node.key.clone(), node.left.clone(), Some(new_right)))
+            },
+            _ => None,
+        }
+    }
+
+    // copy of the subtree with `value` removed, None if the key is missing
+    fn remove_node(node: &Arc<PersistentNode<T>>, value: &T) -> Option<PersistentLink<T>> {
+        match value.partial_cmp(&node.key) {
+            Some(Ordering::Less) => {
+                let new_left = Self::remove_node(node.left.as_ref()?, value)?;
+                Some(Some(Self::balance(node.key.clone(), new_left, node.right.clone())))
+            },
+            Some(Ordering::Greater) => {
+                let new_right = Self::remove_node(node.right.as_ref()?, value)?;
+                Some(Some(Self::balance(node.key.clone(), node.left.clone(), new_right)))
+            },
+            _ => match (&node.left, &node.right) {
+                (None, right) => Some(right.clone()),
+                (left, None) => Some(left.clone()),
+                (left, Some(right)) => {
+                    // replace the key with its in-order successor
+                    let (successor, new_right) = Self::remove_min(right);
+                    Some(Some(Self::balance(successor, left.clone(), new_right)))
+                },
+            },
+        }
+    }
+
+    // smallest key of a subtree and a copy of the subtree without it
+    fn remove_min(node: &Arc<PersistentNode<T>>) -> (T, PersistentLink<T>) {
+        match &node.left {
+            None => (node.key.clone(), node.right.clone()),
+            Some(left) => {
+                let (min, new_left) = Self::remove_min(left);
+                (min, Some(Self::balance(node.key.clone(), new_left, node.right.clone())))
+            },
+        }
+    }
+
+    // balanced tree from strictly increasing values in O(n)
+    pub fn from_sorted(values: &[T]) -> Self {
+        PersistentAVLTree { root: Self::build(values) }
+    }
+
+    // all keys of both versions
+    pub fn union(&self, other: &Self) -> Self {
+        PersistentAVLTree { root: Self::union_node(&self.root, &other.root) }
+    }
+
+    // keys present in both versions
+    pub fn intersection(&self, other: &Self) -> Self {
+        PersistentAVLTree { root: Self::intersection_node(&self.root, &other.root) }
+    }
+
+    // keys below `value`, whether `value` is present, and keys above it
+    pub fn split(&self, value: &T) -> (Self, bool, Self) {
+        let (left, found, right) = Self::split_node(&self.root, value);
+        (PersistentAVLTree { root: left }, found, PersistentAVLTree { root: right })
+    }
+
+    // `f` applied to every key in order
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Vec<U> {
+        let mut out = Vec::with_capacity(self.len());
+        Self::map_node(&self.root, &f, &mut out);
+        out
+    }
+
+    // Join two trees around `key`, every key of `left` has to be smaller and every key of
+    // `right` larger. Walks down the taller side to the height of the other one, O(|h1 - h2|).
+    fn join(left: PersistentLink<T>, key: T, right: PersistentLink<T>) -> PersistentLink<T> {
+        let (left_height, right_height) = (Self::height(&left), Self::height(&right));
+        if left_height > right_height + 1 {
+            let l = left.unwrap();
+            let new_right = Self::join(l.right.clone(), key, right);
+            Some(Self::balance(l.key.clone(), l.left.clone(), new_right))
+        } else if right_height > left_height + 1 {
+            let r = right.unwrap();
+            let new_left = Self::join(left, key, r.left.clone());
+            Some(Self::balance(r.key.clone(), new_left, r.right.clone()))
+        } else {
+            Some(Self::make(key, left, right))
+        }
+    }
+
+    // join without a middle key, the largest key of `left` takes that place
+    fn join_pair(left: PersistentLink<T>, right: PersistentLink<T>) -> PersistentLink<T> {
+        match &left {
+            None => right,
+            Some(l) => {
+                let (max_key, rest) = Self::remove_max(l);
+                Self::join(rest, max_key, right)
+            },
+        }
+    }
+
+    // largest key of a subtree and a copy of the subtree without it
+    fn remove_max(node: &Arc<PersistentNode<T>>) -> (T, PersistentLink<T>) {
+        match &node.right {
+            None => (node.key.clone(), node.left.clone()),
+            Some(right) => {
+                let (max_key, new_right) = Self::remove_max(right);
+                (max_key, Some(Self::balance(node.key.clone(), node.left.clone(), new_right)))
+            },
+        }
+    }
+
+    fn split_node(node: &PersistentLink<T>, value: &T) -> (PersistentLink<T>, bool, PersistentLink<T>) {
+        let n = match node {
+            Some(n) => n,
+            None => return (None, false, None),
+        };
+        match value.partial_cmp(&n.key) {
+            Some(Ordering::Less) => {
+                let (left, found, middle) = Self::split_node(&n.left, value);
+                (left, found, Self::join(middle, n.key.clone(), n.right.clone()))
+            },
+            Some(Ordering::Greater) => {
+                let (middle, found, right) = Self::split_node(&n.right, value);
+                (Self::join(n.left.clone(), n.key.clone(), middle), found, right)
+            },
+            _ => (n.left.clone(), true, n.right.clone()),
+        }
+    }
+
+    fn build(values: &[T]) -> PersistentLink<T> {
+        if values.is_empty() {
+            return None;
+        }
+        let mid = values.len() / 2;
+        let left = Self::build(&values[..mid]);
+        let right = Self::build(&values[mid + 1..]);
+        Some(Self::make(values[mid].clone(), left, right))
+    }
+
+    // split `a` around the root of `b`, then merge the matching halves
+    fn union_node(a: &PersistentLink<T>, b: &PersistentLink<T>) -> PersistentLink<T> {
+        let b_node = match (a, b) {
+            (None, _) => return b.clone(),
+            (_, None) => return a.clone(),
+            (_, Some(b_node)) => b_node,
+        };
+        let (left, _, right) = Self::split_node(a, &b_node.key);
+        let left = Self::union_node(&left, &b_node.left);
+        let right = Self::union_node(&right, &b_node.right);
+        Self::join(left, b_node.key.clone(), right)
+    }
+
+    fn intersection_node(a: &PersistentLink<T>, b: &PersistentLink<T>) -> PersistentLink<T> {
+        let b_node = match (a, b) {
+            (Some(_), Some(b_node)) => b_node,
+            _ => return None,
+        };
+        let (left, found, right) = Self::split_node(a, &b_node.key);
+        let left = Self::intersection_node(&left, &b_node.left);
+        let right = Self::intersection_node(&right, &b_node.right);
+        if found {
+            Self::join(left, b_node.key.clone(), right)
+        } else {
+            Self::join_pair(left, right)
+        }
+    }
+
+    fn map_node<U>(node: &PersistentLink<T>, f: &impl Fn(&T) -> U, out: &mut Vec<U>) {
+        if let Some(n) = node {
+            Self::map_node(&n.left, f, out);
+            out.push(f(&n.key));
+            Self::map_node(&n.right, f, out);
+        }
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> PersistentAVLTree<T> {
+    // Report the first broken property of this version: key order, stored heights and sizes,
+    // and balance factors. Versions share nodes, so a bad node shows up in all of them.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        Self::check_node(&self.root, None, None).map(|_| ())
+    }
+
+    // height of a subtree whose keys must lie strictly between `low` and `high`
+    fn check_node(node: &PersistentLink<T>, low: Option<&T>, high: Option<&T>) -> Result<i32, String> {
+        let n = match node {
+            Some(n) => n,
+            None => return Ok(0),
+        };
+        if low.is_some_and(|low| n.key <= *low) || high.is_some_and(|high| n.key >= *high) {
+            return Err(format!("key {:?} is out of order", n.key));
+        }
+        let left_height = Self::check_node(&n.left, low, Some(&n.key))?;
+        let right_height = Self::check_node(&n.right, Some(&n.key), high)?;
+        let height = 1 + max(left_height, right_height);
+        if n.height != height {
+            return Err(format!("key {:?} stores height {} instead of {}", n.key, n.height, height));
+        }
+        if (left_height - right_height).abs() > 1 {
+            return Err(format!("key {:?} has balance factor {}", n.key, left_height - right_height));
+        }
+        let size = 1 + Self::size(&n.left) + Self::size(&n.right);
+        if n.size != size {
+            return Err(format!("key {:?} stores subtree size {} instead of {}", n.key, n.size, size));
+        }
+        Ok(height)
+    }
+}
+
+impl<T: PartialOrd + Clone> Default for PersistentAVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Parallel versions of the bulk operations. They run the same recursion as the sequential
+// ones and hand the two halves of a split to scoped threads while `threads` allows it, so
+// the resulting trees have exactly the same shape. A budget of 0 or 1 runs sequentially.
+impl<T: PartialOrd + Clone + Send + Sync> PersistentAVLTree<T> {
+    pub fn par_from_sorted(values: &[T], threads: usize) -> Self {
+        PersistentAVLTree { root: Self::par_build(values, threads) }
+    }
+
+    pub fn par_union(&self, other: &Self, threads: usize) -> Self {
+        PersistentAVLTree { root: Self::par_union_node(&self.root, &other.root, threads) }
+    }
+
+    pub fn par_intersection(&self, other: &Self, threads: usize) -> Self {
+        PersistentAVLTree { root: Self::par_intersection_node(&self.root, &other.root, threads) }
+    }
+
+    // same result as `map`, disjoint subtrees are mapped on different threads
+    pub fn par_map<U: Send>(&self, threads: usize, f: impl Fn(&T) -> U + Sync) -> Vec<U> {
+        Self::par_map_node(&self.root, threads, &f)
+    }
+
+    // Run `left` on a scoped thread and `right` on the current one, splitting the thread budget
+    // between them. Falls back to running both in turn when there is nothing to split.
+    fn fork<A: Send, B: Send>(
+        threads: usize,
+        left: impl FnOnce(usize) -> A + Send,
+        right: impl FnOnce(usize) -> B,
+    ) -> (A, B) {
+        if threads <= 1 {
+            return (left(1), right(1));
+        }
+        let left_threads = threads / 2;
+        thread::scope(|scope| {
+            let handle = scope.spawn(move || left(left_threads));
+            let b = right(threads - left_threads);
+            (handle.join().expect("worker thread panicked"), b)
+        })
+    }
+
+    fn par_build(values: &[T], threads: usize) -> PersistentLink<T> {
+        if threads <= 1 || values.is_empty() {
+            return Self::build(values);
+        }
+        let mid = values.len() / 2;
+        let (left, right) = Self::fork(
+            threads,
+            |t| Self::par_build(&values[..mid], t),
+            |t| Self::par_build(&values[mid + 1..], t),
+        );
+        Some(Self::make(values[mid].clone(), left, right))
+    }
+
+    fn par_union_node(a: &PersistentLink<T>, b: &PersistentLink<T>, threads: usize) -> PersistentLink<T> {
+        if threads <= 1 {
+            return Self::union_node(a, b);
+        }
+        let b_node = match (a, b) {
+            (None, _) => return b.clone(),
+            (_, None) => return a.clone(),
+            (_, Some(b_node)) => b_node,
+        };
+        let (left, _, right) = Self::split_node(a, &b_node.key);
+        let (left, right) = Self::fork(
+            threads,
+            |t| Self::par_union_node(&left, &b_node.left, t),
+            |t| Self::par_union_node(&right, &b_node.right, t),
+        );
+        Self::join(left, b_node.key.clone(), right)
+    }
+
+    fn par_intersection_node(a: &PersistentLink<T>, b: &PersistentLink<T>, threads: usize) -> PersistentLink<T> {
+        if threads <= 1 {
+            return Self::intersection_node(a, b);
+        }
+        let b_node = match (a, b) {
+            (Some(_), Some(b_node)) => b_node,
+            _ => return None,
+        };
+        let (left, found, right) = Self::split_node(a, &b_node.key);
+        let (left, right) = Self::fork(
+            threads,
+            |t| Self::par_intersection_node(&left, &b_node.left, t),
+            |t| Self::par_intersection_node(&right, &b_node.right, t),
+        );
+        if found {
+            Self::join(left, b_node.key.clone(), right)
+        } else {
+            Self::join_pair(left, right)
+        }
+    }
+
+    fn par_map_node<U: Send>(node: &PersistentLink<T>, threads: usize, f: &(impl Fn(&T) -> U + Sync)) -> Vec<U> {
+        let n = match node {
+            Some(n) if threads > 1 => n,
+            _ => {
+                let mut out = Vec::with_capacity(Self::size(node));
+                Self::map_node(node, f, &mut out);
+                return out;
+            },
+        };
+        let (mut out, right) = Self::fork(
+            threads,
+            |t| Self::par_map_node(&n.left, t, f),
+            |t| Self::par_map_node(&n.right, t, f),
+        );
+        out.reserve(right.len() + 1);
+        out.push(f(&n.key));
+        out.extend(right);
+        out
+    }
+}
+
+// in-order iterator over one version
+pub struct PersistentIter<T> {
+    stack: Vec<Arc<PersistentNode<T>>>,
+}
+
+impl<T> PersistentIter<T> {
+    fn push_left(&mut self, node: &PersistentLink<T>) {
+        let mut current = node.clone();
+        while let Some(n) = current {
+            current = n.left.clone();
+            self.stack.push(n);
+        }
+    }
+}
+
+impl<T: Clone> Iterator for PersistentIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+        Some(node.key.clone())
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt::{self, Write};
+
+// how `write_tree` lays a tree out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    // root on the first line, children indented below it with box-drawing branches
+    #[default]
+    Horizontal,
+    // root on top, children spread out underneath like in a textbook figure
+    Vertical,
+    // one line, `(root left right)` with leaves written bare and `_` for a missing child
+    Bracket,
+}
+
+// stands for a missing child in every layout
+pub const NIL: &str = "_";
+
+// What the printers need to know about a node, the trees convert themselves into this shape.
+pub struct PrettyNode {
+    pub label: String,
+    pub left: Option<Box<PrettyNode>>,
+    pub right: Option<Box<PrettyNode>>,
+}
+
+impl PrettyNode {
+    fn is_leaf(&self) -> bool {
+        self.left.is_none() && self.right.is_none()
+    }
+}
+
+// Write the tree in `layout`. Horizontal and vertical output ends with a newline, the
+// bracket form is a single line without one.
+pub fn write_tree<W: Write + ?Sized>(out: &mut W, root: Option<&PrettyNode>, layout: Layout) -> fmt::Result {
+    let root = match root {
+        Some(root) => root,
+        None if layout == Layout::Bracket => return out.write_str(NIL),
+        None => return out.write_str("(empty)\n"),
+    };
+    match layout {
+        Layout::Horizontal => {
+            writeln!(out, "{}", root.label)?;
+            horizontal(out, root, "")
+        },
+        Layout::Vertical => {
+            for line in vertical(root).lines {
+                writeln!(out, "{}", line.trim_end())?;
+            }
+            Ok(())
+        },
+        Layout::Bracket => bracket(out, Some(root)),
+    }
+}
+
+fn horizontal<W: Write + ?Sized>(out: &mut W, node: &PrettyNode, prefix: &str) -> fmt::Result {
+    if node.is_leaf() {
+        return Ok(());
+    }
+    // both branches are drawn as soon as one exists, so a lone child keeps its side
+    for (child, last) in [(&node.left, false), (&node.right, true)] {
+        let (branch, extension) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+        match child {
+            Some(child) => {
+                writeln!(out, "{}{}{}", prefix, branch, child.label)?;
+                horizontal(out, child, &format!("{}{}", prefix, extension))?;
+            },
+            None => writeln!(out, "{}{}{}", prefix, branch, NIL)?,
+        }
+    }
+    Ok(())
+}
+
+fn bracket<W: Write + ?Sized>(out: &mut W, node: Option<&PrettyNode>) -> fmt::Result {
+    match node {
+        None => out.write_str(NIL),
+        Some(node) if node.is_leaf() => out.write_str(&node.label),
+        Some(node) => {
+            write!(out, "({} ", node.label)?;
+            bracket(out, node.left.as_deref())?;
+            out.write_char(' ')?;
+            bracket(out, node.right.as_deref())?;
+            out.write_char(')')
+        },
+    }
+}
+
+// a drawn subtree: its lines, all `width` characters wide, and the column above its root
+struct Block {
+    lines: Vec<String>,
+    width: usize,
+    middle: usize,
+}
+
+// The root label sits between the blocks of its subtrees, with a branch running from above
+// each child's root to the label:
+//     ┌─4─┐
+//   ┌─2┐ 6
+//   1  3
+fn vertical(node: &PrettyNode) -> Block {
+    let label_width = node.label.chars().count();
+    let left = node.left.as_deref().map(vertical);
+    let right = node.right.as_deref().map(vertical);
+
+    let mut first = String::new();
+    if let Some(left) = &left {
+        first.push_str(&" ".repeat(left.middle));
+        first.push('┌');
+        first.push_str(&"─".repeat(left.width - left.middle - 1));
+    }
+    first.push_str(&node.label);
+    if let Some(right) = &right {
+        first.push_str(&"─".repeat(right.middle));
+        first.push('┐');
+        first.push_str(&" ".repeat(right.width - right.middle - 1));
+    }
+
+    let left_width = left.as_ref().map_or(0, |b| b.width);
+    let right_width = right.as_ref().map_or(0, |b| b.width);
+    let rows = max_rows(&left).max(max_rows(&right));
+    let mut lines = vec![first];
+    for row in 0..rows {
+        let mut line = row_of(&left, row, left_width);
+        line.push_str(&" ".repeat(label_width));
+        line.push_str(&row_of(&right, row, right_width));
+        lines.push(line);
+    }
+    Block { lines, width: left_width + label_width + right_width, middle: left_width + label_width / 2 }
+}
+
+fn max_rows(block: &Option<Block>) -> usize {
+    block.as_ref().map_or(0, |b| b.lines.len())
+}
+
+// row `row` of a block padded to `width`, blank below its last line
+fn row_of(block: &Option<Block>, row: usize, width: usize) -> String {
+    let line = block.as_ref().and_then(|b| b.lines.get(row)).map_or("", |line| line.as_str());
+    format!("{}{}", line, " ".repeat(width - line.chars().count()))
+}
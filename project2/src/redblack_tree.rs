@@ -2,6 +2,22 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::rc::Weak;
 use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::str::FromStr;
+use crate::augment::Summary;
+use crate::bracket::{self, BracketNode};
+use crate::codec::{self, Codec, TreeKind};
+use crate::interchange::{self, Json, JsonTree};
+use crate::ordered_set::OrderedSet;
+use crate::dot::DotWriter;
+use crate::durable::SnapshotTree;
+use crate::observer::{Direction, ObserverRef, ObserverSlot};
+use crate::pretty::{self, Layout, PrettyNode};
+use crate::stats::{StatsCounter, TreeStats};
+use crate::svg::{self, SvgNode};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum NodeColor {
@@ -9,36 +25,189 @@ pub enum NodeColor {
 	Black, 
 }
 
-type Link<T> = Rc<RefCell<TreeNode<T>>>;
+type Link<T, S = ()> = Rc<RefCell<TreeNode<T, S>>>;
 
 #[derive(Debug, Clone)]
-pub struct TreeNode<T> where T: Clone {
-	pub color: NodeColor,
-	pub value: T,
-    pub parent: Option<Weak<RefCell<TreeNode<T>>>>,
-	left: Option<Link<T>>, 
-	right: Option<Link<T>>
+pub struct TreeNode<T, S = ()> where T: Clone {
+	color: NodeColor,
+	value: T,
+    count: usize,
+    // occurrences in the whole subtree, for rank
+    size: usize,
+    parent: Option<Weak<RefCell<TreeNode<T, S>>>>,
+	left: Option<Link<T, S>>, 
+	right: Option<Link<T, S>>,
+    summary: S,
 }
 
 // Define the Red-Black Tree itself
 #[derive(Debug)]
-pub struct RedBlackTree<T> where T: Clone{
-    root: Option<Link<T>>,
-    rotation_function_inner: fn(&mut Self, &Link<T>),
-    rotation_function_extern: fn(&mut Self, &Link<T>),
+pub struct RedBlackTree<T, S = ()> where T: Clone{
+    root: Option<Link<T, S>>,
+    // number of nodes, a value stored several times in multiset mode is one node
+    count: usize,
+    rotation_function_inner: fn(&mut Self, &Link<T, S>),
+    rotation_function_extern: fn(&mut Self, &Link<T, S>),
+    multiset: bool,
+    stats: StatsCounter,
+    observer: ObserverSlot<T>,
 }
 
-impl<T: Clone> TreeNode<T> {
+// in-order iterator, a value is yielded once per occurrence
+pub struct Iter<T, S = ()> where T: Clone {
+    stack: Vec<Link<T, S>>,
+    repeat: Option<(T, usize)>,
+}
+
+impl<T: Clone, S> Iter<T, S> {
+    fn new(root: &Option<Link<T, S>>) -> Self {
+        let mut iter = Iter { stack: Vec::new(), repeat: None };
+        iter.push_left(root.clone());
+        iter
+    }
+
+    // push a node and all of its left descendants
+    fn push_left(&mut self, mut node: Option<Link<T, S>>) {
+        while let Some(n) = node {
+            node = n.borrow().left.clone();
+            self.stack.push(n);
+        }
+    }
+}
+
+impl<T: Clone, S> Iterator for Iter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if let Some((value, left)) = self.repeat.take() {
+            if left > 1 {
+                self.repeat = Some((value.clone(), left - 1));
+            }
+            return Some(value);
+        }
+        let node = self.stack.pop()?;
+        let (value, count, right) = {
+            let n = node.borrow();
+            (n.value.clone(), n.count, n.right.clone())
+        };
+        self.push_left(right);
+        if count > 1 {
+            self.repeat = Some((value.clone(), count - 1));
+        }
+        Some(value)
+    }
+}
+
+// pre-order iterator, yields every node once together with its depth (the root is at depth 0)
+pub struct PreOrderIter<T, S = ()> where T: Clone {
+    stack: Vec<(Link<T, S>, usize)>,
+}
+
+impl<T: Clone, S> Iterator for PreOrderIter<T, S> {
+    type Item = (T, usize);
+
+    fn next(&mut self) -> Option<(T, usize)> {
+        let (node, depth) = self.stack.pop()?;
+        let n = node.borrow();
+        // right goes first so that the left subtree comes out first
+        if let Some(right) = &n.right {
+            self.stack.push((right.clone(), depth + 1));
+        }
+        if let Some(left) = &n.left {
+            self.stack.push((left.clone(), depth + 1));
+        }
+        Some((n.value.clone(), depth))
+    }
+}
+
+// post-order iterator, yields every node once together with its depth
+pub struct PostOrderIter<T, S = ()> where T: Clone {
+    // the flag tells whether the children of the node were already pushed
+    stack: Vec<(Link<T, S>, usize, bool)>,
+}
+
+impl<T: Clone, S> Iterator for PostOrderIter<T, S> {
+    type Item = (T, usize);
+
+    fn next(&mut self) -> Option<(T, usize)> {
+        while let Some((node, depth, expanded)) = self.stack.pop() {
+            if expanded {
+                let n = node.borrow();
+                return Some((n.value.clone(), depth));
+            }
+            let (left, right) = {
+                let n = node.borrow();
+                (n.left.clone(), n.right.clone())
+            };
+            self.stack.push((node, depth, true));
+            if let Some(right) = right {
+                self.stack.push((right, depth + 1, false));
+            }
+            if let Some(left) = left {
+                self.stack.push((left, depth + 1, false));
+            }
+        }
+        None
+    }
+}
+
+// level-order (breadth-first) iterator, yields every node once together with its depth
+pub struct LevelOrderIter<T, S = ()> where T: Clone {
+    queue: VecDeque<(Link<T, S>, usize)>,
+}
+
+impl<T: Clone, S> Iterator for LevelOrderIter<T, S> {
+    type Item = (T, usize);
+
+    fn next(&mut self) -> Option<(T, usize)> {
+        let (node, depth) = self.queue.pop_front()?;
+        let n = node.borrow();
+        if let Some(left) = &n.left {
+            self.queue.push_back((left.clone(), depth + 1));
+        }
+        if let Some(right) = &n.right {
+            self.queue.push_back((right.clone(), depth + 1));
+        }
+        Some((n.value.clone(), depth))
+    }
+}
+
+impl<T: Clone, S: Summary<T>> TreeNode<T, S> {
     fn new(value: T) -> Self {
         TreeNode {
             color: NodeColor::Red,
+            summary: S::lift(&value, 1),
             value,
+            count: 1,
+            size: 1,
             parent: None,
             left: None,
             right: None,
         }
     }
+
+    // summary and size of the subtree, left + this node + right
+    fn update_summary(&mut self) {
+        let left = self.left.as_ref().map(|n| n.borrow().summary.clone());
+        let right = self.right.as_ref().map(|n| n.borrow().summary.clone());
+        let size = |child: &Option<Link<T, S>>| child.as_ref().map_or(0, |n| n.borrow().size);
+        self.size = size(&self.left) + self.count + size(&self.right);
+        let own = S::lift(&self.value, self.count);
+        self.summary = S::combine_opt(S::combine_opt(left, Some(own)), right).unwrap();
+    }
     
+    // value as the printers show it, `value*count` for a value stored more than once
+    fn label(&self) -> String
+    where
+        T: std::fmt::Debug,
+    {
+        if self.count > 1 {
+            format!("{:?}*{}", self.value, self.count)
+        } else {
+            format!("{:?}", self.value)
+        }
+    }
+
     fn flip_color(&mut self) {
         match self.color {
             NodeColor::Red => self.color = NodeColor::Black,
@@ -49,28 +218,58 @@ impl<T: Clone> TreeNode<T> {
 
 impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T> {
 	pub fn new() -> Self {
+        Self::with_summary()
+    }
+
+    // multiset mode, inserting an existing value adds one more occurrence to its node
+    pub fn new_multiset() -> Self {
+        Self::multiset_with_summary()
+    }
+}
+
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> Default for RedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display, S: Summary<T>> RedBlackTree<T, S> {
+    // augmented tree, every node keeps the summary `S` of its subtree
+    pub fn with_summary() -> Self {
         RedBlackTree { 
             root: None,
+            count: 0,
             rotation_function_inner: Self::rotate_left_right,
             rotation_function_extern: Self::rotate_left_right,
+            multiset: false,
+            stats: StatsCounter::default(),
+            observer: ObserverSlot::default(),
         }
     }
 
-    fn insert_recursive(parent: &mut Link<T>, value: T) -> Option<Link<T>> {
+    pub fn multiset_with_summary() -> Self {
+        RedBlackTree {
+            multiset: true,
+            ..Self::with_summary()
+        }
+    }
+
+    fn insert_recursive(parent: &mut Link<T, S>, value: T, stats: &StatsCounter) -> Option<Link<T, S>> {
+        stats.record(|s| s.nodes_visited += 1);
         let mut parent_mut_borrow =  parent.as_ref().borrow_mut();
         
-        if parent_mut_borrow.value == value {
+        if stats.compare(parent_mut_borrow.value == value) {
             return None;
         }
 
-        let leaf = if value < parent_mut_borrow.value {
+        let leaf = if stats.compare(value < parent_mut_borrow.value) {
             &mut parent_mut_borrow.left
         } else {
             &mut parent_mut_borrow.right
         };
 
         match leaf {
-            Some(ref mut node) => Self::insert_recursive(node, value),
+            Some(ref mut node) => Self::insert_recursive(node, value, stats),
             None => {
                 let mut new_node = TreeNode::new(value);
                 new_node.parent = Some(Rc::downgrade(parent));
@@ -82,303 +281,276 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
     }
     pub fn clear(&mut self) {
         self.root = None;
+        self.count = 0;
     }
 
     // 1 - Insert a node to the red-black tree.
     pub fn insert(&mut self, value: T) -> bool {
+        self.stats.record(|s| s.operations += 1);
+        if self.multiset {
+            if let Some(node) = self.get_node(value.clone()) {
+                node.borrow_mut().count += 1;
+                Self::refresh_upward(node);
+                self.observer.notify(|o| o.on_insert(&value));
+                return true;
+            }
+        }
         // if the root node exist, create the mutable reference to root node
         if let Some(ref mut root) = self.root {
-            if let Some(inserted_node) = Self::insert_recursive(root, value) {
+            if let Some(inserted_node) = Self::insert_recursive(root, value.clone(), &self.stats) {
+                // the new leaf changes the summaries on its way to the root, rotations keep them local
+                Self::refresh_upward(inserted_node.clone());
                 // self.print_tree();
                 // Implement rotation and recoloring to maintain Red-Black Tree properties
                 self.rebalance_insert(inserted_node);
+                self.count += 1;
+                self.observer.notify(|o| o.on_insert(&value));
             } else {
               return false;
             }
         } else {
-            let mut new_node = TreeNode::new(value);
+            let mut new_node = TreeNode::new(value.clone());
             new_node.color = NodeColor::Black;
             self.root = Some(Rc::new(RefCell::new(new_node)));
+            self.count += 1;
+            self.observer.notify(|o| o.on_insert(&value));
         }
         return true;
     }
 
-    fn handle_red_uncle(&mut self, uncle_node: Rc<RefCell<TreeNode<T>>>, parent_node_ref: Rc<RefCell<TreeNode<T>>>, grand_node_ref: Rc<RefCell<TreeNode<T>>>) {
-        uncle_node.borrow_mut().flip_color();
-        parent_node_ref.borrow_mut().flip_color();
-        grand_node_ref.borrow_mut().flip_color(); 
+    fn handle_red_uncle(&mut self, uncle_node: Link<T, S>, parent_node_ref: Link<T, S>, grand_node_ref: Link<T, S>) {
+        for node in [&uncle_node, &parent_node_ref, &grand_node_ref] {
+            let mut node_borrow_mut = node.borrow_mut();
+            node_borrow_mut.flip_color();
+            self.recolored(&node_borrow_mut);
+        }
         self.rebalance_insert(grand_node_ref);
     }
 
-    fn handle_other_uncle(&mut self, inserted_node_ref: Rc<RefCell<TreeNode<T>>>, parent_node_ref: Rc<RefCell<TreeNode<T>>>, grand_node_ref: Rc<RefCell<TreeNode<T>>>) {
-
-        let inserted_node_value = inserted_node_ref.borrow_mut().value.clone();
-        let grand_node_parent = grand_node_ref.borrow_mut().parent.clone();
-
-        let mut node_values = [
-            (inserted_node_value.clone(), Rc::downgrade(&inserted_node_ref)),
-            (parent_node_ref.borrow_mut().value.clone(), Rc::downgrade(&parent_node_ref)),
-            (grand_node_ref.borrow_mut().value.clone(), Rc::downgrade(&grand_node_ref))
-        ];
-        node_values.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-
-        drop(inserted_node_ref);
-
-        let mid_node = node_values[1].1.upgrade().unwrap();
-        let min_node = node_values[0].1.upgrade().unwrap();
-        let max_node = node_values[2].1.upgrade().unwrap();
-
-        let mut mid_node_borrowed = mid_node.borrow_mut();
-        let mid_node_value = mid_node_borrowed.clone().value;
+    fn handle_other_uncle(&mut self, inserted_node_ref: Link<T, S>, parent_node_ref: Link<T, S>, grand_node_ref: Link<T, S>) {
+        let parent_is_left = Self::is_left_child(&parent_node_ref);
+        let inserted_is_left = Self::is_left_child(&inserted_node_ref);
 
-        let mid_node_right = mid_node_borrowed.right.clone();
-        let mid_node_left = mid_node_borrowed.left.clone();
-
-        mid_node_borrowed.left = Some(min_node);
-        mid_node_borrowed.right = Some(max_node);
-        mid_node_borrowed.color = NodeColor::Black;
-
-        if let Some(left_node) = mid_node_borrowed.left.as_ref() {
-            left_node.borrow_mut().parent = Some(Rc::downgrade(&mid_node));
-            left_node.borrow_mut().color = NodeColor::Red;
-            if mid_node_value < inserted_node_value || mid_node_value == inserted_node_value {
-                left_node.borrow_mut().right = mid_node_left;
-                if let Some(ref mut right_node) = left_node.borrow_mut().right.as_mut() {
-                    right_node.borrow_mut().parent = Some(Rc::downgrade(&left_node));
-                };
-            };
+        // the node that ends up on top of the rotated subtree
+        let mid_node = match (parent_is_left, inserted_is_left) {
+            (true, true) => {
+                self.rotate_right_extern(&grand_node_ref);
+                parent_node_ref
+            },
+            (true, false) => {
+                self.rotate_right_inner(&grand_node_ref);
+                inserted_node_ref
+            },
+            (false, false) => {
+                self.rotate_left_right(&grand_node_ref);
+                parent_node_ref
+            },
+            (false, true) => {
+                self.rotate_left_left(&grand_node_ref);
+                inserted_node_ref
+            },
         };
+        self.recolor(&mut mid_node.borrow_mut(), NodeColor::Black);
+        self.recolor(&mut grand_node_ref.borrow_mut(), NodeColor::Red);
+    }
 
-        if let Some(right_node) = mid_node_borrowed.right.as_mut() {
-            right_node.borrow_mut().parent = Some(Rc::downgrade(&mid_node));
-            right_node.borrow_mut().color = NodeColor::Red;
+    // give `node` a color, a change is counted and reported
+    fn recolor(&self, node: &mut TreeNode<T, S>, color: NodeColor) {
+        if node.color != color {
+            node.color = color;
+            self.recolored(node);
+        }
+    }
 
-            if mid_node_value > inserted_node_value || mid_node_value == inserted_node_value{
-                right_node.borrow_mut().left = mid_node_right;
-                if let Some(ref mut left_node) = right_node.borrow_mut().right.as_mut() {
-                    left_node.borrow_mut().parent = Some(Rc::downgrade(&right_node));
-                };
-            }
-        };
-        mid_node_borrowed.parent = grand_node_parent;
-        
-        if mid_node_borrowed.parent.is_none() {
-            drop(mid_node_borrowed);
-            self.root = Some(mid_node.clone());
-        }else {
-            if Rc::ptr_eq(&mid_node_borrowed.parent.as_ref().unwrap().upgrade().unwrap().borrow().clone().left.unwrap(), &grand_node_ref) {
-                mid_node_borrowed.parent.as_ref().unwrap().upgrade().unwrap().borrow_mut().left = Some(mid_node.clone());
-            }else{
-                mid_node_borrowed.parent.as_ref().unwrap().upgrade().unwrap().borrow_mut().right = Some(mid_node.clone());
-            };
-            drop(mid_node_borrowed);
-        };
-        self.rebalance_insert(mid_node);
+    fn recolored(&self, node: &TreeNode<T, S>) {
+        self.stats.record(|s| s.recolors += 1);
+        self.observer.notify(|o| o.on_recolor(&node.value, node.color == NodeColor::Red));
     }
 
-    // calling this function after inserting operation to keep the balance of the rb tree 
-    fn rebalance_insert(&mut self, inserted_node: Link<T>){
-        let mut inserted_node_ref = inserted_node.borrow_mut();
-        let parent = match inserted_node_ref.parent.as_mut() {
-            Some(parent) => parent,
-            None => { // Recursion up to the inserted_node equal the root node
-                inserted_node_ref.color = NodeColor::Black;
-                return;
-            }
-        };
+    // check whether a node hangs on the left side of its parent
+    fn is_left_child(node: &Link<T, S>) -> bool {
+        let parent = node.borrow().parent.as_ref().and_then(|p| p.upgrade());
+        parent.is_some_and(|parent| {
+            parent.borrow().left.as_ref().is_some_and(|left| Rc::ptr_eq(left, node))
+        })
+    }
 
-        let parent_node = match parent.upgrade() {
+    // calling this function after inserting operation to keep the balance of the rb tree 
+    fn rebalance_insert(&mut self, inserted_node: Link<T, S>){
+        let parent = inserted_node.borrow().parent.as_ref().and_then(|p| p.upgrade());
+        let parent_node = match parent {
             Some(parent_node) => parent_node,
-            None => {
-                println!("Parent node upgrade failed");
+            None => { // Recursion up to the inserted_node equal the root node
+                self.recolor(&mut inserted_node.borrow_mut(), NodeColor::Black);
                 return;
             }
         };
 
         // if the parent_node is not red, the tree is already balanced.
-        if parent_node.borrow().color != NodeColor::Red || inserted_node_ref.color != NodeColor::Red{
+        if parent_node.borrow().color != NodeColor::Red || inserted_node.borrow().color != NodeColor::Red {
             return;
         }
 
-        let mut parent_node_ref = parent_node.borrow_mut();
-        let grand_node = parent_node_ref.parent.as_mut().unwrap().upgrade().unwrap();
-        let grand_node_ref = grand_node.borrow_mut();
+        // a red parent is never the root, so the grand node exists
+        let grand_node = parent_node.borrow().parent.as_ref().unwrap().upgrade().unwrap();
+        let uncle_node = if Self::is_left_child(&parent_node) {
+            grand_node.borrow().right.clone()
+        } else {
+            grand_node.borrow().left.clone()
+        };
 
-        
-        if let Some(left_child) = &grand_node_ref.left.clone() { // the left node of grand_node is exist.
-            if Rc::ptr_eq(left_child, &parent_node) { // The parent node is the left child of the grandparent node
-                drop(inserted_node_ref);
-                drop(parent_node_ref);
-                if let Some(uncle_node) = &grand_node_ref.right.clone() {
-                    if uncle_node.borrow().color == NodeColor::Red {
-                        drop(grand_node_ref);
-                        self.handle_red_uncle(uncle_node.clone(), parent_node, grand_node);
-                    } else {
-                        // Handle the case where uncle_node is black
-                    };
+        match uncle_node {
+            Some(uncle_node) if uncle_node.borrow().color == NodeColor::Red => {
+                self.handle_red_uncle(uncle_node, parent_node, grand_node);
+            },
+            _ => self.handle_other_uncle(inserted_node, parent_node, grand_node),
+        }
+    }
+
+    // hang `new_child` where `old_child` used to be below `parent`, or make it the root
+    fn replace_child(&mut self, parent: Option<Link<T, S>>, old_child: &Link<T, S>, new_child: &Link<T, S>) {
+        match parent {
+            Some(parent) => {
+                new_child.borrow_mut().parent = Some(Rc::downgrade(&parent));
+                let mut parent_borrow_mut = parent.borrow_mut();
+                if parent_borrow_mut.left.as_ref().is_some_and(|left| Rc::ptr_eq(left, old_child)) {
+                    parent_borrow_mut.left = Some(new_child.clone());
                 } else {
-                    drop(grand_node_ref);
-                    self.handle_other_uncle(inserted_node, parent_node, grand_node);
-                };
-            }else{ // The parent node is the right child of the grandparent node
-                let uncle_node = left_child;
-                // println!("{}", uncle_node.borrow().value);
-                if uncle_node.borrow().color == NodeColor::Red {
-                    drop(inserted_node_ref);
-                    drop(parent_node_ref);
-                    drop(grand_node_ref);
-                    self.handle_red_uncle(uncle_node.clone(), parent_node, grand_node);
-                }else {
-                    drop(inserted_node_ref);
-                    drop(parent_node_ref);
-                    drop(grand_node_ref);
-                    self.handle_other_uncle(inserted_node, parent_node, grand_node);
+                    parent_borrow_mut.right = Some(new_child.clone());
                 }
-            };
-        } else{ 
-            drop(inserted_node_ref);
-            drop(parent_node_ref);
-            match &grand_node_ref.left.clone() { // try get uncle node
-                Some(uncle_node) if uncle_node.borrow().color == NodeColor::Red => {
-                    drop(grand_node_ref);
-                    self.handle_red_uncle(uncle_node.clone(), parent_node, grand_node);
-                },  
-                _ => {
-                    drop(grand_node_ref);
-                    self.handle_other_uncle(inserted_node, parent_node, grand_node);
-                },
-            }
-        };
+            },
+            None => {
+                let mut new_child_borrow_mut = new_child.borrow_mut();
+                new_child_borrow_mut.parent = None;
+                self.recolor(&mut new_child_borrow_mut, NodeColor::Black);
+                drop(new_child_borrow_mut);
+                self.root = Some(new_child.clone());
+            },
+        }
     }
 
-    fn rotate_left_right(&mut self, node: &Link<T>) {
-        let mut node_borrow_mut = node.borrow_mut();
-        if let Some(right) = node_borrow_mut.right.take() {
-            let mut right_borrow_mut = right.borrow_mut();
-            node_borrow_mut.right = right_borrow_mut.left.take();
-
-            right_borrow_mut.left = Some(node.clone());
-            if let Some(weak_parent) = node_borrow_mut.parent.as_mut() {
-                if let Some(parent) = weak_parent.upgrade() {
-                    let mut parent_borrow_mut = parent.borrow_mut();
-                    if Rc::ptr_eq(&node, &parent_borrow_mut.left.as_mut().unwrap()){
-                        parent_borrow_mut.left = Some(right.clone());
-                    }else {
-                        parent_borrow_mut.right = Some(right.clone());
-                    }
-                    right_borrow_mut.parent = Some(Rc::downgrade(&parent));
-                    drop(right_borrow_mut);
-                    node_borrow_mut.parent = Some(Rc::downgrade(&right));
-                }
-            }else {
-                node_borrow_mut.parent = Some(Rc::downgrade(&right));
-                right_borrow_mut.color = NodeColor::Black;
-                right_borrow_mut.parent = None;
-                self.root = Some(right.clone());
-            }
+    // set the parent pointer of a subtree that moved during a rotation
+    fn adopt(child: &Option<Link<T, S>>, parent: &Link<T, S>) {
+        if let Some(child) = child {
+            child.borrow_mut().parent = Some(Rc::downgrade(parent));
         }
     }
 
-    fn rotate_left_left(&mut self, node: &Link<T>) {
-        let mut node_borrow_mut = node.borrow_mut();
-        if let Some(right) = node_borrow_mut.right.take() {
-            let mut right_borrow_mut = right.borrow_mut();
-            if let Some(right_left) = right_borrow_mut.left.take(){
-                let mut right_left_borrow_mut = right_left.borrow_mut();
-                node_borrow_mut.right = right_left_borrow_mut.left.take();
-                right_borrow_mut.left = right_left_borrow_mut.right.take();
-
-                right_left_borrow_mut.left = Some(node.clone());
-                right_left_borrow_mut.right = Some(right.clone());
-
-                right_borrow_mut.parent = Some(Rc::downgrade(&right_left));
-
-                if let Some(weak_parent) = node_borrow_mut.parent.as_mut() {
-                    if let Some(parent) = weak_parent.upgrade() {
-                        if Rc::ptr_eq(&node, &parent.borrow_mut().left.as_mut().unwrap()){
-                            parent.borrow_mut().left = Some(right_left.clone());
-                        }else {
-                            parent.borrow_mut().right = Some(right_left.clone());
-                        };
-                        right_left_borrow_mut.parent = Some(Rc::downgrade(&parent));
-                        drop(right_borrow_mut);
-                        node_borrow_mut.parent = Some(Rc::downgrade(&right_left));
-                    };
-                }else {
-                    node_borrow_mut.parent = Some(Rc::downgrade(&right_left));
-                    right_left_borrow_mut.color = NodeColor::Black;
-                    right_borrow_mut.parent = None;
-                    self.root = Some(right_left.clone());
-                };
-            };
+    // single left rotation around `node`
+    fn rotate_left_right(&mut self, node: &Link<T, S>) {
+        let right = match node.borrow_mut().right.take() {
+            Some(right) => right,
+            None => return,
         };
+        self.observer.notify(|o| o.on_rotate(&node.borrow().value, Direction::Left));
+        let parent = node.borrow().parent.as_ref().and_then(|p| p.upgrade());
+
+        let right_left = right.borrow_mut().left.take();
+        Self::adopt(&right_left, node);
+        node.borrow_mut().right = right_left;
+
+        right.borrow_mut().left = Some(node.clone());
+        node.borrow_mut().parent = Some(Rc::downgrade(&right));
+        node.borrow_mut().update_summary();
+        right.borrow_mut().update_summary();
+        self.replace_child(parent, node, &right);
+        self.stats.record(|s| s.single_rotations += 1);
     }
 
-    fn rotate_right_extern(&mut self, node: &Link<T>) {
-        let mut node_borrow_mut = node.borrow_mut();
-        if let Some(left) = node_borrow_mut.left.take() {
-            let mut left_borrow_mut = left.borrow_mut();
-            node_borrow_mut.left = left_borrow_mut.right.take();
-
-            left_borrow_mut.right = Some(node.clone());
-            if let Some(weak_parent) = node_borrow_mut.parent.as_mut() {
-                if let Some(parent) = weak_parent.upgrade() {
-                    if Rc::ptr_eq(&node, &parent.borrow_mut().left.as_mut().unwrap()){
-                        parent.borrow_mut().left = Some(left.clone());
-                    }else {
-                        parent.borrow_mut().right = Some(left.clone());
-                    };
-
-                    left_borrow_mut.parent = Some(Rc::downgrade(&parent));
-                    drop(left_borrow_mut);
-                    node_borrow_mut.parent = Some(Rc::downgrade(&left));
-                }
-            }else {
-                left_borrow_mut.color = NodeColor::Black;
-                left_borrow_mut.parent = None;
-                node_borrow_mut.parent = Some(Rc::downgrade(&left));
-                self.root = Some(left.clone());
-            }
-        }
+    // double rotation: right rotation around the right child, then left rotation around `node`
+    fn rotate_left_left(&mut self, node: &Link<T, S>) {
+        let right = match node.borrow().right.clone() {
+            Some(right) => right,
+            None => return,
+        };
+        let right_left = match right.borrow().left.clone() {
+            Some(right_left) => right_left,
+            None => return,
+        };
+        self.observer.notify(|o| {
+            o.on_rotate(&right.borrow().value, Direction::Right);
+            o.on_rotate(&node.borrow().value, Direction::Left);
+        });
+        let parent = node.borrow().parent.as_ref().and_then(|p| p.upgrade());
+
+        let mut right_left_borrow_mut = right_left.borrow_mut();
+        let inner_left = right_left_borrow_mut.left.take();
+        let inner_right = right_left_borrow_mut.right.take();
+        Self::adopt(&inner_left, node);
+        Self::adopt(&inner_right, &right);
+        node.borrow_mut().right = inner_left;
+        right.borrow_mut().left = inner_right;
+
+        right_left_borrow_mut.left = Some(node.clone());
+        right_left_borrow_mut.right = Some(right.clone());
+        drop(right_left_borrow_mut);
+        node.borrow_mut().parent = Some(Rc::downgrade(&right_left));
+        right.borrow_mut().parent = Some(Rc::downgrade(&right_left));
+        node.borrow_mut().update_summary();
+        right.borrow_mut().update_summary();
+        right_left.borrow_mut().update_summary();
+        self.replace_child(parent, node, &right_left);
+        self.stats.record(|s| s.double_rotations += 1);
     }
 
-    fn rotate_right_inner(&mut self, node: &Link<T>) {
-        let mut node_borrow_mut = node.borrow_mut();
-        if let Some(left) = node_borrow_mut.left.take() {
-            let mut left_borrow_mut = left.borrow_mut();
-            if let Some(left_right) = left_borrow_mut.right.take(){
-                let mut left_right_borrow_mut = left_right.borrow_mut();
-                node_borrow_mut.right = left_right_borrow_mut.left.take();
-                left_borrow_mut.left = left_right_borrow_mut.right.take();
-
-                left_right_borrow_mut.left = Some(node.clone());
-                left_right_borrow_mut.right = Some(left.clone());
-
-                left_borrow_mut.parent = Some(Rc::downgrade(&left_right));
-
-                if let Some(weak_parent) = node_borrow_mut.parent.as_mut() {
-                    if let Some(parent) = weak_parent.upgrade() {
-                        if Rc::ptr_eq(&node, &parent.borrow_mut().left.as_mut().unwrap()){
-                            parent.borrow_mut().left = Some(left_right.clone());
-                        }else {
-                            parent.borrow_mut().right = Some(left_right.clone());
-                        };
+    // single right rotation around `node`
+    fn rotate_right_extern(&mut self, node: &Link<T, S>) {
+        let left = match node.borrow_mut().left.take() {
+            Some(left) => left,
+            None => return,
+        };
+        self.observer.notify(|o| o.on_rotate(&node.borrow().value, Direction::Right));
+        let parent = node.borrow().parent.as_ref().and_then(|p| p.upgrade());
+
+        let left_right = left.borrow_mut().right.take();
+        Self::adopt(&left_right, node);
+        node.borrow_mut().left = left_right;
+
+        left.borrow_mut().right = Some(node.clone());
+        node.borrow_mut().parent = Some(Rc::downgrade(&left));
+        node.borrow_mut().update_summary();
+        left.borrow_mut().update_summary();
+        self.replace_child(parent, node, &left);
+        self.stats.record(|s| s.single_rotations += 1);
+    }
 
-                        left_right_borrow_mut.parent = Some(Rc::downgrade(&parent));
-                        drop(left_borrow_mut);
-                        node_borrow_mut.parent = Some(Rc::downgrade(&left_right));
-                    };
-                }else {
-                    node_borrow_mut.parent = Some(Rc::downgrade(&left_right));
-                    left_right_borrow_mut.color = NodeColor::Black;
-                    left_right_borrow_mut.parent = None;
-                    self.root = Some(left_right.clone());
-                };
-            };
+    // double rotation: left rotation around the left child, then right rotation around `node`
+    fn rotate_right_inner(&mut self, node: &Link<T, S>) {
+        let left = match node.borrow().left.clone() {
+            Some(left) => left,
+            None => return,
+        };
+        let left_right = match left.borrow().right.clone() {
+            Some(left_right) => left_right,
+            None => return,
         };
+        self.observer.notify(|o| {
+            o.on_rotate(&left.borrow().value, Direction::Left);
+            o.on_rotate(&node.borrow().value, Direction::Right);
+        });
+        let parent = node.borrow().parent.as_ref().and_then(|p| p.upgrade());
+
+        let mut left_right_borrow_mut = left_right.borrow_mut();
+        let inner_left = left_right_borrow_mut.left.take();
+        let inner_right = left_right_borrow_mut.right.take();
+        Self::adopt(&inner_left, &left);
+        Self::adopt(&inner_right, node);
+        left.borrow_mut().right = inner_left;
+        node.borrow_mut().left = inner_right;
+
+        left_right_borrow_mut.left = Some(left.clone());
+        left_right_borrow_mut.right = Some(node.clone());
+        drop(left_right_borrow_mut);
+        node.borrow_mut().parent = Some(Rc::downgrade(&left_right));
+        left.borrow_mut().parent = Some(Rc::downgrade(&left_right));
+        node.borrow_mut().update_summary();
+        left.borrow_mut().update_summary();
+        left_right.borrow_mut().update_summary();
+        self.replace_child(parent, node, &left_right);
+        self.stats.record(|s| s.double_rotations += 1);
     }
 
-    fn delete_balanced(&mut self, node: &Link<T>) {
+    fn delete_balanced(&mut self, node: &Link<T, S>) {
         let node_borrow_mut = node.borrow_mut();
         match node_borrow_mut.parent.as_ref() {
             Some(parent_weak) =>{
@@ -412,7 +584,7 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
                 match (parent_node_color, node_sibling_color){
                     (NodeColor::Black, NodeColor::Black) => { 
                         if node_sibling_left_color == NodeColor::Black && node_sibling_right_color == NodeColor::Black { // case 8
-                            node_sibling_borrow_mut.color = NodeColor::Red;
+                            self.recolor(&mut node_sibling_borrow_mut, NodeColor::Red);
                             drop(node_borrow_mut);
                             drop(node_sibling_borrow_mut);
                             drop(parent_borrow_mut);
@@ -420,10 +592,10 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
                         }else {
                             if flag_color == NodeColor::Red { // case 5 6  [R_L] / [L_R]
                                 if node_borrow_mut.value.clone() < parent_node_value {
-                                    node_sibling_borrow_mut.left.as_mut().unwrap().borrow_mut().color = NodeColor::Black;
+                                    self.recolor(&mut node_sibling_borrow_mut.left.as_ref().unwrap().borrow_mut(), NodeColor::Black);
                                     drop(node_sibling_borrow_mut.left.as_mut().unwrap().borrow_mut());
                                 }else {
-                                    node_sibling_borrow_mut.right.as_mut().unwrap().borrow_mut().color = NodeColor::Black;
+                                    self.recolor(&mut node_sibling_borrow_mut.right.as_ref().unwrap().borrow_mut(), NodeColor::Black);
                                     drop(node_sibling_borrow_mut.right.as_mut().unwrap().borrow_mut());
                                 }
                                 drop(node_sibling_borrow_mut);
@@ -432,15 +604,15 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
                                 (self.rotation_function_inner)(self, &parent_node);
                             }else { // case 7
                                 if node_borrow_mut.value.clone() < parent_node_value {
-                                    node_sibling_borrow_mut.right.as_mut().unwrap().borrow_mut().color = NodeColor::Black;
+                                    self.recolor(&mut node_sibling_borrow_mut.right.as_ref().unwrap().borrow_mut(), NodeColor::Black);
                                     drop(node_sibling_borrow_mut.right.as_mut().unwrap().borrow_mut());
                                 }else {
-                                    node_sibling_borrow_mut.left.as_mut().unwrap().borrow_mut().color = NodeColor::Black;
+                                    self.recolor(&mut node_sibling_borrow_mut.left.as_ref().unwrap().borrow_mut(), NodeColor::Black);
                                     drop(node_sibling_borrow_mut.left.as_mut().unwrap().borrow_mut());
                                 }
-                                node_sibling_borrow_mut.color = NodeColor::Black;
+                                self.recolor(&mut node_sibling_borrow_mut, NodeColor::Black);
                                 drop(node_sibling_borrow_mut);
-                                parent_borrow_mut.color = NodeColor::Black;
+                                self.recolor(&mut parent_borrow_mut, NodeColor::Black);
                                 drop(node_borrow_mut);
                                 drop(parent_borrow_mut);
                                 (self.rotation_function_extern)(self, &parent_node);                                
@@ -449,36 +621,36 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
                     },
                     (NodeColor::Red, NodeColor::Black) => {
                         if node_sibling_left_color == NodeColor::Black && node_sibling_right_color == NodeColor::Black{ // case 3
-                            node_sibling_borrow_mut.color = NodeColor::Red;
+                            self.recolor(&mut node_sibling_borrow_mut, NodeColor::Red);
                             drop(node_sibling_borrow_mut);
-                            parent_borrow_mut.color = NodeColor::Black;
+                            self.recolor(&mut parent_borrow_mut, NodeColor::Black);
                             drop(node_borrow_mut);
                             drop(parent_borrow_mut);
                         }else{
                             if flag_color == NodeColor::Red { // case 0 1
                                 if node_borrow_mut.value.clone() < parent_node_value {
-                                    node_sibling_borrow_mut.left.as_mut().unwrap().borrow_mut().color = NodeColor::Red;
+                                    self.recolor(&mut node_sibling_borrow_mut.left.as_ref().unwrap().borrow_mut(), NodeColor::Red);
                                     drop(node_sibling_borrow_mut.left.as_mut().unwrap().borrow_mut());
                                 }else {
-                                    node_sibling_borrow_mut.right.as_mut().unwrap().borrow_mut().color = NodeColor::Red;
+                                    self.recolor(&mut node_sibling_borrow_mut.right.as_ref().unwrap().borrow_mut(), NodeColor::Red);
                                     drop(node_sibling_borrow_mut.right.as_mut().unwrap().borrow_mut());
                                 }
                                 drop(node_sibling_borrow_mut);
-                                parent_borrow_mut.color = NodeColor::Black;
+                                self.recolor(&mut parent_borrow_mut, NodeColor::Black);
                                 drop(node_borrow_mut);
                                 drop(parent_borrow_mut);
                                 (self.rotation_function_inner)(self, &parent_node);
                             }else { // case 2
                                 if node_borrow_mut.value.clone() < parent_node_value {
-                                    node_sibling_borrow_mut.right.as_mut().unwrap().borrow_mut().color = NodeColor::Black;
+                                    self.recolor(&mut node_sibling_borrow_mut.right.as_ref().unwrap().borrow_mut(), NodeColor::Black);
                                     // drop(node_sibling_borrow_mut.right.as_mut());
                                 }else {
-                                    node_sibling_borrow_mut.left.as_mut().unwrap().borrow_mut().color = NodeColor::Black;
+                                    self.recolor(&mut node_sibling_borrow_mut.left.as_ref().unwrap().borrow_mut(), NodeColor::Black);
                                     // drop(node_sibling_borrow_mut.left.as_mut());
                                 }
-                                node_sibling_borrow_mut.color = NodeColor::Red;
+                                self.recolor(&mut node_sibling_borrow_mut, NodeColor::Red);
                                 drop(node_sibling_borrow_mut);
-                                parent_borrow_mut.color = NodeColor::Black;
+                                self.recolor(&mut parent_borrow_mut, NodeColor::Black);
                                 drop(node_borrow_mut);
                                 drop(parent_borrow_mut);
                                 (self.rotation_function_extern)(self, &parent_node);
@@ -486,9 +658,9 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
                         }
                     },
                     (NodeColor::Black, NodeColor::Red) => { // case 4
-                        node_sibling_borrow_mut.color = NodeColor::Black;
+                        self.recolor(&mut node_sibling_borrow_mut, NodeColor::Black);
                         drop(node_sibling_borrow_mut);
-                        parent_borrow_mut.color = NodeColor::Red;
+                        self.recolor(&mut parent_borrow_mut, NodeColor::Red);
                         drop(node_borrow_mut);
                         drop(parent_borrow_mut);
                         (self.rotation_function_extern)(self, &parent_node);
@@ -502,12 +674,24 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
     }
 
 
-    pub fn try_get_node(&self, value: T) -> Option<Link<T>> {
+    // read-only handle on the node holding `value`
+    pub fn try_get_node(&self, value: T) -> Option<NodeRef<'_, T, S>> {
+        self.stats.record(|s| s.operations += 1);
+        self.get_node(value).map(NodeRef::new)
+    }
+
+    // read-only handle on the root node
+    pub fn root_node(&self) -> Option<NodeRef<'_, T, S>> {
+        self.root.clone().map(NodeRef::new)
+    }
+
+    fn get_node(&self, value: T) -> Option<Link<T, S>> {
         let mut current = self.root.clone();
         while let Some(node) = current {
-            if node.borrow().value == value {
+            self.stats.record(|s| s.nodes_visited += 1);
+            if self.stats.compare(node.borrow().value == value) {
                 return Some(node.clone());
-            } else if node.borrow().value < value {
+            } else if self.stats.compare(node.borrow().value < value) {
                 current = node.borrow().right.clone();
             } else {
                 current = node.borrow().left.clone();
@@ -516,7 +700,7 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
         None
     }
     
-    fn find_min_node(node: Link<T>) -> Link<T> {
+    fn find_min_node(node: Link<T, S>) -> Link<T, S> {
         let mut current = node;
         while current.borrow().left.is_some() {
             let left = current.borrow().left.clone().unwrap();
@@ -525,7 +709,65 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
         current
     }
 
-    fn delete_node(&mut self, node: Link<T>) {
+    fn find_max_node(node: Link<T, S>) -> Link<T, S> {
+        let mut current = node;
+        while current.borrow().right.is_some() {
+            let right = current.borrow().right.clone().unwrap();
+            current = right;
+        }
+        current
+    }
+
+    // next node in order, walking up through parent pointers when there is no right subtree
+    fn successor(node: &Link<T, S>) -> Option<Link<T, S>> {
+        if let Some(right) = node.borrow().right.clone() {
+            return Some(Self::find_min_node(right));
+        }
+        let mut child = node.clone();
+        let mut parent = node.borrow().parent.as_ref().and_then(|p| p.upgrade());
+        while let Some(p) = parent {
+            if Self::is_left_child(&child) {
+                return Some(p);
+            }
+            parent = p.borrow().parent.as_ref().and_then(|p| p.upgrade());
+            child = p;
+        }
+        None
+    }
+
+    // previous node in order, mirror of successor
+    fn predecessor(node: &Link<T, S>) -> Option<Link<T, S>> {
+        if let Some(left) = node.borrow().left.clone() {
+            return Some(Self::find_max_node(left));
+        }
+        let mut child = node.clone();
+        let mut parent = node.borrow().parent.as_ref().and_then(|p| p.upgrade());
+        while let Some(p) = parent {
+            if !Self::is_left_child(&child) {
+                return Some(p);
+            }
+            parent = p.borrow().parent.as_ref().and_then(|p| p.upgrade());
+            child = p;
+        }
+        None
+    }
+
+    // cursor on the first node whose value is not less than `value`
+    pub fn lower_bound_cursor(&mut self, value: T) -> CursorMut<'_, T, S> {
+        let mut current = self.root.clone();
+        let mut found = None;
+        while let Some(node) = current {
+            if node.borrow().value < value {
+                current = node.borrow().right.clone();
+            } else {
+                current = node.borrow().left.clone();
+                found = Some(node);
+            }
+        }
+        CursorMut { tree: self, current: found }
+    }
+
+    fn delete_node(&mut self, node: Link<T, S>) {
         let mut node_borrow_mut = node.borrow_mut();
         match node_borrow_mut.parent.as_mut() {
             Some(parent_weak) =>{
@@ -541,19 +783,23 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
                             drop(parent_borrow_mut);
                             let successor_node = Self::find_min_node(node_borrow_mut.right.as_ref().unwrap().clone());
                             node_borrow_mut.value = successor_node.borrow().value.clone(); // swap the value
+                            node_borrow_mut.count = successor_node.borrow().count;
                             drop(node_borrow_mut);
                             self.delete_node(successor_node);
                         },
                         (true, false) | (false, true) => { // Node with only one child
                             *node_self = node_borrow_mut.left.take().or(node_borrow_mut.right.take());
                             let child = node_self.as_mut().unwrap();
-                            child.borrow_mut().color = NodeColor::Black;
+                            self.recolor(&mut child.borrow_mut(), NodeColor::Black);
                             child.borrow_mut().parent = node_borrow_mut.parent.clone();
+                            drop(parent_borrow_mut);
+                            Self::refresh_upward(parent_node.clone());
                         },
                         (false, false) => {
                             *node_self = None;
+                            drop(parent_borrow_mut);
+                            Self::refresh_upward(parent_node.clone());
                             if node_borrow_mut.color.clone() == NodeColor::Black{ // double black, node_sibling must exist
-                                drop(parent_borrow_mut);
                                 drop(node_borrow_mut);
                                 self.delete_balanced(&node);
                             };
@@ -566,6 +812,7 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
                     (true, true) => { // Root node with both children
                         let successor_node = Self::find_min_node(node_borrow_mut.right.as_ref().unwrap().clone());
                         node_borrow_mut.value = successor_node.borrow().value.clone();
+                        node_borrow_mut.count = successor_node.borrow().count;
                         drop(node_borrow_mut);
                         self.delete_node(successor_node);
                     },
@@ -578,7 +825,7 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
                                 drop(node_borrow_mut);
                                 self.delete_balanced(child);
                             } else {
-                                child.borrow_mut().color = NodeColor::Black;
+                                self.recolor(&mut child.borrow_mut(), NodeColor::Black);
                                 child.borrow_mut().parent = None;
                             };
                         });
@@ -592,14 +839,176 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
     }
     // 2 - Delete a node from the red-black tree.
     pub fn delete(&mut self, value: T) -> bool{
-        if let Some(node) = self.try_get_node(value) {
+        self.stats.record(|s| s.operations += 1);
+        if let Some(node) = self.get_node(value.clone()) {
             // println!("{:?}", node);
-            self.delete_node(node);
+            self.remove_node(node, &value);
             return true;
         }
         return false;
     }
 
+    // take a node out with all of its occurrences, return how many there were
+    fn remove_node(&mut self, node: Link<T, S>, value: &T) -> usize {
+        let removed = node.borrow().count;
+        self.delete_node(node);
+        self.count -= 1;
+        self.removed(value, removed);
+        removed
+    }
+
+    // Every removal ends here: the observer hears of each occurrence that went away, the
+    // way insert reports each occurrence added
+    fn removed(&self, value: &T, occurrences: usize) {
+        self.observer.notify(|o| {
+            for _ in 0..occurrences {
+                o.on_delete(value);
+            }
+        });
+    }
+
+    // number of occurrences of a value, 0 if absent
+    pub fn count(&self, value: T) -> usize {
+        self.get_node(value).map_or(0, |node| node.borrow().count)
+    }
+
+    // remove a single occurrence, the node goes away with its last occurrence
+    pub fn remove_one(&mut self, value: T) -> bool {
+        match self.get_node(value.clone()) {
+            Some(node) => {
+                if node.borrow().count > 1 {
+                    node.borrow_mut().count -= 1;
+                    Self::refresh_upward(node);
+                    self.removed(&value, 1);
+                } else {
+                    self.remove_node(node, &value);
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    // remove every occurrence of a value, return how many were removed
+    pub fn remove_all(&mut self, value: T) -> usize {
+        match self.get_node(value.clone()) {
+            Some(node) => self.remove_node(node, &value),
+            None => 0,
+        }
+    }
+
+    // recompute summaries from a node up to the root after its subtree changed
+    fn refresh_upward(node: Link<T, S>) {
+        let mut current = Some(node);
+        while let Some(n) = current {
+            n.borrow_mut().update_summary();
+            current = n.borrow().parent.as_ref().and_then(|p| p.upgrade());
+        }
+    }
+
+    // summary of the whole tree, None when it is empty
+    pub fn summary(&self) -> Option<S> {
+        self.root.as_ref().map(|root| root.borrow().summary.clone())
+    }
+
+    // summary of the values in `low..high`, None when the range is empty
+    // only the two boundary paths are walked, whole subtrees in between use their stored summary
+    pub fn range_summary(&self, low: T, high: T) -> Option<S> {
+        Self::range_summary_node(&self.root, Some(&low), Some(&high))
+    }
+
+    // `low` is inclusive and `high` exclusive, None means unbounded on that side
+    fn range_summary_node(node: &Option<Link<T, S>>, low: Option<&T>, high: Option<&T>) -> Option<S> {
+        let node = node.as_ref()?;
+        let n = node.borrow();
+        if low.is_some_and(|low| n.value < *low) {
+            return Self::range_summary_node(&n.right, low, high);
+        }
+        if high.is_some_and(|high| n.value >= *high) {
+            return Self::range_summary_node(&n.left, low, high);
+        }
+        // the value is inside the range, so each side is only bounded from one end
+        let left = match low {
+            Some(_) => Self::range_summary_node(&n.left, low, None),
+            None => n.left.as_ref().map(|left| left.borrow().summary.clone()),
+        };
+        let right = match high {
+            Some(_) => Self::range_summary_node(&n.right, None, high),
+            None => n.right.as_ref().map(|right| right.borrow().summary.clone()),
+        };
+        S::combine_opt(S::combine_opt(left, Some(S::lift(&n.value, n.count))), right)
+    }
+
+    // values inside `range` in order, repeated values once per occurrence
+    // subtrees that lie wholly outside the range are skipped
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Vec<T> {
+        let mut values = Vec::new();
+        Self::range_node(&self.root, &range, &mut values);
+        values
+    }
+
+    fn range_node<R: RangeBounds<T>>(node: &Option<Link<T, S>>, range: &R, values: &mut Vec<T>) {
+        if let Some(node) = node {
+            let n = node.borrow();
+            let after_start = match range.start_bound() {
+                Bound::Included(start) => n.value >= *start,
+                Bound::Excluded(start) => n.value > *start,
+                Bound::Unbounded => true,
+            };
+            let before_end = match range.end_bound() {
+                Bound::Included(end) => n.value <= *end,
+                Bound::Excluded(end) => n.value < *end,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                Self::range_node(&n.left, range, values);
+            }
+            if after_start && before_end {
+                values.extend(std::iter::repeat_n(n.value.clone(), n.count));
+            }
+            if before_end {
+                Self::range_node(&n.right, range, values);
+            }
+        }
+    }
+
+    // number of stored values smaller than `value`, repeated values once per occurrence
+    // one path down: a step right skips the left subtree and the node by their sizes
+    pub fn rank(&self, value: T) -> usize {
+        let mut rank = 0;
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let n = node.borrow();
+            if n.value < value {
+                rank += n.left.as_ref().map_or(0, |left| left.borrow().size) + n.count;
+                current = n.right.clone();
+            } else {
+                current = n.left.clone();
+            }
+        }
+        rank
+    }
+
+    // iterate values in order, repeated values are yielded once per occurrence
+    pub fn iter(&self) -> Iter<T, S> {
+        Iter::new(&self.root)
+    }
+
+    // walk the nodes root first, then the left and right subtrees
+    pub fn pre_order(&self) -> PreOrderIter<T, S> {
+        PreOrderIter { stack: self.root.iter().map(|root| (root.clone(), 0)).collect() }
+    }
+
+    // walk the nodes with both subtrees before the node itself
+    pub fn post_order(&self) -> PostOrderIter<T, S> {
+        PostOrderIter { stack: self.root.iter().map(|root| (root.clone(), 0, false)).collect() }
+    }
+
+    // walk the nodes level by level from the root down
+    pub fn level_order(&self) -> LevelOrderIter<T, S> {
+        LevelOrderIter { queue: self.root.iter().map(|root| (root.clone(), 0)).collect() }
+    }
+
     // 3- Count the number of leaves in a tree.
     pub fn get_leaves_number(&self) -> u32 {
         self.root.as_ref().map_or(0, |root| {
@@ -647,7 +1056,7 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
         Self::in_order_traversal_node(&self.root, visit);
     }
 
-    fn in_order_traversal_node(node: &Option<Link<T>>, visit: &dyn Fn(&T)) {
+    fn in_order_traversal_node(node: &Option<Link<T, S>>, visit: &dyn Fn(&T)) {
         node.as_ref().map(|n| {
             let n_borrow = n.borrow();
             Self::in_order_traversal_node(&n_borrow.left, visit);
@@ -662,7 +1071,7 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
     }
     
     // 7 - Print the tree showing its colors and structure.
-    fn recursion_print(node: &Option<Link<T>>, pre_space: &String, is_left: bool, child_pre: String) {
+    fn recursion_print<W: fmt::Write>(out: &mut W, node: &Option<Link<T, S>>, pre_space: &String, is_left: bool, child_pre: String) -> fmt::Result {
         let none_pre = if is_left { "├───" } else { "└───" };
         let pre_current = if is_left { "├───" } else { "└───" };
         let pre_child = if is_left { "|   " } else { "    " };
@@ -670,30 +1079,125 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
         node.as_ref().map(|node| {
             let node = node.borrow();
             let col = if node.color == NodeColor::Black { "Black" } else { "Red" };
-            println!("{}{}{} {:?}:{}", pre_space, pre_current, child_pre, node.value, col);
+            writeln!(out, "{}{}{} {:?}:{}", pre_space, pre_current, child_pre, node.value, col)?;
 
             let mut pre_space = pre_space.to_owned();
             pre_space.push_str(&pre_child);
 
-            Self::recursion_print(&node.left, &pre_space, true, "L".to_string());
-            Self::recursion_print(&node.right, &pre_space, false, "R".to_string());
-        }).unwrap_or_else(|| println!("{}{}{}", pre_space, none_pre, "null"));
+            Self::recursion_print(out, &node.left, &pre_space, true, "L".to_string())?;
+            Self::recursion_print(out, &node.right, &pre_space, false, "R".to_string())
+        }).unwrap_or_else(|| writeln!(out, "{}{}{}", pre_space, none_pre, "null"))
     }
 
 
     pub fn print_tree(&self) {
-        println!("\n================== TREE PRINT <Node:Color> ==================");
-        Self::recursion_print(&self.root, &"".to_string(), false, "Root".to_string());
-        println!("\n======================= FINISH PRINT ========================");
+        print!("{}", self.structure_string());
+    }
+
+    // the box-drawing picture print_tree shows, every node with its color
+    pub fn structure_string(&self) -> String {
+        let mut out = String::from("\n================== TREE PRINT <Node:Color> ==================\n");
+        // writing into a String cannot fail
+        Self::recursion_print(&mut out, &self.root, &"".to_string(), false, "Root".to_string()).unwrap();
+        out.push_str("\n======================= FINISH PRINT ========================\n");
+        out
+    }
+
+    // values in order separated by spaces
+    pub fn write_in_order<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        for (index, value) in self.iter().enumerate() {
+            if index > 0 {
+                out.write_char(' ')?;
+            }
+            write!(out, "{}", value)?;
+        }
+        Ok(())
+    }
+
+    pub fn in_order_string(&self) -> String {
+        let mut out = String::new();
+        self.write_in_order(&mut out).unwrap();
+        out
+    }
+
+    // the tree in one of the pretty-printing layouts, values carry their color as `:R` or `:B`
+    pub fn to_pretty_string(&self, layout: Layout) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, layout).unwrap();
+        out
+    }
+
+    pub fn write_pretty<W: fmt::Write>(&self, out: &mut W, layout: Layout) -> fmt::Result {
+        let root = Self::pretty_node(&self.root);
+        pretty::write_tree(out, root.as_deref(), layout)
+    }
+
+    pub fn write_pretty_io<W: io::Write>(&self, out: &mut W, layout: Layout) -> io::Result<()> {
+        out.write_all(self.to_pretty_string(layout).as_bytes())
+    }
+
+    fn pretty_node(node: &Option<Link<T, S>>) -> Option<Box<PrettyNode>> {
+        let node = node.as_ref()?.borrow();
+        let color = if node.color == NodeColor::Red { "R" } else { "B" };
+        Some(Box::new(PrettyNode {
+            label: format!("{}:{}", node.label(), color),
+            left: Self::pretty_node(&node.left),
+            right: Self::pretty_node(&node.right),
+        }))
+    }
+
+    // Graphviz DOT document of the tree with the nodes filled in their colors
+    pub fn to_dot(&self) -> String {
+        let mut dot = DotWriter::new("RedBlackTree");
+        if self.root.is_some() {
+            Self::dot_node(&self.root, &mut dot);
+        }
+        dot.finish()
+    }
+
+    // standalone SVG drawing of the tree, laid out as a tidy tree
+    pub fn to_svg(&self) -> String {
+        let root = Self::svg_node(&self.root);
+        svg::render(root.as_deref(), "Red-Black Tree")
+    }
+
+    fn svg_node(node: &Option<Link<T, S>>) -> Option<Box<SvgNode>> {
+        let node = node.as_ref()?.borrow();
+        let fill = if node.color == NodeColor::Red { "#d62728" } else { "#222222" };
+        Some(Box::new(SvgNode {
+            label: node.label(),
+            detail: None,
+            fill,
+            text_color: "#ffffff",
+            left: Self::svg_node(&node.left),
+            right: Self::svg_node(&node.right),
+        }))
+    }
+
+    // write the subtree and return the id of its top node
+    fn dot_node(node: &Option<Link<T, S>>, dot: &mut DotWriter) -> String {
+        let node = match node {
+            Some(node) => node.borrow(),
+            None => return dot.nil(),
+        };
+        let fill = if node.color == NodeColor::Red { "red" } else { "black" };
+        let id = dot.node(&node.label(), &format!("style=filled, fillcolor={}, fontcolor=white", fill));
+        for child in [&node.left, &node.right] {
+            let child_id = Self::dot_node(child, dot);
+            dot.edge(&id, &child_id);
+        }
+        id
     }
 
 
     pub fn search_node(&mut self, value: T) -> bool{
+        self.stats.record(|s| s.operations += 1);
         let mut current = self.root.clone();
         while let Some(node) = current {
-            if node.borrow().value == value {
+            self.stats.record(|s| s.nodes_visited += 1);
+            if self.stats.compare(node.borrow().value == value) {
                 return true;
-            } else if node.borrow().value < value {
+            } else if self.stats.compare(node.borrow().value < value) {
                 current = node.borrow().right.clone();
             } else {
                 current = node.borrow().left.clone();
@@ -702,4 +1206,439 @@ impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display> RedBlackTree<T
         return false;
     }
 
-}
\ No newline at end of file
+    // Start counting rotations, recolorings, comparisons and visited nodes. Counting is off by
+    // default and keeps its totals while switched off, reset_stats clears them.
+    pub fn enable_stats(&self) {
+        self.stats.enable();
+    }
+
+    pub fn disable_stats(&self) {
+        self.stats.disable();
+    }
+
+    // totals since the tree was built or the counters were last reset
+    pub fn stats(&self) -> TreeStats {
+        self.stats.get()
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.reset();
+    }
+
+    // attach an observer for inserts, deletes, rotations and recolorings, None detaches it
+    pub fn set_observer(&mut self, observer: Option<ObserverRef<T>>) {
+        self.observer.set(observer);
+    }
+
+    // Build the tree with exactly the shape and colors written in bracket notation, e.g.
+    // `(B4 (R2 B1 B3) B6)` (see `bracket::parse`). Every node needs a color. The result is
+    // checked with `check_invariants`, so a shape that breaks a red-black rule is reported as
+    // an error. A `value*count` node turns on multiset mode.
+    pub fn from_bracket(text: &str) -> Result<Self, String>
+    where
+        T: FromStr,
+    {
+        Self::from_shape(false, bracket::parse(text)?)
+    }
+
+    // the tree with exactly the shape and colors of `root`, checked with `check_invariants`
+    fn from_shape(multiset: bool, root: Option<Box<BracketNode<T>>>) -> Result<Self, String> {
+        let mut tree = Self::with_summary();
+        tree.multiset = multiset;
+        if let Some(root) = root {
+            tree.root = Some(tree.build_node(*root, None)?);
+        }
+        tree.check_invariants().map_err(|error| format!("not a valid red-black tree: {}", error))?;
+        Ok(tree)
+    }
+
+    fn build_node(&mut self, parsed: BracketNode<T>, parent: Option<&Link<T, S>>) -> Result<Link<T, S>, String> {
+        let color = match parsed.red {
+            Some(true) => NodeColor::Red,
+            Some(false) => NodeColor::Black,
+            None => return Err(format!("value {:?} has no color, write it as R{:?} or B{:?}", parsed.value, parsed.value, parsed.value)),
+        };
+        let node = Rc::new(RefCell::new(TreeNode::new(parsed.value)));
+        self.count += 1;
+        self.multiset |= parsed.count > 1;
+        let left = match parsed.left {
+            Some(left) => Some(self.build_node(*left, Some(&node))?),
+            None => None,
+        };
+        let right = match parsed.right {
+            Some(right) => Some(self.build_node(*right, Some(&node))?),
+            None => None,
+        };
+        {
+            let mut n = node.borrow_mut();
+            n.color = color;
+            n.count = parsed.count;
+            n.parent = parent.map(Rc::downgrade);
+            n.left = left;
+            n.right = right;
+            n.update_summary();
+        }
+        Ok(node)
+    }
+
+    // the tree in the binary format described in `codec`
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        T: Codec,
+    {
+        codec::encode_tree(TreeKind::RedBlack, self.multiset, self.iter())
+    }
+
+    // Load a tree written by `to_bytes`. The entries are stored sorted, so the tree is built
+    // directly from them in O(n) instead of inserting them one by one.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String>
+    where
+        T: Codec,
+    {
+        let (multiset, entries) = codec::decode_tree(TreeKind::RedBlack, bytes)?;
+        Ok(Self::from_entries(multiset, &entries))
+    }
+
+    // `[1, 2, 2, 5]`, the values in order and repeated once per occurrence
+    pub fn to_json_array(&self) -> String {
+        interchange::values_to_json(self.iter())
+    }
+
+    // The exact shape as nested objects, missing children are null:
+    //     {"type": "red-black", "multiset": false, "nodes": 3,
+    //      "root": {"value": 2, "count": 1, "color": "black", "left": {...}, "right": {...}}}
+    pub fn to_json_tree(&self) -> String {
+        Json::Object(vec![
+            ("type".to_string(), Json::String(TreeKind::RedBlack.id().to_string())),
+            ("multiset".to_string(), Json::Bool(self.multiset)),
+            ("nodes".to_string(), Json::Number(self.count.to_string())),
+            ("root".to_string(), Self::json_node(&self.root)),
+        ]).to_pretty_string()
+    }
+
+    fn json_node(node: &Option<Link<T, S>>) -> Json {
+        let node = match node {
+            Some(node) => node.borrow(),
+            None => return Json::Null,
+        };
+        let color = if node.color == NodeColor::Red { "red" } else { "black" };
+        Json::Object(vec![
+            ("value".to_string(), interchange::value_to_json(&node.value)),
+            ("count".to_string(), Json::Number(node.count.to_string())),
+            ("color".to_string(), Json::String(color.to_string())),
+            ("left".to_string(), Self::json_node(&node.left)),
+            ("right".to_string(), Self::json_node(&node.right)),
+        ])
+    }
+
+    // `value,count` rows in value order
+    pub fn to_csv(&self) -> String {
+        interchange::values_to_csv(self.iter())
+    }
+
+    // Load either JSON form. A flat array has to be sorted and is built in O(n), repeated
+    // values turn on multiset mode. A nested document keeps its shape and colors and is
+    // checked like `from_bracket`.
+    pub fn from_json(text: &str) -> Result<Self, String>
+    where
+        T: FromStr,
+    {
+        match interchange::parse_json_tree(text, TreeKind::RedBlack)? {
+            JsonTree::Values(entries) => Ok(Self::from_entries(entries.iter().any(|(_, count)| *count > 1), &entries)),
+            JsonTree::Shape { multiset, root } => Self::from_shape(multiset, root),
+        }
+    }
+
+    // load what `to_csv` wrote, the rows have to be sorted
+    pub fn from_csv(text: &str) -> Result<Self, String>
+    where
+        T: FromStr,
+    {
+        let entries = interchange::parse_csv_tree(text)?;
+        Ok(Self::from_entries(entries.iter().any(|(_, count)| *count > 1), &entries))
+    }
+
+    fn from_entries(multiset: bool, entries: &[(T, usize)]) -> Self {
+        let mut tree = Self::with_summary();
+        tree.multiset = multiset;
+        // splitting at the middle fills every level above floor(log2(n + 1)) completely, so
+        // coloring the nodes on that last, partial level red gives every path the same
+        // number of black nodes
+        let red_depth = (usize::BITS - 1 - (entries.len() + 1).leading_zeros()) as usize;
+        tree.root = Self::build_sorted(entries, None, 0, red_depth);
+        tree.count = entries.len();
+        tree
+    }
+
+    fn build_sorted(entries: &[(T, usize)], parent: Option<&Link<T, S>>, depth: usize, red_depth: usize) -> Option<Link<T, S>> {
+        if entries.is_empty() {
+            return None;
+        }
+        let mid = entries.len() / 2;
+        let (value, count) = entries[mid].clone();
+        let node = Rc::new(RefCell::new(TreeNode::new(value)));
+        let left = Self::build_sorted(&entries[..mid], Some(&node), depth + 1, red_depth);
+        let right = Self::build_sorted(&entries[mid + 1..], Some(&node), depth + 1, red_depth);
+        {
+            let mut n = node.borrow_mut();
+            n.color = if depth == red_depth { NodeColor::Red } else { NodeColor::Black };
+            n.count = count;
+            n.parent = parent.map(Rc::downgrade);
+            n.left = left;
+            n.right = right;
+            n.update_summary();
+        }
+        Some(node)
+    }
+
+    // Walk the whole tree and report the first broken red-black property: a black root, no red
+    // node with a red child, the same number of black nodes on every path, key order and
+    // parent links.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if let Some(root) = &self.root {
+            let root = root.borrow();
+            if root.color != NodeColor::Black {
+                return Err(format!("root {:?} is red", root.value));
+            }
+            if root.parent.is_some() {
+                return Err(format!("root {:?} has a parent", root.value));
+            }
+        }
+        let (_, nodes) = Self::check_node(&self.root, None, None)?;
+        if nodes != self.count {
+            return Err(format!("tree holds {} nodes but counts {}", nodes, self.count));
+        }
+        Ok(())
+    }
+
+    // black height and node count of a subtree whose values must lie strictly between `low`
+    // and `high`
+    fn check_node(node: &Option<Link<T, S>>, low: Option<&T>, high: Option<&T>) -> Result<(usize, usize), String> {
+        let node = match node {
+            Some(node) => node,
+            None => return Ok((1, 0)),
+        };
+        let n = node.borrow();
+        if low.is_some_and(|low| n.value <= *low) || high.is_some_and(|high| n.value >= *high) {
+            return Err(format!("value {:?} is out of order", n.value));
+        }
+        if n.count == 0 {
+            return Err(format!("value {:?} has no occurrences", n.value));
+        }
+        for child in [&n.left, &n.right].into_iter().flatten() {
+            let c = child.borrow();
+            let parent_ok = c.parent.as_ref().and_then(|p| p.upgrade()).is_some_and(|p| Rc::ptr_eq(&p, node));
+            if !parent_ok {
+                return Err(format!("child {:?} of {:?} has a wrong parent link", c.value, n.value));
+            }
+            if n.color == NodeColor::Red && c.color == NodeColor::Red {
+                return Err(format!("red node {:?} has the red child {:?}", n.value, c.value));
+            }
+        }
+        let size = n.left.as_ref().map_or(0, |c| c.borrow().size) + n.count + n.right.as_ref().map_or(0, |c| c.borrow().size);
+        if n.size != size {
+            return Err(format!("value {:?} stores subtree size {} instead of {}", n.value, n.size, size));
+        }
+        let (left, left_nodes) = Self::check_node(&n.left, low, Some(&n.value))?;
+        let (right, right_nodes) = Self::check_node(&n.right, Some(&n.value), high)?;
+        if left != right {
+            return Err(format!("value {:?} has black heights {} and {} below it", n.value, left, right));
+        }
+        Ok((left + if n.color == NodeColor::Black { 1 } else { 0 }, left_nodes + right_nodes + 1))
+    }
+}
+
+// the horizontal layout, `to_pretty_string` gives the others
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display, S: Summary<T>> fmt::Display for RedBlackTree<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_pretty(f, Layout::Horizontal)
+    }
+}
+
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display, S: Summary<T>> OrderedSet<T> for RedBlackTree<T, S> {
+    fn new() -> Self {
+        Self::with_summary()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        if self.get_node(value.clone()).is_some() {
+            self.stats.record(|s| s.operations += 1);
+            return false;
+        }
+        RedBlackTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        RedBlackTree::delete(self, value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        self.stats.record(|s| s.operations += 1);
+        self.get_node(value).is_some()
+    }
+
+    fn len(&self) -> usize {
+        self.count
+    }
+
+    fn is_empty(&self) -> bool {
+        RedBlackTree::is_empty(self)
+    }
+
+    fn height(&self) -> usize {
+        self.get_height() as usize
+    }
+
+    fn leaves(&self) -> usize {
+        self.get_leaves_number() as usize
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+
+    fn print_structure(&self) {
+        self.print_tree();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        RedBlackTree::check_invariants(self)
+    }
+}
+
+// lets `DurableTree` keep this tree on disk, snapshots use the binary format of `to_bytes`
+impl<T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display + Codec, S: Summary<T>> SnapshotTree<T> for RedBlackTree<T, S> {
+    fn snapshot_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn restore(bytes: &[u8]) -> Result<Self, String> {
+        Self::from_bytes(bytes)
+    }
+}
+
+
+// read-only view of a node, it borrows the tree so the structure cannot change while it is held
+#[derive(Clone)]
+pub struct NodeRef<'a, T, S = ()> where T: Clone {
+    node: Link<T, S>,
+    tree: PhantomData<&'a RedBlackTree<T, S>>,
+}
+
+impl<'a, T: Clone, S: Clone> NodeRef<'a, T, S> {
+    fn new(node: Link<T, S>) -> Self {
+        NodeRef { node, tree: PhantomData }
+    }
+
+    pub fn value(&self) -> T {
+        self.node.borrow().value.clone()
+    }
+
+    pub fn color(&self) -> NodeColor {
+        self.node.borrow().color.clone()
+    }
+
+    // number of occurrences stored in the node, always 1 outside multiset mode
+    pub fn count(&self) -> usize {
+        self.node.borrow().count
+    }
+
+    // summary of the subtree rooted at this node
+    pub fn summary(&self) -> S {
+        self.node.borrow().summary.clone()
+    }
+
+    pub fn left(&self) -> Option<NodeRef<'a, T, S>> {
+        self.node.borrow().left.clone().map(NodeRef::new)
+    }
+
+    pub fn right(&self) -> Option<NodeRef<'a, T, S>> {
+        self.node.borrow().right.clone().map(NodeRef::new)
+    }
+
+    pub fn parent(&self) -> Option<NodeRef<'a, T, S>> {
+        self.node.borrow().parent.as_ref().and_then(|p| p.upgrade()).map(NodeRef::new)
+    }
+}
+
+// mutable cursor over the nodes of a red-black tree
+// `current` is None on the ghost position that sits past the last and before the first node
+pub struct CursorMut<'a, T, S = ()> where T: Clone {
+    tree: &'a mut RedBlackTree<T, S>,
+    current: Option<Link<T, S>>,
+}
+
+impl<'a, T: PartialOrd + Clone + std::fmt::Debug + std::fmt::Display, S: Summary<T>> CursorMut<'a, T, S> {
+    // value under the cursor, None on the ghost position
+    pub fn peek(&self) -> Option<T> {
+        self.current.as_ref().map(|node| node.borrow().value.clone())
+    }
+
+    // node after the cursor, the first node when sitting on the ghost position
+    fn next_node(&self) -> Option<Link<T, S>> {
+        match &self.current {
+            Some(node) => RedBlackTree::successor(node),
+            None => self.tree.root.clone().map(RedBlackTree::find_min_node),
+        }
+    }
+
+    // node before the cursor, the last node when sitting on the ghost position
+    fn prev_node(&self) -> Option<Link<T, S>> {
+        match &self.current {
+            Some(node) => RedBlackTree::predecessor(node),
+            None => self.tree.root.clone().map(RedBlackTree::find_max_node),
+        }
+    }
+
+    // step to the next node, from the last node the cursor moves to the ghost position
+    pub fn move_next(&mut self) {
+        self.current = self.next_node();
+    }
+
+    // step to the previous node, from the first node the cursor moves to the ghost position
+    pub fn move_prev(&mut self) {
+        self.current = self.prev_node();
+    }
+
+    fn next_value(&self) -> Option<T> {
+        self.next_node().map(|node| node.borrow().value.clone())
+    }
+
+    fn prev_value(&self) -> Option<T> {
+        self.prev_node().map(|node| node.borrow().value.clone())
+    }
+
+    // check that `low < value < high`, equal values are allowed in multiset mode
+    fn fits(&self, low: Option<T>, value: &T, high: Option<T>) -> bool {
+        let above = |bound: T| if self.tree.multiset { bound <= *value } else { bound < *value };
+        let below = |bound: T| if self.tree.multiset { *value <= bound } else { *value < bound };
+        low.is_none_or(above) && high.is_none_or(below)
+    }
+
+    // insert right before the cursor, fails if the value does not belong there
+    // the cursor keeps pointing at the same node
+    pub fn insert_before(&mut self, value: T) -> bool {
+        if !self.fits(self.prev_value(), &value, self.peek()) {
+            return false;
+        }
+        self.tree.insert(value)
+    }
+
+    // insert right after the cursor, fails if the value does not belong there
+    // the cursor keeps pointing at the same node
+    pub fn insert_after(&mut self, value: T) -> bool {
+        if !self.fits(self.peek(), &value, self.next_value()) {
+            return false;
+        }
+        self.tree.insert(value)
+    }
+
+    // remove the node under the cursor with all of its occurrences and move to the next node
+    pub fn remove_current(&mut self) -> Option<T> {
+        let value = self.peek()?;
+        let next = self.next_value();
+        self.tree.remove_all(value.clone());
+        self.current = next.and_then(|next| self.tree.get_node(next));
+        Some(value)
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use crate::commands::{self, CommandTree, COMMANDS};
+
+// Line-based command prompt, started with `project --repl`. It takes the same commands as
+// --ops, one per line, and answers in words instead of JSON:
+//     avl> insert 5 3 8
+//     Insert [5, 3, 8] successfully.
+//     avl> ra 3 7
+//     `ra` could be range or rank
+
+// the commands only the prompt knows, besides the tree commands
+const PROMPT_COMMANDS: &[(&str, &str, &str)] = &[
+    ("help", "[COMMAND]", "list the commands, or explain one"),
+    ("history", "[N]", "show the last N lines entered, 20 by default; `!N` runs line N again, `!!` the last one"),
+    ("quit", "", "leave, the end of the input does the same"),
+];
+
+// short names, most of them would be ambiguous as prefixes
+const ALIASES: &[(&str, &str)] = &[
+    ("i", "insert"),
+    ("d", "delete"),
+    ("c", "contains"),
+    ("p", "print"),
+    ("h", "help"),
+    ("?", "help"),
+    ("q", "quit"),
+    ("exit", "quit"),
+];
+
+// lines kept in the history file
+const HISTORY_LIMIT: usize = 500;
+
+// the default history file, `.balanced_tree_history` in the home directory
+pub fn default_history_path() -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    home.join(".balanced_tree_history")
+}
+
+// The lines entered so far, the ones from earlier sessions first. Every line is appended
+// to the file as soon as it is entered, so a crash loses nothing.
+struct History {
+    path: Option<PathBuf>,
+    lines: Vec<String>,
+}
+
+impl History {
+    fn load(path: PathBuf) -> History {
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.len() > HISTORY_LIMIT {
+            lines.drain(..lines.len() - HISTORY_LIMIT);
+            let mut trimmed = lines.join("\n");
+            trimmed.push('\n');
+            // the file only grows by one line per entry, trimming it can wait for the next run
+            let _ = fs::write(&path, trimmed);
+        }
+        History { path: Some(path), lines }
+    }
+
+    fn add(&mut self, line: &str) {
+        if self.lines.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.lines.push(line.to_string());
+        if let Some(path) = &self.path {
+            let written = OpenOptions::new().create(true).append(true).open(path)
+                .and_then(|mut file| writeln!(file, "{}", line));
+            if let Err(error) = written {
+                println!("warning: the history cannot be saved to {}: {}", path.display(), error);
+                self.path = None;
+            }
+        }
+    }
+
+    // `!!` is the last line, `!N` line N as `history` numbers them
+    fn recall(&self, text: &str) -> Result<String, String> {
+        let index = match text {
+            "!!" => self.lines.len().checked_sub(1).ok_or("the history is empty")?,
+            _ => {
+                let number: usize = text[1..].parse().map_err(|_| format!("`{}` is not a history line, use !N or !!", text))?;
+                if number == 0 || number > self.lines.len() {
+                    return Err(format!("there is no history line {}, the history has {} line(s)", number, self.lines.len()));
+                }
+                number - 1
+            },
+        };
+        Ok(self.lines[index].clone())
+    }
+
+    fn show(&self, count: usize) {
+        let start = self.lines.len().saturating_sub(count);
+        for (index, line) in self.lines.iter().enumerate().skip(start) {
+            println!("{:>5}  {}", index + 1, line);
+        }
+    }
+}
+
+// the full name of a command from its name, an alias or a prefix that only one command has
+fn resolve(word: &str) -> Result<&'static str, String> {
+    let names = COMMANDS.iter().chain(PROMPT_COMMANDS).map(|(name, _, _)| *name);
+    if let Some(name) = names.clone().find(|name| *name == word) {
+        return Ok(name);
+    }
+    if let Some((_, name)) = ALIASES.iter().find(|(alias, _)| *alias == word) {
+        return Ok(name);
+    }
+    let matching: Vec<&str> = names.filter(|name| name.starts_with(word)).collect();
+    match matching[..] {
+        [name] => Ok(name),
+        [] => Err(format!("unknown command `{}`, type `help` for the list of commands", word)),
+        _ => Err(format!("`{}` could be {}", word, or_list(&matching))),
+    }
+}
+
+// "a, b or c"
+fn or_list(names: &[&str]) -> String {
+    match names.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+fn help(topic: Option<&str>) -> Result<(), String> {
+    let usage = |(name, arguments, description): &(&str, &str, &str)| {
+        let aliases: Vec<&str> = ALIASES.iter().filter(|(_, full)| full == name).map(|(alias, _)| *alias).collect();
+        let aliases = if aliases.is_empty() { String::new() } else { format!(" (also {})", aliases.join(", ")) };
+        format!("  {:<24} {}{}", format!("{} {}", name, arguments).trim_end(), description, aliases)
+    };
+    match topic {
+        Some(word) => {
+            let name = resolve(word)?;
+            let entry = COMMANDS.iter().chain(PROMPT_COMMANDS).find(|(full, _, _)| *full == name).unwrap();
+            println!("{}", usage(entry));
+        },
+        None => {
+            println!("Commands, separate values with spaces:");
+            for entry in COMMANDS {
+                println!("{}", usage(entry));
+            }
+            for entry in PROMPT_COMMANDS {
+                println!("{}", usage(entry));
+            }
+            println!("Any prefix that only one command starts with works too, e.g. `ins 1 2` or `hei`.");
+        },
+    }
+    Ok(())
+}
+
+// Run the prompt on a new tree until `quit` or the end of the input. `label` is the prompt,
+// `history` the file the entered lines are kept in, None keeps them for this session only.
+pub fn run<T, Tree>(label: &str, history: Option<PathBuf>) -> i32
+where
+    T: Clone + Display + FromStr,
+    Tree: CommandTree<T>,
+{
+    let mut history = match history {
+        Some(path) => History::load(path),
+        None => History { path: None, lines: Vec::new() },
+    };
+    let mut tree = Tree::new();
+    println!("Type `help` for the commands and `quit` to leave.");
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    loop {
+        print!("{}> ", label);
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) => {
+                println!();
+                break;
+            },
+            Ok(_) => {},
+            Err(error) => {
+                println!("error: cannot read the input: {}", error);
+                return crate::cli::EXIT_FAILED;
+            },
+        }
+        let mut line = line.trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('!') {
+            match history.recall(&line) {
+                Ok(recalled) => {
+                    println!("{}", recalled);
+                    line = recalled;
+                },
+                Err(error) => {
+                    println!("error: {}", error);
+                    continue;
+                },
+            }
+        }
+        history.add(&line);
+
+        let mut words = line.split_whitespace();
+        let word = words.next().unwrap();
+        let arguments: Vec<&str> = words.collect();
+        let name = match resolve(word) {
+            Ok(name) => name,
+            Err(error) => {
+                println!("error: {}", error);
+                continue;
+            },
+        };
+        let result = match name {
+            "quit" => break,
+            "help" if arguments.len() > 1 => Err("`help` takes at most one command".to_string()),
+            "help" => help(arguments.first().copied()),
+            "history" => match arguments[..] {
+                [] => {
+                    history.show(20);
+                    Ok(())
+                },
+                [count] => count.parse().map(|count| history.show(count))
+                    .map_err(|_| format!("`{}` is not a number of lines", count)),
+                _ => Err("`history` takes at most one number".to_string()),
+            },
+            _ => commands::parse_command::<T>(&format!("{} {}", name, arguments.join(" ")))
+                .and_then(|command| commands::run(&mut tree, &command))
+                .map(|outcome| {
+                    for line in outcome.describe() {
+                        println!("{}", line);
+                    }
+                }),
+        };
+        if let Err(error) = result {
+            println!("error: {}", error);
+        }
+    }
+    crate::cli::EXIT_OK
+}
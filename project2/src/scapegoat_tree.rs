@@ -0,0 +1,315 @@
+use std::cmp::{max, Ordering};
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+
+type ScapegoatLink<T> = Option<Box<ScapegoatNode<T>>>;
+
+#[derive(Debug)]
+struct ScapegoatNode<T> {
+    key: T,
+    left: ScapegoatLink<T>,
+    right: ScapegoatLink<T>,
+}
+
+// outcome of an insert below some node
+enum Inserted {
+    Duplicate,
+    Done,
+    // the new node is too deep and no scapegoat was found yet, carries the subtree size
+    Pending(usize),
+}
+
+// Scapegoat tree: no balance data in the nodes at all. When an insert lands deeper than
+// log(n) / log(1 / alpha), the first ancestor whose child is heavier than `alpha` times its
+// own size is rebuilt into a perfectly balanced subtree. Deletes rebuild the whole tree once
+// enough of it is gone.
+#[derive(Debug)]
+pub struct ScapegoatTree<T> {
+    root: ScapegoatLink<T>,
+    len: usize,
+    // largest `len` since the last full rebuild
+    max_len: usize,
+    alpha: f64,
+}
+
+impl<T: PartialOrd + Clone + Debug> ScapegoatTree<T> {
+    pub fn new() -> Self {
+        Self::with_alpha(0.7)
+    }
+
+    // `alpha` between 0.5 (strict balance, frequent rebuilds) and 1 (lazy balance)
+    pub fn with_alpha(alpha: f64) -> Self {
+        assert!(alpha > 0.5 && alpha < 1.0, "alpha has to lie strictly between 0.5 and 1");
+        ScapegoatTree { root: None, len: 0, max_len: 0, alpha }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.partial_cmp(&node.key) {
+                Some(Ordering::Less) => current = &node.left,
+                Some(Ordering::Greater) => current = &node.right,
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let max_depth = self.depth_limit(self.len + 1);
+        match Self::insert_node(&mut self.root, value, 0, max_depth, self.alpha) {
+            Inserted::Duplicate => false,
+            _ => {
+                self.len += 1;
+                self.max_len = max(self.max_len, self.len);
+                true
+            },
+        }
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        if !Self::delete_node(&mut self.root, value) {
+            return false;
+        }
+        self.len -= 1;
+        if (self.len as f64) < self.alpha * self.max_len as f64 {
+            Self::rebuild(&mut self.root, self.len);
+            self.max_len = self.len;
+        }
+        true
+    }
+
+    pub fn height(&self) -> usize {
+        fn height<T>(node: &ScapegoatLink<T>) -> usize {
+            node.as_ref().map_or(0, |n| 1 + max(height(&n.left), height(&n.right)))
+        }
+        height(&self.root)
+    }
+
+    pub fn leaves(&self) -> usize {
+        fn leaves<T>(node: &ScapegoatLink<T>) -> usize {
+            match node {
+                None => 0,
+                Some(n) if n.left.is_none() && n.right.is_none() => 1,
+                Some(n) => leaves(&n.left) + leaves(&n.right),
+            }
+        }
+        leaves(&self.root)
+    }
+
+    // keys in order
+    pub fn to_vec(&self) -> Vec<T> {
+        fn collect<T: Clone>(node: &ScapegoatLink<T>, out: &mut Vec<T>) {
+            if let Some(n) = node {
+                collect(&n.left, out);
+                out.push(n.key.clone());
+                collect(&n.right, out);
+            }
+        }
+        let mut out = Vec::with_capacity(self.len);
+        collect(&self.root, &mut out);
+        out
+    }
+
+    pub fn print_struct(&self) {
+        fn print<T: Debug>(node: &ScapegoatLink<T>, depth: usize, position: &str) {
+            if let Some(n) = node {
+                println!("{}{}{:?}", " ".repeat(depth * 2), position, n.key);
+                print(&n.left, depth + 1, "L: ");
+                print(&n.right, depth + 1, "R: ");
+            }
+        }
+        println!("Scapegoat Tree Structure:");
+        print(&self.root, 0, "Root: ");
+    }
+
+    // report the first broken property: key order, the length or a node deeper than the
+    // alpha-height bound for the largest size since the last full rebuild
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let limit = self.depth_limit(self.max_len);
+        let count = Self::check_node(&self.root, None, None, 0, limit)?;
+        if count != self.len {
+            return Err(format!("tree holds {} nodes but counts {}", count, self.len));
+        }
+        Ok(())
+    }
+
+    fn check_node(node: &ScapegoatLink<T>, low: Option<&T>, high: Option<&T>, depth: usize, limit: usize) -> Result<usize, String> {
+        let n = match node {
+            Some(n) => n,
+            None => return Ok(0),
+        };
+        if low.is_some_and(|low| n.key <= *low) || high.is_some_and(|high| n.key >= *high) {
+            return Err(format!("key {:?} is out of order", n.key));
+        }
+        if depth > limit {
+            return Err(format!("key {:?} sits at depth {}, more than {}", n.key, depth, limit));
+        }
+        let left = Self::check_node(&n.left, low, Some(&n.key), depth + 1, limit)?;
+        let right = Self::check_node(&n.right, Some(&n.key), high, depth + 1, limit)?;
+        Ok(left + right + 1)
+    }
+
+    // deepest allowed depth (root at 0) for a tree of `len` nodes
+    fn depth_limit(&self, len: usize) -> usize {
+        if len <= 1 {
+            return 0;
+        }
+        ((len as f64).ln() / (1.0 / self.alpha).ln()).floor() as usize
+    }
+
+    fn size(node: &ScapegoatLink<T>) -> usize {
+        node.as_ref().map_or(0, |n| 1 + Self::size(&n.left) + Self::size(&n.right))
+    }
+
+    fn insert_node(node: &mut ScapegoatLink<T>, value: T, depth: usize, max_depth: usize, alpha: f64) -> Inserted {
+        let n = match node {
+            Some(n) => n,
+            None => {
+                *node = Some(Box::new(ScapegoatNode { key: value, left: None, right: None }));
+                return if depth > max_depth { Inserted::Pending(1) } else { Inserted::Done };
+            },
+        };
+        let (child, other) = match value.partial_cmp(&n.key) {
+            Some(Ordering::Less) => (&mut n.left, &n.right),
+            Some(Ordering::Greater) => (&mut n.right, &n.left),
+            _ => return Inserted::Duplicate,
+        };
+        match Self::insert_node(child, value, depth + 1, max_depth, alpha) {
+            Inserted::Pending(child_size) => {
+                let size = 1 + child_size + Self::size(other);
+                if child_size as f64 > alpha * size as f64 {
+                    // found the scapegoat
+                    Self::rebuild(node, size);
+                    Inserted::Done
+                } else {
+                    Inserted::Pending(size)
+                }
+            },
+            inserted => inserted,
+        }
+    }
+
+    fn delete_node(node: &mut ScapegoatLink<T>, value: &T) -> bool {
+        let n = match node {
+            Some(n) => n,
+            None => return false,
+        };
+        match value.partial_cmp(&n.key) {
+            Some(Ordering::Less) => Self::delete_node(&mut n.left, value),
+            Some(Ordering::Greater) => Self::delete_node(&mut n.right, value),
+            _ => {
+                match (n.left.take(), n.right.take()) {
+                    (None, right) => *node = right,
+                    (left, None) => *node = left,
+                    (left, Some(right)) => {
+                        // replace the key with its in-order successor
+                        let (successor, rest) = Self::take_min(right);
+                        n.key = successor;
+                        n.left = left;
+                        n.right = rest;
+                    },
+                }
+                true
+            },
+        }
+    }
+
+    // smallest key of a subtree and the subtree without it
+    fn take_min(mut node: Box<ScapegoatNode<T>>) -> (T, ScapegoatLink<T>) {
+        match node.left.take() {
+            None => (node.key, node.right.take()),
+            Some(left) => {
+                let (min, rest) = Self::take_min(left);
+                node.left = rest;
+                (min, Some(node))
+            },
+        }
+    }
+
+    // replace a subtree of `size` nodes by a perfectly balanced one with the same keys
+    fn rebuild(node: &mut ScapegoatLink<T>, size: usize) {
+        let mut keys = Vec::with_capacity(size);
+        Self::flatten(node.take(), &mut keys);
+        *node = Self::build(&mut keys.into_iter(), size);
+    }
+
+    fn flatten(node: ScapegoatLink<T>, keys: &mut Vec<T>) {
+        if let Some(n) = node {
+            let n = *n;
+            Self::flatten(n.left, keys);
+            keys.push(n.key);
+            Self::flatten(n.right, keys);
+        }
+    }
+
+    fn build(keys: &mut impl Iterator<Item = T>, size: usize) -> ScapegoatLink<T> {
+        if size == 0 {
+            return None;
+        }
+        let left = Self::build(keys, size / 2);
+        let key = keys.next().unwrap();
+        let right = Self::build(keys, size - size / 2 - 1);
+        Some(Box::new(ScapegoatNode { key, left, right }))
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> Default for ScapegoatTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> OrderedSet<T> for ScapegoatTree<T> {
+    fn new() -> Self {
+        ScapegoatTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        ScapegoatTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        ScapegoatTree::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        ScapegoatTree::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        ScapegoatTree::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        ScapegoatTree::is_empty(self)
+    }
+
+    fn height(&self) -> usize {
+        ScapegoatTree::height(self)
+    }
+
+    fn leaves(&self) -> usize {
+        ScapegoatTree::leaves(self)
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        ScapegoatTree::to_vec(self)
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        ScapegoatTree::check_invariants(self)
+    }
+}
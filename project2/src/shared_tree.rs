@@ -0,0 +1,57 @@
+use std::sync::RwLock;
+use crate::persistent_tree::PersistentAVLTree;
+
+// Ordered set that can be shared between threads (`Send + Sync` for `T: Send + Sync`).
+// Writers build the next version of a persistent AVL tree under the write lock and publish it,
+// readers take an O(1) snapshot and then read it without holding any lock, so a long scan never
+// blocks a writer and always sees one consistent version.
+pub struct SharedTree<T> {
+    current: RwLock<PersistentAVLTree<T>>,
+}
+
+impl<T: PartialOrd + Clone> SharedTree<T> {
+    pub fn new() -> Self {
+        SharedTree { current: RwLock::new(PersistentAVLTree::new()) }
+    }
+
+    // the latest published version, later writes do not show up in it
+    pub fn snapshot(&self) -> PersistentAVLTree<T> {
+        self.current.read().unwrap().clone()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.snapshot().contains(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.current.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // returns false if the value was already there
+    pub fn insert(&self, value: T) -> bool {
+        let mut current = self.current.write().unwrap();
+        let next = current.insert(value);
+        let inserted = next.len() != current.len();
+        *current = next;
+        inserted
+    }
+
+    // returns false if the value was not there
+    pub fn remove(&self, value: &T) -> bool {
+        let mut current = self.current.write().unwrap();
+        let next = current.remove(value);
+        let removed = next.len() != current.len();
+        *current = next;
+        removed
+    }
+}
+
+impl<T: PartialOrd + Clone> Default for SharedTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
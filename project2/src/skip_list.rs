@@ -0,0 +1,250 @@
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+use crate::rng::XorShift64;
+
+const MAX_LEVEL: usize = 32;
+const HEAD: usize = 0;
+
+#[derive(Debug)]
+struct SkipNode<T> {
+    // None only for the head and for free slots
+    key: Option<T>,
+    // successor on every level this node takes part in
+    next: Vec<Option<usize>>,
+}
+
+// Probabilistic skip list (Pugh). Every element sits on level 0 and on each further level
+// with probability 1/2, so a search skips ahead on the sparse upper lists and only walks
+// short stretches below. Nodes live in one Vec and link by index; freed slots are reused.
+// The coin flips come from a seedable generator so benchmarks can be repeated exactly.
+#[derive(Debug)]
+pub struct SkipList<T> {
+    nodes: Vec<SkipNode<T>>,
+    free: Vec<usize>,
+    // number of levels in use, at least 1
+    level: usize,
+    len: usize,
+    rng: XorShift64,
+}
+
+impl<T: PartialOrd + Clone + Debug> SkipList<T> {
+    pub fn new() -> Self {
+        Self::with_seed(0x5EED)
+    }
+
+    // the seed fixes the tower heights, the same seed and operations give the same lists
+    pub fn with_seed(seed: u64) -> Self {
+        let head = SkipNode { key: None, next: vec![None; MAX_LEVEL] };
+        SkipList { nodes: vec![head], free: Vec::new(), level: 1, len: 0, rng: XorShift64::new(seed) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = HEAD;
+        for level in (0..self.level).rev() {
+            current = self.advance(current, level, value);
+        }
+        self.next_key(current, 0).is_some_and(|key| key == value)
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let update = self.predecessors(&value);
+        if self.next_key(update[0], 0).is_some_and(|key| *key == value) {
+            return false;
+        }
+        let height = self.random_height();
+        self.level = self.level.max(height);
+        let next = (0..height).map(|level| self.nodes[update[level]].next[level]).collect();
+        let node = SkipNode { key: Some(value), next };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            },
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            },
+        };
+        for (level, &before) in update.iter().enumerate().take(height) {
+            self.nodes[before].next[level] = Some(index);
+        }
+        self.len += 1;
+        true
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        let update = self.predecessors(value);
+        let target = match self.nodes[update[0]].next[0] {
+            Some(target) if self.nodes[target].key.as_ref() == Some(value) => target,
+            _ => return false,
+        };
+        let height = self.nodes[target].next.len();
+        for (level, &before) in update.iter().enumerate().take(height) {
+            self.nodes[before].next[level] = self.nodes[target].next[level];
+        }
+        self.nodes[target] = SkipNode { key: None, next: Vec::new() };
+        self.free.push(target);
+        while self.level > 1 && self.nodes[HEAD].next[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        true
+    }
+
+    // number of levels in use
+    pub fn height(&self) -> usize {
+        if self.len == 0 { 0 } else { self.level }
+    }
+
+    // elements that only take part in the bottom list
+    pub fn leaves(&self) -> usize {
+        self.bottom().filter(|&index| self.nodes[index].next.len() == 1).count()
+    }
+
+    // keys in order
+    pub fn to_vec(&self) -> Vec<T> {
+        self.bottom().map(|index| self.key(index).clone()).collect()
+    }
+
+    pub fn print_struct(&self) {
+        println!("Skip List Structure:");
+        for level in (0..self.height()).rev() {
+            let mut keys = Vec::new();
+            let mut current = self.nodes[HEAD].next[level];
+            while let Some(index) = current {
+                keys.push(self.key(index));
+                current = self.nodes[index].next[level];
+            }
+            println!("Level {}: {:?}", level, keys);
+        }
+    }
+
+    // Report the first broken property: every level sorted, every upper level made of nodes
+    // that are on all the levels below, no links above the levels in use and the length.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.nodes[HEAD].next[self.level..].iter().any(|next| next.is_some()) {
+            return Err(format!("the head links above the {} levels in use", self.level));
+        }
+        for level in 0..self.level {
+            let mut previous: Option<&T> = None;
+            let mut current = self.nodes[HEAD].next[level];
+            while let Some(index) = current {
+                let node = &self.nodes[index];
+                let key = match &node.key {
+                    Some(key) => key,
+                    None => return Err(format!("level {} links to the free slot {}", level, index)),
+                };
+                if node.next.len() <= level {
+                    return Err(format!("key {:?} is linked on level {} above its tower", key, level));
+                }
+                if previous.is_some_and(|previous| previous >= key) {
+                    return Err(format!("key {:?} is out of order on level {}", key, level));
+                }
+                previous = Some(key);
+                current = node.next[level];
+            }
+        }
+        let count = self.bottom().count();
+        if count != self.len {
+            return Err(format!("bottom list holds {} keys but counts {}", count, self.len));
+        }
+        Ok(())
+    }
+
+    fn key(&self, index: usize) -> &T {
+        self.nodes[index].key.as_ref().expect("linked nodes always carry a key")
+    }
+
+    fn next_key(&self, index: usize, level: usize) -> Option<&T> {
+        self.nodes[index].next[level].map(|next| self.key(next))
+    }
+
+    // last node on `level` from `current` on whose key is smaller than `value`
+    fn advance(&self, mut current: usize, level: usize, value: &T) -> usize {
+        while let Some(next) = self.nodes[current].next[level] {
+            if self.key(next) < value {
+                current = next;
+            } else {
+                break;
+            }
+        }
+        current
+    }
+
+    // on every level the last node before `value`, the head above the levels in use
+    fn predecessors(&self, value: &T) -> [usize; MAX_LEVEL] {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut current = HEAD;
+        for level in (0..self.level).rev() {
+            current = self.advance(current, level, value);
+            update[level] = current;
+        }
+        update
+    }
+
+    // tower height, each further level is taken with probability 1/2
+    fn random_height(&mut self) -> usize {
+        (1 + self.rng.next_u64().trailing_ones() as usize).min(MAX_LEVEL)
+    }
+
+    // node indices along the bottom list
+    fn bottom(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.nodes[HEAD].next[0], move |&index| self.nodes[index].next[0])
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> Default for SkipList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> OrderedSet<T> for SkipList<T> {
+    fn new() -> Self {
+        SkipList::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        SkipList::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        SkipList::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        SkipList::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn height(&self) -> usize {
+        SkipList::height(self)
+    }
+
+    fn leaves(&self) -> usize {
+        SkipList::leaves(self)
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        SkipList::to_vec(self)
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        SkipList::check_invariants(self)
+    }
+}
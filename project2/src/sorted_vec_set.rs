@@ -0,0 +1,111 @@
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+
+// Sorted Vec with binary search, the reference point for the trees: lookups are as cache
+// friendly as it gets, while every insert or delete in the middle shifts the tail, O(n).
+#[derive(Debug, Clone)]
+pub struct SortedVecSet<T> {
+    values: Vec<T>,
+}
+
+impl<T: PartialOrd + Clone + Debug> SortedVecSet<T> {
+    pub fn new() -> Self {
+        SortedVecSet { values: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.find(value).1
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let (index, found) = self.find(&value);
+        if !found {
+            self.values.insert(index, value);
+        }
+        !found
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        let (index, found) = self.find(value);
+        if found {
+            self.values.remove(index);
+        }
+        found
+    }
+
+    // values in order
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    // report the first pair of neighbours that is not strictly increasing
+    pub fn check_invariants(&self) -> Result<(), String> {
+        match self.values.windows(2).find(|pair| pair[0] >= pair[1]) {
+            Some(pair) => Err(format!("value {:?} is out of order", pair[1])),
+            None => Ok(()),
+        }
+    }
+
+    // index of the first value not smaller than `value`, and whether it is `value` itself
+    fn find(&self, value: &T) -> (usize, bool) {
+        let index = self.values.partition_point(|v| v < value);
+        (index, self.values.get(index).is_some_and(|v| v == value))
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> Default for SortedVecSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A flat Vec has no tree shape: it counts as a single level whose every element is a leaf.
+impl<T: PartialOrd + Clone + Debug> OrderedSet<T> for SortedVecSet<T> {
+    fn new() -> Self {
+        SortedVecSet::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        SortedVecSet::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        SortedVecSet::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        SortedVecSet::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn height(&self) -> usize {
+        if self.values.is_empty() { 0 } else { 1 }
+    }
+
+    fn leaves(&self) -> usize {
+        self.values.len()
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        self.values.clone()
+    }
+
+    fn print_structure(&self) {
+        println!("Sorted Vec: {:?}", self.values);
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        SortedVecSet::check_invariants(self)
+    }
+}
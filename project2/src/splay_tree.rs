@@ -0,0 +1,316 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+
+type SplayLink<T> = Option<Box<SplayNode<T>>>;
+
+#[derive(Debug)]
+struct SplayNode<T> {
+    key: T,
+    left: SplayLink<T>,
+    right: SplayLink<T>,
+}
+
+// Self-adjusting splay tree. Every access moves the key it looked for to the root, which
+// gives amortized O(log n) operations and makes recently used keys cheap to reach again.
+// The tree can degenerate into a long path, so every walk over it is iterative.
+// Lookups restructure the tree too, the root sits in a RefCell so that `contains` can take
+// `&self` like in the other sets.
+#[derive(Debug)]
+pub struct SplayTree<T> {
+    root: RefCell<SplayLink<T>>,
+    len: usize,
+}
+
+impl<T: PartialOrd + Clone + Debug> SplayTree<T> {
+    pub fn new() -> Self {
+        SplayTree { root: RefCell::new(None), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // splays the closest key to the root
+    pub fn contains(&self, value: &T) -> bool {
+        let mut root = self.root.borrow_mut();
+        match root.take() {
+            None => false,
+            Some(node) => {
+                let node = Self::splay(node, value);
+                let found = node.key == *value;
+                *root = Some(node);
+                found
+            },
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let root = match self.root.get_mut().take() {
+            None => {
+                *self.root.get_mut() = Some(Box::new(SplayNode { key: value, left: None, right: None }));
+                self.len += 1;
+                return true;
+            },
+            Some(root) => Self::splay(root, &value),
+        };
+        // the splayed root is the neighbour of `value`, the new node takes its place
+        let mut root = root;
+        let new_root = match value.partial_cmp(&root.key) {
+            Some(Ordering::Less) => {
+                let left = root.left.take();
+                SplayNode { key: value, left, right: Some(root) }
+            },
+            Some(Ordering::Greater) => {
+                let right = root.right.take();
+                SplayNode { key: value, left: Some(root), right }
+            },
+            _ => {
+                *self.root.get_mut() = Some(root);
+                return false;
+            },
+        };
+        *self.root.get_mut() = Some(Box::new(new_root));
+        self.len += 1;
+        true
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        let mut root = match self.root.get_mut().take() {
+            None => return false,
+            Some(root) => Self::splay(root, value),
+        };
+        if root.key != *value {
+            *self.root.get_mut() = Some(root);
+            return false;
+        }
+        // every key on the left is smaller, so splaying for `value` there lifts its maximum,
+        // which has no right child and can adopt the right subtree
+        let new_root = match root.left.take() {
+            None => root.right.take(),
+            Some(left) => {
+                let mut left = Self::splay(left, value);
+                left.right = root.right.take();
+                Some(left)
+            },
+        };
+        *self.root.get_mut() = new_root;
+        self.len -= 1;
+        true
+    }
+
+    pub fn height(&self) -> usize {
+        let root = self.root.borrow();
+        let mut height = 0;
+        let mut stack: Vec<(&SplayNode<T>, usize)> = root.iter().map(|n| (n.as_ref(), 1)).collect();
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                stack.push((child, depth + 1));
+            }
+        }
+        height
+    }
+
+    pub fn leaves(&self) -> usize {
+        let root = self.root.borrow();
+        let mut leaves = 0;
+        let mut stack: Vec<&SplayNode<T>> = root.iter().map(|n| n.as_ref()).collect();
+        while let Some(node) = stack.pop() {
+            if node.left.is_none() && node.right.is_none() {
+                leaves += 1;
+            }
+            stack.extend([&node.left, &node.right].into_iter().flatten().map(|n| n.as_ref()));
+        }
+        leaves
+    }
+
+    // keys in order
+    pub fn to_vec(&self) -> Vec<T> {
+        let root = self.root.borrow();
+        let mut out = Vec::with_capacity(self.len);
+        let mut stack: Vec<&SplayNode<T>> = Vec::new();
+        let mut current = root.as_deref();
+        loop {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_deref();
+            }
+            match stack.pop() {
+                Some(node) => {
+                    out.push(node.key.clone());
+                    current = node.right.as_deref();
+                },
+                None => break,
+            }
+        }
+        out
+    }
+
+    pub fn print_struct(&self) {
+        let root = self.root.borrow();
+        println!("Splay Tree Structure:");
+        let mut stack: Vec<(&SplayNode<T>, usize, &str)> = root.iter().map(|n| (n.as_ref(), 0, "Root: ")).collect();
+        while let Some((node, depth, position)) = stack.pop() {
+            println!("{}{}{:?}", " ".repeat(depth * 2), position, node.key);
+            if let Some(right) = &node.right {
+                stack.push((right, depth + 1, "R: "));
+            }
+            if let Some(left) = &node.left {
+                stack.push((left, depth + 1, "L: "));
+            }
+        }
+    }
+
+    // a splay tree has no shape invariant, only key order and the length can break
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let keys = self.to_vec();
+        if let Some(pair) = keys.windows(2).find(|pair| pair[0] >= pair[1]) {
+            return Err(format!("key {:?} is out of order", pair[1]));
+        }
+        if keys.len() != self.len {
+            return Err(format!("tree holds {} nodes but counts {}", keys.len(), self.len));
+        }
+        Ok(())
+    }
+
+    // Top-down splay: walk towards `value`, hanging the nodes passed on the way into a left tree
+    // (smaller keys) and a right tree (larger keys), rotating on zig-zig steps. The last node
+    // reached becomes the root and the two side trees are reattached below it.
+    fn splay(mut t: Box<SplayNode<T>>, value: &T) -> Box<SplayNode<T>> {
+        // nodes of the left tree, each one becomes the right child of the one before it
+        let mut smaller: Vec<Box<SplayNode<T>>> = Vec::new();
+        // nodes of the right tree, each one becomes the left child of the one before it
+        let mut larger: Vec<Box<SplayNode<T>>> = Vec::new();
+        loop {
+            match value.partial_cmp(&t.key) {
+                Some(Ordering::Less) => {
+                    let mut l = match t.left.take() {
+                        Some(l) => l,
+                        None => break,
+                    };
+                    if *value < l.key {
+                        // zig-zig, rotate right first
+                        t.left = l.right.take();
+                        l.right = Some(t);
+                        t = l;
+                        match t.left.take() {
+                            Some(next) => {
+                                larger.push(t);
+                                t = next;
+                            },
+                            None => break,
+                        }
+                    } else {
+                        larger.push(t);
+                        t = l;
+                    }
+                },
+                Some(Ordering::Greater) => {
+                    let mut r = match t.right.take() {
+                        Some(r) => r,
+                        None => break,
+                    };
+                    if *value > r.key {
+                        // zag-zag, rotate left first
+                        t.right = r.left.take();
+                        r.left = Some(t);
+                        t = r;
+                        match t.right.take() {
+                            Some(next) => {
+                                smaller.push(t);
+                                t = next;
+                            },
+                            None => break,
+                        }
+                    } else {
+                        smaller.push(t);
+                        t = r;
+                    }
+                },
+                _ => break,
+            }
+        }
+        let mut left = t.left.take();
+        for mut node in smaller.into_iter().rev() {
+            node.right = left;
+            left = Some(node);
+        }
+        let mut right = t.right.take();
+        for mut node in larger.into_iter().rev() {
+            node.left = right;
+            right = Some(node);
+        }
+        t.left = left;
+        t.right = right;
+        t
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> Default for SplayTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the default drop recurses once per level, which a degenerate splay tree cannot afford
+impl<T> Drop for SplayTree<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<SplayNode<T>>> = self.root.get_mut().take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> OrderedSet<T> for SplayTree<T> {
+    fn new() -> Self {
+        SplayTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        SplayTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        SplayTree::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        SplayTree::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        SplayTree::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        SplayTree::is_empty(self)
+    }
+
+    fn height(&self) -> usize {
+        SplayTree::height(self)
+    }
+
+    fn leaves(&self) -> usize {
+        SplayTree::leaves(self)
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        SplayTree::to_vec(self)
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        SplayTree::check_invariants(self)
+    }
+}
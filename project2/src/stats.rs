@@ -0,0 +1,96 @@
+use std::cell::Cell;
+use std::fmt;
+
+// Work done by a tree since its counters were last reset, to compare how AVL and red-black
+// trees pay for their balance: rotations on one side, recolorings on the other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    // inserts, deletes and lookups
+    pub operations: u64,
+    pub single_rotations: u64,
+    // a double rotation counts once here, not as two single ones
+    pub double_rotations: u64,
+    // color changes of red-black nodes, always 0 for AVL
+    pub recolors: u64,
+    // key comparisons made while searching for a position
+    pub comparisons: u64,
+    pub nodes_visited: u64,
+}
+
+impl TreeStats {
+    // rotations of either kind
+    pub fn rotations(&self) -> u64 {
+        self.single_rotations + self.double_rotations
+    }
+
+    pub fn comparisons_per_operation(&self) -> f64 {
+        Self::per_operation(self.comparisons, self.operations)
+    }
+
+    pub fn visits_per_operation(&self) -> f64 {
+        Self::per_operation(self.nodes_visited, self.operations)
+    }
+
+    fn per_operation(total: u64, operations: u64) -> f64 {
+        if operations == 0 { 0.0 } else { total as f64 / operations as f64 }
+    }
+}
+
+impl fmt::Display for TreeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "operations:       {}", self.operations)?;
+        writeln!(f, "single rotations: {}", self.single_rotations)?;
+        writeln!(f, "double rotations: {}", self.double_rotations)?;
+        writeln!(f, "recolors:         {}", self.recolors)?;
+        writeln!(f, "comparisons:      {} ({:.2} per operation)", self.comparisons, self.comparisons_per_operation())?;
+        write!(f, "nodes visited:    {} ({:.2} per operation)", self.nodes_visited, self.visits_per_operation())
+    }
+}
+
+// Counters a tree carries around. They are off by default so the trees pay one branch per
+// event when nobody is looking, and they sit in Cells so lookups through &self can count too.
+#[derive(Clone, Debug, Default)]
+pub struct StatsCounter {
+    enabled: Cell<bool>,
+    stats: Cell<TreeStats>,
+}
+
+impl StatsCounter {
+    pub fn enable(&self) {
+        self.enabled.set(true);
+    }
+
+    pub fn disable(&self) {
+        self.enabled.set(false);
+    }
+
+    pub fn get(&self) -> TreeStats {
+        self.stats.get()
+    }
+
+    pub fn reset(&self) {
+        self.stats.set(TreeStats::default());
+    }
+
+    // count one key comparison and pass its outcome through, for use inside conditions
+    pub fn compare(&self, outcome: bool) -> bool {
+        self.record(|s| s.comparisons += 1);
+        outcome
+    }
+
+    // apply `update` to the counters, nothing happens while they are disabled
+    pub fn record(&self, update: impl FnOnce(&mut TreeStats)) {
+        if self.enabled.get() {
+            let mut stats = self.stats.get();
+            update(&mut stats);
+            self.stats.set(stats);
+        }
+    }
+}
+
+// the counters are bookkeeping, two trees are equal when they hold the same nodes
+impl PartialEq for StatsCounter {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
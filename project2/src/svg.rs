@@ -0,0 +1,166 @@
+use std::fmt::Write;
+
+// vertical distance between two levels
+const LEVEL_HEIGHT: f64 = 70.0;
+// smallest horizontal gap between two nodes on the same level
+const NODE_GAP: f64 = 16.0;
+const MARGIN: f64 = 24.0;
+const MIN_RADIUS: f64 = 18.0;
+const FONT_SIZE: f64 = 14.0;
+const DETAIL_FONT_SIZE: f64 = 10.0;
+
+// What the renderer needs to know about a node. The trees convert themselves into this shape
+// so the layout below does not depend on how they store their nodes.
+pub struct SvgNode {
+    pub label: String,
+    // small second line drawn under the node, e.g. the height and balance factor
+    pub detail: Option<String>,
+    pub fill: &'static str,
+    pub text_color: &'static str,
+    pub left: Option<Box<SvgNode>>,
+    pub right: Option<Box<SvgNode>>,
+}
+
+impl SvgNode {
+    fn radius(&self) -> f64 {
+        MIN_RADIUS.max(self.label.chars().count() as f64 * FONT_SIZE * 0.3 + 8.0)
+    }
+
+    // half of the horizontal room the node takes, the detail line may be wider than the circle
+    fn half_width(&self) -> f64 {
+        let detail = self.detail.as_ref().map_or(0.0, |d| d.chars().count() as f64 * DETAIL_FONT_SIZE * 0.3);
+        self.radius().max(detail)
+    }
+}
+
+// leftmost and rightmost extent of a subtree on every level, relative to its root
+struct Contour {
+    left: Vec<f64>,
+    right: Vec<f64>,
+}
+
+impl Contour {
+    // add a child contour moved by `offset` one level below the current ones
+    fn merge_below(&mut self, child: &Contour, offset: f64) {
+        for (depth, (&left, &right)) in child.left.iter().zip(&child.right).enumerate() {
+            let level = depth + 1;
+            if level < self.left.len() {
+                self.left[level] = self.left[level].min(left + offset);
+                self.right[level] = self.right[level].max(right + offset);
+            } else {
+                self.left.push(left + offset);
+                self.right.push(right + offset);
+            }
+        }
+    }
+}
+
+// a node with the offsets of its children relative to it
+struct Placed<'a> {
+    node: &'a SvgNode,
+    left: Option<(f64, Box<Placed<'a>>)>,
+    right: Option<(f64, Box<Placed<'a>>)>,
+}
+
+// Reingold–Tilford: lay out both subtrees on their own, then push them apart until their
+// facing contours keep NODE_GAP on every level they share, and center the parent above them.
+// A single child sits half a step to its side so the tree still reads as binary.
+fn place(node: &SvgNode) -> (Placed<'_>, Contour) {
+    let left = node.left.as_deref().map(place);
+    let right = node.right.as_deref().map(place);
+    let half = node.half_width();
+    let mut contour = Contour { left: vec![-half], right: vec![half] };
+
+    let (left_offset, right_offset) = match (&left, &right) {
+        (Some((_, left)), Some((_, right))) => {
+            let overlap = left.right.iter().zip(&right.left)
+                .map(|(l, r)| l - r)
+                .fold(f64::MIN, f64::max);
+            let separation = (overlap + NODE_GAP).max(2.0 * MIN_RADIUS);
+            (-separation / 2.0, separation / 2.0)
+        },
+        (Some((_, left)), None) => (-(left.right[0] + NODE_GAP / 2.0).max(MIN_RADIUS), 0.0),
+        (None, Some((_, right))) => (0.0, (-right.left[0] + NODE_GAP / 2.0).max(MIN_RADIUS)),
+        (None, None) => (0.0, 0.0),
+    };
+
+    let left = left.map(|(placed, child)| {
+        contour.merge_below(&child, left_offset);
+        (left_offset, Box::new(placed))
+    });
+    let right = right.map(|(placed, child)| {
+        contour.merge_below(&child, right_offset);
+        (right_offset, Box::new(placed))
+    });
+    (Placed { node, left, right }, contour)
+}
+
+// absolute positions of the nodes and the edges between them
+fn collect<'a>(placed: &Placed<'a>, x: f64, depth: usize, nodes: &mut Vec<(f64, usize, &'a SvgNode)>, edges: &mut Vec<(f64, usize, f64)>) {
+    nodes.push((x, depth, placed.node));
+    for (offset, child) in [&placed.left, &placed.right].into_iter().flatten() {
+        edges.push((x, depth, x + offset));
+        collect(child, x + offset, depth + 1, nodes, edges);
+    }
+}
+
+// Render a binary tree as a self-contained SVG document; None draws an empty tree.
+pub fn render(root: Option<&SvgNode>, title: &str) -> String {
+    let mut out = String::new();
+    let (placed, contour) = match root {
+        Some(root) => place(root),
+        None => {
+            let (width, height) = (2.0 * MARGIN + 120.0, 2.0 * MARGIN + FONT_SIZE);
+            header(&mut out, title, width, height);
+            writeln!(out, "  <text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"#555\">empty tree</text>",
+                width / 2.0, height / 2.0, FONT_SIZE).unwrap();
+            out.push_str("</svg>\n");
+            return out;
+        },
+    };
+
+    let min_x = contour.left.iter().cloned().fold(f64::MAX, f64::min);
+    let max_x = contour.right.iter().cloned().fold(f64::MIN, f64::max);
+    let top = MARGIN + MIN_RADIUS.max(placed.node.radius());
+    let width = max_x - min_x + 2.0 * MARGIN;
+    let height = top + (contour.left.len() - 1) as f64 * LEVEL_HEIGHT + MIN_RADIUS + DETAIL_FONT_SIZE + 2.0 * MARGIN;
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    collect(&placed, MARGIN - min_x, 0, &mut nodes, &mut edges);
+    let y = |depth: usize| top + depth as f64 * LEVEL_HEIGHT;
+
+    header(&mut out, title, width, height);
+    out.push_str("  <g stroke=\"#555\" stroke-width=\"1.5\">\n");
+    for (from, depth, to) in edges {
+        writeln!(out, "    <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>", from, y(depth), to, y(depth + 1)).unwrap();
+    }
+    out.push_str("  </g>\n");
+    out.push_str("  <g text-anchor=\"middle\" dominant-baseline=\"central\">\n");
+    for (x, depth, node) in nodes {
+        let radius = node.radius();
+        writeln!(out, "    <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\" stroke=\"#222\" stroke-width=\"1.5\"/>",
+            x, y(depth), radius, node.fill).unwrap();
+        writeln!(out, "    <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{}\" fill=\"{}\">{}</text>",
+            x, y(depth), FONT_SIZE, node.text_color, escape(&node.label)).unwrap();
+        if let Some(detail) = &node.detail {
+            writeln!(out, "    <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{}\" fill=\"#333\">{}</text>",
+                x, y(depth) + radius + DETAIL_FONT_SIZE, DETAIL_FONT_SIZE, escape(detail)).unwrap();
+        }
+    }
+    out.push_str("  </g>\n");
+    out.push_str("</svg>\n");
+    out
+}
+
+fn header(out: &mut String, title: &str, width: f64, height: f64) {
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\" font-family=\"Helvetica, Arial, sans-serif\">",
+        w = width.ceil(), h = height.ceil()).unwrap();
+    writeln!(out, "  <title>{}</title>", escape(title)).unwrap();
+    out.push_str("  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+}
+
+// the characters that would break the markup
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,262 @@
+use std::cmp::{max, Ordering};
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+use crate::rng::XorShift64;
+
+type TreapLink<T> = Option<Box<TreapNode<T>>>;
+
+#[derive(Debug)]
+struct TreapNode<T> {
+    key: T,
+    priority: u64,
+    left: TreapLink<T>,
+    right: TreapLink<T>,
+}
+
+// Treap, a search tree on the keys that is also a max-heap on random priorities. The shape
+// is that of a tree built from a random insertion order, so the expected height is O(log n)
+// whatever order the keys really arrive in.
+#[derive(Debug)]
+pub struct Treap<T> {
+    root: TreapLink<T>,
+    len: usize,
+    rng: XorShift64,
+}
+
+impl<T: PartialOrd + Clone + Debug> Treap<T> {
+    pub fn new() -> Self {
+        Self::with_seed(0x5EED)
+    }
+
+    // the seed fixes the priorities, the same seed and operations give the same shape
+    pub fn with_seed(seed: u64) -> Self {
+        Treap { root: None, len: 0, rng: XorShift64::new(seed) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.partial_cmp(&node.key) {
+                Some(Ordering::Less) => current = &node.left,
+                Some(Ordering::Greater) => current = &node.right,
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+        let priority = self.rng.next_u64();
+        self.root = Some(Self::insert_node(self.root.take(), value, priority));
+        self.len += 1;
+        true
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        self.root = Self::delete_node(self.root.take(), value);
+        self.len -= 1;
+        true
+    }
+
+    pub fn height(&self) -> usize {
+        fn height<T>(node: &TreapLink<T>) -> usize {
+            node.as_ref().map_or(0, |n| 1 + max(height(&n.left), height(&n.right)))
+        }
+        height(&self.root)
+    }
+
+    pub fn leaves(&self) -> usize {
+        fn leaves<T>(node: &TreapLink<T>) -> usize {
+            match node {
+                None => 0,
+                Some(n) if n.left.is_none() && n.right.is_none() => 1,
+                Some(n) => leaves(&n.left) + leaves(&n.right),
+            }
+        }
+        leaves(&self.root)
+    }
+
+    // keys in order
+    pub fn to_vec(&self) -> Vec<T> {
+        fn collect<T: Clone>(node: &TreapLink<T>, out: &mut Vec<T>) {
+            if let Some(n) = node {
+                collect(&n.left, out);
+                out.push(n.key.clone());
+                collect(&n.right, out);
+            }
+        }
+        let mut out = Vec::with_capacity(self.len);
+        collect(&self.root, &mut out);
+        out
+    }
+
+    pub fn print_struct(&self) {
+        fn print<T: Debug>(node: &TreapLink<T>, depth: usize, position: &str) {
+            if let Some(n) = node {
+                println!("{}{}{:?} (Priority: {})", " ".repeat(depth * 2), position, n.key, n.priority);
+                print(&n.left, depth + 1, "L: ");
+                print(&n.right, depth + 1, "R: ");
+            }
+        }
+        println!("Treap Structure:");
+        print(&self.root, 0, "Root: ");
+    }
+
+    // report the first broken property: key order, heap order of the priorities or the length
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let count = Self::check_node(&self.root, None, None, u64::MAX)?;
+        if count != self.len {
+            return Err(format!("treap holds {} nodes but counts {}", count, self.len));
+        }
+        Ok(())
+    }
+
+    // node count of a subtree whose keys lie strictly between `low` and `high` and whose
+    // priorities do not exceed `limit`
+    fn check_node(node: &TreapLink<T>, low: Option<&T>, high: Option<&T>, limit: u64) -> Result<usize, String> {
+        let n = match node {
+            Some(n) => n,
+            None => return Ok(0),
+        };
+        if low.is_some_and(|low| n.key <= *low) || high.is_some_and(|high| n.key >= *high) {
+            return Err(format!("key {:?} is out of order", n.key));
+        }
+        if n.priority > limit {
+            return Err(format!("key {:?} has a higher priority than its parent", n.key));
+        }
+        let left = Self::check_node(&n.left, low, Some(&n.key), n.priority)?;
+        let right = Self::check_node(&n.right, Some(&n.key), high, n.priority)?;
+        Ok(left + right + 1)
+    }
+
+    // plain leaf insertion, then rotate the new node up while it beats its parent's priority
+    fn insert_node(node: TreapLink<T>, value: T, priority: u64) -> Box<TreapNode<T>> {
+        let mut h = match node {
+            Some(h) => h,
+            None => return Box::new(TreapNode { key: value, priority, left: None, right: None }),
+        };
+        if value < h.key {
+            let left = Self::insert_node(h.left.take(), value, priority);
+            let rotate = left.priority > h.priority;
+            h.left = Some(left);
+            if rotate {
+                h = Self::rotate_right(h);
+            }
+        } else {
+            let right = Self::insert_node(h.right.take(), value, priority);
+            let rotate = right.priority > h.priority;
+            h.right = Some(right);
+            if rotate {
+                h = Self::rotate_left(h);
+            }
+        }
+        h
+    }
+
+    fn delete_node(node: TreapLink<T>, value: &T) -> TreapLink<T> {
+        let mut h = node?;
+        match value.partial_cmp(&h.key) {
+            Some(Ordering::Less) => h.left = Self::delete_node(h.left.take(), value),
+            Some(Ordering::Greater) => h.right = Self::delete_node(h.right.take(), value),
+            _ => return Self::merge(h.left.take(), h.right.take()),
+        }
+        Some(h)
+    }
+
+    // merge two treaps where every key of `left` is smaller than every key of `right`
+    fn merge(left: TreapLink<T>, right: TreapLink<T>) -> TreapLink<T> {
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(mut l), Some(mut r)) => {
+                if l.priority > r.priority {
+                    l.right = Self::merge(l.right.take(), Some(r));
+                    Some(l)
+                } else {
+                    r.left = Self::merge(Some(l), r.left.take());
+                    Some(r)
+                }
+            },
+        }
+    }
+
+    fn rotate_left(mut h: Box<TreapNode<T>>) -> Box<TreapNode<T>> {
+        let mut x = h.right.take().unwrap();
+        h.right = x.left.take();
+        x.left = Some(h);
+        x
+    }
+
+    fn rotate_right(mut h: Box<TreapNode<T>>) -> Box<TreapNode<T>> {
+        let mut x = h.left.take().unwrap();
+        h.left = x.right.take();
+        x.right = Some(h);
+        x
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> Default for Treap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> OrderedSet<T> for Treap<T> {
+    fn new() -> Self {
+        Treap::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        Treap::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        Treap::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        Treap::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        Treap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        Treap::is_empty(self)
+    }
+
+    fn height(&self) -> usize {
+        Treap::height(self)
+    }
+
+    fn leaves(&self) -> usize {
+        Treap::leaves(self)
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        Treap::to_vec(self)
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        Treap::check_invariants(self)
+    }
+}
@@ -0,0 +1,391 @@
+use std::cmp::{max, Ordering};
+use std::fmt::Debug;
+use crate::ordered_set::OrderedSet;
+
+type WAVLLink<T> = Option<Box<WAVLNode<T>>>;
+
+#[derive(Debug)]
+struct WAVLNode<T> {
+    key: T,
+    // leaves have rank 0, a missing child counts as rank -1
+    rank: i32,
+    left: WAVLLink<T>,
+    right: WAVLLink<T>,
+}
+
+// Weak AVL tree (Haeupler, Sen and Tarjan). Every node keeps a rank, the rank difference to
+// each child is 1 or 2 and every leaf has rank 0. Built by inserts only it is an AVL tree;
+// deletes are allowed to leave it less balanced than that, which caps them at two rotations
+// while the height stays below 2 log n.
+#[derive(Debug)]
+pub struct WAVLTree<T> {
+    root: WAVLLink<T>,
+    len: usize,
+}
+
+impl<T: PartialOrd + Clone + Debug> WAVLTree<T> {
+    pub fn new() -> Self {
+        WAVLTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.partial_cmp(&node.key) {
+                Some(Ordering::Less) => current = &node.left,
+                Some(Ordering::Greater) => current = &node.right,
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+        self.root = Some(Self::insert_node(self.root.take(), value));
+        self.len += 1;
+        true
+    }
+
+    pub fn delete(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        self.root = Self::delete_node(self.root.take(), value);
+        self.len -= 1;
+        true
+    }
+
+    pub fn height(&self) -> usize {
+        fn height<T>(node: &WAVLLink<T>) -> usize {
+            node.as_ref().map_or(0, |n| 1 + max(height(&n.left), height(&n.right)))
+        }
+        height(&self.root)
+    }
+
+    pub fn leaves(&self) -> usize {
+        fn leaves<T>(node: &WAVLLink<T>) -> usize {
+            match node {
+                None => 0,
+                Some(n) if n.left.is_none() && n.right.is_none() => 1,
+                Some(n) => leaves(&n.left) + leaves(&n.right),
+            }
+        }
+        leaves(&self.root)
+    }
+
+    // keys in order
+    pub fn to_vec(&self) -> Vec<T> {
+        fn collect<T: Clone>(node: &WAVLLink<T>, out: &mut Vec<T>) {
+            if let Some(n) = node {
+                collect(&n.left, out);
+                out.push(n.key.clone());
+                collect(&n.right, out);
+            }
+        }
+        let mut out = Vec::with_capacity(self.len);
+        collect(&self.root, &mut out);
+        out
+    }
+
+    pub fn print_struct(&self) {
+        fn print<T: Debug>(node: &WAVLLink<T>, depth: usize, position: &str) {
+            if let Some(n) = node {
+                println!("{}{}{:?} (Rank: {})", " ".repeat(depth * 2), position, n.key, n.rank);
+                print(&n.left, depth + 1, "L: ");
+                print(&n.right, depth + 1, "R: ");
+            }
+        }
+        println!("WAVL Tree Structure:");
+        print(&self.root, 0, "Root: ");
+    }
+
+    // report the first broken property: key order, rank differences of 1 or 2 and leaves of rank 0
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let count = Self::check_node(&self.root, None, None)?;
+        if count != self.len {
+            return Err(format!("tree holds {} nodes but counts {}", count, self.len));
+        }
+        Ok(())
+    }
+
+    fn check_node(node: &WAVLLink<T>, low: Option<&T>, high: Option<&T>) -> Result<usize, String> {
+        let n = match node {
+            Some(n) => n,
+            None => return Ok(0),
+        };
+        if low.is_some_and(|low| n.key <= *low) || high.is_some_and(|high| n.key >= *high) {
+            return Err(format!("key {:?} is out of order", n.key));
+        }
+        if n.left.is_none() && n.right.is_none() && n.rank != 0 {
+            return Err(format!("leaf {:?} has rank {}", n.key, n.rank));
+        }
+        for child in [&n.left, &n.right] {
+            let difference = n.rank - Self::rank(child);
+            if difference != 1 && difference != 2 {
+                return Err(format!("key {:?} has a rank difference of {}", n.key, difference));
+            }
+        }
+        let left = Self::check_node(&n.left, low, Some(&n.key))?;
+        let right = Self::check_node(&n.right, Some(&n.key), high)?;
+        Ok(left + right + 1)
+    }
+
+    fn rank(node: &WAVLLink<T>) -> i32 {
+        node.as_ref().map_or(-1, |n| n.rank)
+    }
+
+    fn rotate_left(mut h: Box<WAVLNode<T>>) -> Box<WAVLNode<T>> {
+        let mut x = h.right.take().unwrap();
+        h.right = x.left.take();
+        x.left = Some(h);
+        x
+    }
+
+    fn rotate_right(mut h: Box<WAVLNode<T>>) -> Box<WAVLNode<T>> {
+        let mut x = h.left.take().unwrap();
+        h.left = x.right.take();
+        x.right = Some(h);
+        x
+    }
+
+    fn insert_node(node: WAVLLink<T>, value: T) -> Box<WAVLNode<T>> {
+        let mut h = match node {
+            Some(h) => h,
+            None => return Box::new(WAVLNode { key: value, rank: 0, left: None, right: None }),
+        };
+        if value < h.key {
+            h.left = Some(Self::insert_node(h.left.take(), value));
+            if Self::rank(&h.left) == h.rank {
+                h = Self::fix_left_insert(h);
+            }
+        } else {
+            h.right = Some(Self::insert_node(h.right.take(), value));
+            if Self::rank(&h.right) == h.rank {
+                h = Self::fix_right_insert(h);
+            }
+        }
+        h
+    }
+
+    // the left child reached the rank of `h`: promote, or rotate when the sibling is too far down
+    fn fix_left_insert(mut h: Box<WAVLNode<T>>) -> Box<WAVLNode<T>> {
+        if h.rank - Self::rank(&h.right) == 1 {
+            h.rank += 1;
+            return h;
+        }
+        let y = h.left.as_mut().unwrap();
+        if y.rank - Self::rank(&y.left) == 1 {
+            h.rank -= 1;
+            Self::rotate_right(h)
+        } else {
+            // the inner grandchild goes to the top
+            let z = y.right.as_mut().unwrap();
+            z.rank += 1;
+            y.rank -= 1;
+            h.rank -= 1;
+            let y = h.left.take().unwrap();
+            h.left = Some(Self::rotate_left(y));
+            Self::rotate_right(h)
+        }
+    }
+
+    fn fix_right_insert(mut h: Box<WAVLNode<T>>) -> Box<WAVLNode<T>> {
+        if h.rank - Self::rank(&h.left) == 1 {
+            h.rank += 1;
+            return h;
+        }
+        let y = h.right.as_mut().unwrap();
+        if y.rank - Self::rank(&y.right) == 1 {
+            h.rank -= 1;
+            Self::rotate_left(h)
+        } else {
+            let z = y.left.as_mut().unwrap();
+            z.rank += 1;
+            y.rank -= 1;
+            h.rank -= 1;
+            let y = h.right.take().unwrap();
+            h.right = Some(Self::rotate_right(y));
+            Self::rotate_left(h)
+        }
+    }
+
+    // `value` is known to be in the subtree
+    fn delete_node(node: WAVLLink<T>, value: &T) -> WAVLLink<T> {
+        let mut h = node?;
+        match value.partial_cmp(&h.key) {
+            Some(Ordering::Less) => h.left = Self::delete_node(h.left.take(), value),
+            Some(Ordering::Greater) => h.right = Self::delete_node(h.right.take(), value),
+            _ => match (h.left.take(), h.right.take()) {
+                // the child of a unary node is a leaf, moving it up keeps every rank valid
+                (None, right) => return right,
+                (left, None) => return left,
+                (left, Some(right)) => {
+                    let (successor, rest) = Self::take_min(right);
+                    h.key = successor;
+                    h.left = left;
+                    h.right = rest;
+                },
+            },
+        }
+        Some(Self::rebalance_delete(h))
+    }
+
+    // smallest key of a subtree and the rebalanced subtree without it
+    fn take_min(mut h: Box<WAVLNode<T>>) -> (T, WAVLLink<T>) {
+        match h.left.take() {
+            None => (h.key, h.right.take()),
+            Some(left) => {
+                let (min, rest) = Self::take_min(left);
+                h.left = rest;
+                (min, Some(Self::rebalance_delete(h)))
+            },
+        }
+    }
+
+    // repair `h` after one of its subtrees lost a node
+    fn rebalance_delete(mut h: Box<WAVLNode<T>>) -> Box<WAVLNode<T>> {
+        if h.left.is_none() && h.right.is_none() {
+            // a leaf has to be at rank 0
+            h.rank = 0;
+            h
+        } else if h.rank - Self::rank(&h.left) == 3 {
+            Self::fix_left_delete(h)
+        } else if h.rank - Self::rank(&h.right) == 3 {
+            Self::fix_right_delete(h)
+        } else {
+            h
+        }
+    }
+
+    // the left child is three ranks down
+    fn fix_left_delete(mut h: Box<WAVLNode<T>>) -> Box<WAVLNode<T>> {
+        let sibling_difference = h.rank - Self::rank(&h.right);
+        let y = h.right.as_mut().unwrap();
+        if sibling_difference == 2 {
+            h.rank -= 1;
+            return h;
+        }
+        let y_rank = y.rank;
+        if y_rank - Self::rank(&y.left) == 2 && y_rank - Self::rank(&y.right) == 2 {
+            y.rank -= 1;
+            h.rank -= 1;
+            return h;
+        }
+        if y_rank - Self::rank(&y.right) == 1 {
+            // single rotation, the sibling goes up
+            y.rank += 1;
+            h.rank -= 1;
+            let mut top = Self::rotate_left(h);
+            let x = top.left.as_mut().unwrap();
+            if x.left.is_none() && x.right.is_none() {
+                x.rank = 0;
+            }
+            top
+        } else {
+            // double rotation, the sibling's inner child goes up
+            let w = y.left.as_mut().unwrap();
+            w.rank += 2;
+            y.rank -= 1;
+            h.rank -= 2;
+            let y = h.right.take().unwrap();
+            h.right = Some(Self::rotate_right(y));
+            Self::rotate_left(h)
+        }
+    }
+
+    fn fix_right_delete(mut h: Box<WAVLNode<T>>) -> Box<WAVLNode<T>> {
+        let sibling_difference = h.rank - Self::rank(&h.left);
+        let y = h.left.as_mut().unwrap();
+        if sibling_difference == 2 {
+            h.rank -= 1;
+            return h;
+        }
+        let y_rank = y.rank;
+        if y_rank - Self::rank(&y.left) == 2 && y_rank - Self::rank(&y.right) == 2 {
+            y.rank -= 1;
+            h.rank -= 1;
+            return h;
+        }
+        if y_rank - Self::rank(&y.left) == 1 {
+            y.rank += 1;
+            h.rank -= 1;
+            let mut top = Self::rotate_right(h);
+            let x = top.right.as_mut().unwrap();
+            if x.left.is_none() && x.right.is_none() {
+                x.rank = 0;
+            }
+            top
+        } else {
+            let w = y.right.as_mut().unwrap();
+            w.rank += 2;
+            y.rank -= 1;
+            h.rank -= 2;
+            let y = h.left.take().unwrap();
+            h.left = Some(Self::rotate_left(y));
+            Self::rotate_right(h)
+        }
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> Default for WAVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone + Debug> OrderedSet<T> for WAVLTree<T> {
+    fn new() -> Self {
+        WAVLTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        WAVLTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: T) -> bool {
+        WAVLTree::delete(self, &value)
+    }
+
+    fn contains(&self, value: T) -> bool {
+        WAVLTree::contains(self, &value)
+    }
+
+    fn len(&self) -> usize {
+        WAVLTree::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        WAVLTree::is_empty(self)
+    }
+
+    fn height(&self) -> usize {
+        WAVLTree::height(self)
+    }
+
+    fn leaves(&self) -> usize {
+        WAVLTree::leaves(self)
+    }
+
+    fn to_vec(&self) -> Vec<T> {
+        WAVLTree::to_vec(self)
+    }
+
+    fn print_structure(&self) {
+        self.print_struct();
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        WAVLTree::check_invariants(self)
+    }
+}
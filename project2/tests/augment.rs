@@ -0,0 +1,98 @@
+use std::fmt::Debug;
+use project::augment::{Count, Max, Min, Sum, Summary};
+use project::avl_tree::AVLTree;
+use project::redblack_tree::RedBlackTree;
+use project::rng::XorShift64;
+
+// a value in 0..bound
+fn below(rng: &mut XorShift64, bound: u64) -> i64 {
+    (rng.next_u64() % bound) as i64
+}
+
+// the summary of `low..high` folded one occurrence at a time
+fn brute_force<S: Summary<i64>>(values: &[i64], low: i64, high: i64) -> Option<S> {
+    values.iter()
+        .filter(|value| low <= **value && **value < high)
+        .fold(None, |summary, value| S::combine_opt(summary, Some(S::lift(value, 1))))
+}
+
+// Fill a multiset with random values, take some out again, and compare the range summaries
+// of the tree against a fold over the sorted values.
+fn matches_brute_force<S: Summary<i64> + PartialEq + Debug>(seed: u64) {
+    let mut rng = XorShift64::new(seed);
+    let mut avl: AVLTree<i64, S> = AVLTree::multiset_with_summary();
+    let mut rb: RedBlackTree<i64, S> = RedBlackTree::multiset_with_summary();
+    let mut values = Vec::new();
+    for _ in 0..400 {
+        let value = below(&mut rng, 100) - 50;
+        avl.insert(value);
+        rb.insert(value);
+        values.push(value);
+    }
+    for _ in 0..150 {
+        let value = below(&mut rng, 100) - 50;
+        let removed = avl.remove_one(value);
+        assert_eq!(rb.remove_one(value), removed);
+        if removed {
+            let index = values.iter().position(|v| *v == value).unwrap();
+            values.remove(index);
+        }
+    }
+    values.sort();
+
+    assert_eq!(avl.summary(), brute_force::<S>(&values, i64::MIN, i64::MAX));
+    assert_eq!(rb.summary(), brute_force::<S>(&values, i64::MIN, i64::MAX));
+    for _ in 0..300 {
+        let low = below(&mut rng, 120) - 60;
+        let high = low + below(&mut rng, 60);
+        let expected = brute_force::<S>(&values, low, high);
+        assert_eq!(avl.range_summary(low, high), expected, "AVL {}..{}", low, high);
+        assert_eq!(rb.range_summary(low, high), expected, "red-black {}..{}", low, high);
+    }
+}
+
+#[test]
+fn sum_matches_brute_force() {
+    matches_brute_force::<Sum<i64>>(1);
+}
+
+#[test]
+fn min_matches_brute_force() {
+    matches_brute_force::<Min<i64>>(2);
+}
+
+#[test]
+fn max_matches_brute_force() {
+    matches_brute_force::<Max<i64>>(3);
+}
+
+#[test]
+fn count_matches_brute_force() {
+    matches_brute_force::<Count>(4);
+}
+
+#[test]
+fn sum_lifts_many_occurrences() {
+    for count in 1..=70 {
+        assert_eq!(Sum::lift(&3i64, count), Sum(3 * count as i64));
+    }
+    // doubling keeps this to a few dozen additions
+    assert_eq!(Sum::lift(&7u64, 1 << 40), Sum(7 << 40));
+    assert_eq!(Sum::lift(&0.5f64, 1_000_001), Sum(500_000.5));
+}
+
+#[test]
+fn empty_ranges_have_no_summary() {
+    let mut avl: AVLTree<i64, Sum<i64>> = AVLTree::with_summary();
+    let mut rb: RedBlackTree<i64, Sum<i64>> = RedBlackTree::with_summary();
+    assert_eq!(avl.summary(), None);
+    assert_eq!(rb.summary(), None);
+    for value in [10, 20, 30] {
+        avl.insert(value);
+        rb.insert(value);
+    }
+    assert_eq!(avl.range_summary(11, 20), None);
+    assert_eq!(rb.range_summary(30, 30), None);
+    assert_eq!(avl.range_summary(10, 31), Some(Sum(60)));
+    assert_eq!(rb.range_summary(15, 30), Some(Sum(20)));
+}
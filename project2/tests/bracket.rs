@@ -0,0 +1,67 @@
+use project::avl_tree::AVLTree;
+use project::pretty::Layout;
+use project::redblack_tree::RedBlackTree;
+use project::rng::XorShift64;
+
+#[test]
+fn printed_trees_read_back_with_the_same_shape() {
+    let mut rng = XorShift64::new(44);
+    for size in [0, 1, 2, 5, 50, 400] {
+        let mut avl: AVLTree<i32> = AVLTree::new_multiset();
+        let mut rb: RedBlackTree<i32> = RedBlackTree::new_multiset();
+        for _ in 0..size {
+            let value = (rng.next_u64() % 300) as i32 - 150;
+            avl.insert(value);
+            rb.insert(value);
+        }
+        let text = avl.to_pretty_string(Layout::Bracket);
+        let read = AVLTree::<i32>::from_bracket(&text).unwrap();
+        assert_eq!(read.to_pretty_string(Layout::Bracket), text);
+        assert_eq!(read.iter().collect::<Vec<_>>(), avl.iter().collect::<Vec<_>>());
+
+        let text = rb.to_pretty_string(Layout::Bracket);
+        let read = RedBlackTree::<i32>::from_bracket(&text).unwrap();
+        assert_eq!(read.to_pretty_string(Layout::Bracket), text);
+        assert_eq!(read.iter().collect::<Vec<_>>(), rb.iter().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn colors_may_come_before_or_after_the_value() {
+    let before = RedBlackTree::<i32>::from_bracket("(B4 (R2 B1 B3) B6)").unwrap();
+    let after = RedBlackTree::<i32>::from_bracket(" ( 4:B (2:R 1:B 3:B) 6:B ) ").unwrap();
+    assert_eq!(before.to_pretty_string(Layout::Bracket), "(4:B (2:R 1:B 3:B) 6:B)");
+    assert_eq!(after.to_pretty_string(Layout::Bracket), "(4:B (2:R 1:B 3:B) 6:B)");
+    assert_eq!(AVLTree::<i32>::from_bracket("(4 (2 1 3) (6 _ 7))").unwrap().to_pretty_string(Layout::Bracket), "(4 (2 1 3) (6 _ 7))");
+    assert_eq!(AVLTree::<i32>::from_bracket("(5)").unwrap().iter().collect::<Vec<_>>(), vec![5]);
+    assert!(AVLTree::<i32>::from_bracket("_").unwrap().iter().next().is_none());
+}
+
+#[test]
+fn syntax_errors_name_the_column() {
+    let error = |text: &str| AVLTree::<i32>::from_bracket(text).err().unwrap();
+    assert_eq!(error("(4 (2 1 3) 6) 7"), "column 15: unexpected `7` after the tree");
+    assert_eq!(error("(4 ) 6)"), "column 6: unexpected `6` after the tree");
+    assert_eq!(error("(4 1 ) )"), "column 6: unexpected `)`, expected a tree");
+    assert_eq!(error("( _ 1 2)"), "column 3: expected a node, found `_`");
+    assert_eq!(error("(2 1 3 4)"), "column 8: expected `)` after the two children, found `4`");
+    assert_eq!(error("(2 1 3"), "unexpected end of input, expected `)`");
+    assert_eq!(error("(2 1"), "unexpected end of input, expected a tree");
+    assert_eq!(error(""), "unexpected end of input, expected a tree");
+    assert_eq!(error("(2 x 3)"), "column 4: `x` is not a valid value");
+    assert_eq!(error("(2 1 3*0)"), "column 6: `0` is not a valid count in `3*0`");
+    assert_eq!(error("(2 1 y*2)"), "column 6: `y` is not a valid value in `y*2`");
+}
+
+#[test]
+fn trees_that_break_the_rules_are_turned_down() {
+    let avl = |text: &str| AVLTree::<i32>::from_bracket(text).err().unwrap();
+    assert!(avl("(2 3 1)").starts_with("not a valid AVL tree: "), "{}", avl("(2 3 1)"));
+    assert!(avl("(1 _ (2 _ 3))").starts_with("not a valid AVL tree: "));
+    assert_eq!(avl("(B2 1 3)"), "key 2 has a color, AVL nodes have none");
+
+    let rb = |text: &str| RedBlackTree::<i32>::from_bracket(text).err().unwrap();
+    assert!(rb("(R2 B1 B3)").starts_with("not a valid red-black tree: "));
+    assert!(rb("(B2 R1 B3)").starts_with("not a valid red-black tree: "));
+    assert_eq!(rb("(B2 1 R3)"), "value 1 has no color, write it as R1 or B1");
+}
@@ -0,0 +1,102 @@
+use std::process::{Command, Output};
+
+// Exit status of the command-line mode: 0 when every command ran, 1 when one failed after
+// the ones before it ran, 2 when the arguments or the commands could not be understood.
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_project")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn commands_that_all_run_exit_with_0() {
+    let output = run(&["--tree", "rb", "--type", "i64", "--ops", "insert 5 3 8; delete 3; height; print"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "\
+{\"op\":\"insert\",\"inserted\":[5,3,8],\"existing\":[]}
+{\"op\":\"delete\",\"deleted\":[3],\"missing\":[]}
+{\"op\":\"height\",\"height\":2}
+{\"op\":\"print\",\"tree\":\"(5:B _ 8:R)\"}
+");
+    assert_eq!(stderr(&output), "");
+
+    let output = run(&["--tree=rb", "--type=string", "--ops", "insert b a; len"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "{\"op\":\"insert\",\"inserted\":[\"b\",\"a\"],\"existing\":[]}\n{\"op\":\"len\",\"len\":2}\n");
+}
+
+#[test]
+fn the_avl_tree_takes_strings_too() {
+    let output = run(&["--type", "string", "--ops", "insert pear apple fig; delete fig; contains apple; print"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "\
+{\"op\":\"insert\",\"inserted\":[\"pear\",\"apple\",\"fig\"],\"existing\":[]}
+{\"op\":\"delete\",\"deleted\":[\"fig\"],\"missing\":[]}
+{\"op\":\"contains\",\"found\":[\"apple\"],\"missing\":[]}
+{\"op\":\"print\",\"tree\":\"(\\\"pear\\\" \\\"apple\\\" _)\"}
+");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn help_exits_with_0() {
+    for flag in ["--help", "-h"] {
+        let output = run(&[flag]);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(stdout(&output).starts_with("usage: project "));
+        assert!(stdout(&output).contains("Exit status: 0 when all commands ran,\n1 when a command failed, 2 for invalid arguments or commands."));
+    }
+}
+
+#[test]
+fn a_failed_command_exits_with_1_after_the_earlier_ones_ran() {
+    let output = run(&["--ops", "insert 1; assert-height 4; len"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "{\"op\":\"insert\",\"inserted\":[1],\"existing\":[]}\n");
+    assert_eq!(stderr(&output), "error: command 2: assert-height failed: expected height 4, found 1\n");
+}
+
+#[test]
+fn bad_arguments_exit_with_2() {
+    let cases: [(&[&str], &str); 6] = [
+        (&["--tree", "splay", "--ops", "len"], "unknown tree `splay`, expected avl or rb"),
+        (&["--type", "u8", "--ops", "len"], "unknown type `u8`, expected i32, f32, i64 or string"),
+        (&["--ops", "len", "--verbose"], "unknown argument `--verbose`"),
+        (&["--tree", "rb"], "--ops, --script or --repl is missing"),
+        (&["--ops", "len", "--script", "x"], "give one of --ops, --script and --repl, once"),
+        (&["--ops", "len", "--history", "h"], "--history only goes with --repl"),
+    ];
+    for (args, message) in cases {
+        let output = run(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert_eq!(stderr(&output), format!("error: {}\nrun with --help for the usage\n", message));
+        assert_eq!(stdout(&output), "");
+    }
+    let output = run(&["--ops"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: --ops needs a value\n"));
+}
+
+#[test]
+fn commands_that_do_not_parse_exit_with_2_before_any_runs() {
+    let output = run(&["--ops", "insert 1; insert x"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(stderr(&output), "error: command 2: `x` is not a valid value\n");
+
+    let output = run(&["--ops", "insert 1; frobnicate 1"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(stderr(&output), "error: command 2: unknown command `frobnicate`\n");
+
+    let output = run(&["--script", "/nonexistent/script.txt"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: cannot read /nonexistent/script.txt: "));
+}
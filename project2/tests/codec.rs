@@ -0,0 +1,118 @@
+use project::avl_tree::AVLTree;
+use project::codec::{Codec, TreeKind};
+use project::ordered_set::OrderedSet;
+use project::redblack_tree::RedBlackTree;
+use project::rng::XorShift64;
+
+#[test]
+fn layout_of_a_small_set() {
+    let mut tree: AVLTree<i32> = AVLTree::new();
+    tree.insert(2);
+    tree.insert(1);
+    assert_eq!(tree.to_bytes(), [
+        b'B', b'T', b'R', b'E', 1, 1, 0x03, 0,
+        2, 0, 0, 0, 0, 0, 0, 0,
+        1, 0, 0, 0,
+        2, 0, 0, 0,
+    ]);
+    let mut tree: RedBlackTree<String> = RedBlackTree::new_multiset();
+    tree.insert("ab".to_string());
+    tree.insert("ab".to_string());
+    assert_eq!(tree.to_bytes(), [
+        b'B', b'T', b'R', b'E', 1, 2, 0x31, 1,
+        1, 0, 0, 0, 0, 0, 0, 0,
+        2, 0, 0, 0, b'a', b'b',
+        2, 0, 0, 0, 0, 0, 0, 0,
+    ]);
+}
+
+#[test]
+fn trees_read_back_what_they_wrote() {
+    let mut rng = XorShift64::new(45);
+    for size in [0, 1, 3, 100, 2000] {
+        let mut avl: AVLTree<i64> = AVLTree::new_multiset();
+        let mut rb: RedBlackTree<String> = RedBlackTree::new();
+        let mut floats: AVLTree<f64> = AVLTree::new();
+        for _ in 0..size {
+            let value = (rng.next_u64() % 500) as i64 - 250;
+            avl.insert(value);
+            rb.insert(format!("key é{}", value));
+            OrderedSet::insert(&mut floats, value as f64 / 8.0);
+        }
+        let read = AVLTree::<i64>::from_bytes(&avl.to_bytes()).unwrap();
+        read.check_invariants().unwrap();
+        assert_eq!(read.iter().collect::<Vec<_>>(), avl.iter().collect::<Vec<_>>());
+        // the multiset flag comes back too, another insert adds an occurrence
+        let mut read = read;
+        read.insert(7);
+        assert_eq!(read.count(7), avl.count(7) + 1);
+
+        let read = RedBlackTree::<String>::from_bytes(&rb.to_bytes()).unwrap();
+        read.check_invariants().unwrap();
+        assert_eq!(read.iter().collect::<Vec<_>>(), rb.iter().collect::<Vec<_>>());
+
+        let read = AVLTree::<f64>::from_bytes(&floats.to_bytes()).unwrap();
+        assert_eq!(read.iter().collect::<Vec<_>>(), floats.iter().collect::<Vec<_>>());
+    }
+}
+
+fn small_file() -> Vec<u8> {
+    let mut tree: AVLTree<i32> = AVLTree::new_multiset();
+    for value in [1, 2, 2] {
+        tree.insert(value);
+    }
+    tree.to_bytes()
+}
+
+#[test]
+fn broken_headers_are_turned_down() {
+    let read = |bytes: &[u8]| AVLTree::<i32>::from_bytes(bytes).err().unwrap();
+    let good = small_file();
+
+    let mut bytes = good.clone();
+    bytes[0] = b'X';
+    assert_eq!(read(&bytes), "not a tree file, the header is missing");
+    assert_eq!(read(b"BT"), "not a tree file, the header is missing");
+    let mut bytes = good.clone();
+    bytes[4] = 2;
+    assert_eq!(read(&bytes), "unsupported format version 2, expected 1");
+    let mut bytes = good.clone();
+    bytes[5] = 9;
+    assert_eq!(read(&bytes), "unknown tree kind 9");
+    assert_eq!(RedBlackTree::<i32>::from_bytes(&good).err().unwrap(), "the file holds an AVL tree, not a red-black tree");
+    assert_eq!(AVLTree::<i64>::from_bytes(&good).err().unwrap(), "the elements are encoded with tag 0x03, expected 0x04");
+    assert_eq!(i32::TAG, good[6]);
+    let mut bytes = good.clone();
+    bytes[7] = 3;
+    assert_eq!(read(&bytes), "unknown flags 0x03");
+    assert_eq!(TreeKind::RedBlack.id(), "red-black");
+}
+
+#[test]
+fn broken_entries_are_turned_down() {
+    let read = |bytes: &[u8]| AVLTree::<i32>::from_bytes(bytes).err().unwrap();
+    let good = small_file();
+    // header, length, then 1 * 1 and 2 * 2
+    assert_eq!(good.len(), 8 + 8 + 2 * (4 + 8));
+
+    let mut bytes = good.clone();
+    bytes[16..20].copy_from_slice(&5i32.to_le_bytes());
+    assert_eq!(read(&bytes), "entry 1 is not larger than the one before it");
+    let mut bytes = good.clone();
+    bytes[20..28].copy_from_slice(&0u64.to_le_bytes());
+    assert_eq!(read(&bytes), "entry 0 has no occurrences");
+    let mut bytes = good.clone();
+    bytes.push(0);
+    assert_eq!(read(&bytes), "1 unexpected bytes after the last entry");
+    assert_eq!(read(&good[..good.len() - 3]), "unexpected end of data, 3 more bytes needed");
+    // a cut anywhere is an error, never a panic or a shorter tree
+    for len in 0..good.len() {
+        assert!(AVLTree::<i32>::from_bytes(&good[..len]).is_err(), "{} bytes", len);
+    }
+
+    let mut tree: RedBlackTree<String> = RedBlackTree::new();
+    tree.insert("a".to_string());
+    let mut bytes = tree.to_bytes();
+    *bytes.last_mut().unwrap() = 0xff;
+    assert_eq!(RedBlackTree::<String>::from_bytes(&bytes).err().unwrap(), "a string is not valid UTF-8");
+}
@@ -0,0 +1,252 @@
+use project::avl_tree::{self, AVLTree};
+use project::redblack_tree::{self, RedBlackTree};
+use project::rng::XorShift64;
+
+// what the cursors of both trees offer, so one test body checks both
+trait Cursor {
+    fn peek(&self) -> Option<i32>;
+    fn move_next(&mut self);
+    fn move_prev(&mut self);
+    fn insert_before(&mut self, value: i32) -> bool;
+    fn insert_after(&mut self, value: i32) -> bool;
+    fn remove_current(&mut self) -> Option<i32>;
+}
+
+trait CursorTree {
+    type Cursor<'a>: Cursor where Self: 'a;
+    fn new(multiset: bool) -> Self;
+    fn insert(&mut self, value: i32);
+    fn lower_bound_cursor(&mut self, value: i32) -> Self::Cursor<'_>;
+    fn values(&self) -> Vec<i32>;
+    fn check(&self) -> Result<(), String>;
+}
+
+macro_rules! impl_cursor {
+    ($cursor:ty) => {
+        impl Cursor for $cursor {
+            fn peek(&self) -> Option<i32> {
+                <$cursor>::peek(self)
+            }
+
+            fn move_next(&mut self) {
+                <$cursor>::move_next(self)
+            }
+
+            fn move_prev(&mut self) {
+                <$cursor>::move_prev(self)
+            }
+
+            fn insert_before(&mut self, value: i32) -> bool {
+                <$cursor>::insert_before(self, value)
+            }
+
+            fn insert_after(&mut self, value: i32) -> bool {
+                <$cursor>::insert_after(self, value)
+            }
+
+            fn remove_current(&mut self) -> Option<i32> {
+                <$cursor>::remove_current(self)
+            }
+        }
+    };
+}
+
+impl_cursor!(avl_tree::CursorMut<'_, i32>);
+impl_cursor!(redblack_tree::CursorMut<'_, i32>);
+
+impl CursorTree for AVLTree<i32> {
+    type Cursor<'a> = avl_tree::CursorMut<'a, i32>;
+
+    fn new(multiset: bool) -> Self {
+        if multiset { AVLTree::new_multiset() } else { AVLTree::new() }
+    }
+
+    fn insert(&mut self, value: i32) {
+        AVLTree::insert(self, value);
+    }
+
+    fn lower_bound_cursor(&mut self, value: i32) -> Self::Cursor<'_> {
+        AVLTree::lower_bound_cursor(self, value)
+    }
+
+    fn values(&self) -> Vec<i32> {
+        self.iter().collect()
+    }
+
+    fn check(&self) -> Result<(), String> {
+        self.check_invariants()
+    }
+}
+
+impl CursorTree for RedBlackTree<i32> {
+    type Cursor<'a> = redblack_tree::CursorMut<'a, i32>;
+
+    fn new(multiset: bool) -> Self {
+        if multiset { RedBlackTree::new_multiset() } else { RedBlackTree::new() }
+    }
+
+    fn insert(&mut self, value: i32) {
+        RedBlackTree::insert(self, value);
+    }
+
+    fn lower_bound_cursor(&mut self, value: i32) -> Self::Cursor<'_> {
+        RedBlackTree::lower_bound_cursor(self, value)
+    }
+
+    fn values(&self) -> Vec<i32> {
+        self.iter().collect()
+    }
+
+    fn check(&self) -> Result<(), String> {
+        self.check_invariants()
+    }
+}
+
+fn tree_of<Tree: CursorTree>(values: &[i32]) -> Tree {
+    let mut tree = Tree::new(false);
+    for &value in values {
+        tree.insert(value);
+    }
+    tree
+}
+
+fn cursor_walks_in_order_through_the_ghost<Tree: CursorTree>() {
+    let mut tree: Tree = tree_of(&[30, 10, 50, 20, 40]);
+    let mut cursor = tree.lower_bound_cursor(25);
+    assert_eq!(cursor.peek(), Some(30));
+    let mut seen = Vec::new();
+    for _ in 0..5 {
+        cursor.move_next();
+        seen.push(cursor.peek());
+    }
+    // past the last node is the ghost, and after it the first node again
+    assert_eq!(seen, vec![Some(40), Some(50), None, Some(10), Some(20)]);
+    seen.clear();
+    for _ in 0..4 {
+        cursor.move_prev();
+        seen.push(cursor.peek());
+    }
+    assert_eq!(seen, vec![Some(10), None, Some(50), Some(40)]);
+    drop(cursor);
+
+    assert_eq!(tree.lower_bound_cursor(30).peek(), Some(30));
+    assert_eq!(tree.lower_bound_cursor(i32::MIN).peek(), Some(10));
+    assert_eq!(tree.lower_bound_cursor(51).peek(), None);
+    assert_eq!(Tree::new(false).lower_bound_cursor(0).peek(), None);
+}
+
+#[test]
+fn avl_cursor_walks_in_order_through_the_ghost() {
+    cursor_walks_in_order_through_the_ghost::<AVLTree<i32>>();
+}
+
+#[test]
+fn red_black_cursor_walks_in_order_through_the_ghost() {
+    cursor_walks_in_order_through_the_ghost::<RedBlackTree<i32>>();
+}
+
+fn inserts_keep_the_order_and_the_position<Tree: CursorTree>() {
+    let mut tree: Tree = tree_of(&[10, 20, 30, 40, 50]);
+    let mut cursor = tree.lower_bound_cursor(30);
+    assert!(cursor.insert_before(25));
+    // 15 belongs before 20, not right before the cursor
+    assert!(!cursor.insert_before(15));
+    assert!(cursor.insert_after(35));
+    assert!(!cursor.insert_after(45));
+    // an equal key does not fit in a set
+    assert!(!cursor.insert_after(30));
+    assert!(!cursor.insert_before(30));
+    assert_eq!(cursor.peek(), Some(30));
+    cursor.move_prev();
+    assert_eq!(cursor.peek(), Some(25));
+    cursor.move_next();
+    cursor.move_next();
+    assert_eq!(cursor.peek(), Some(35));
+    drop(cursor);
+
+    // on the ghost, before means after the last node and after means before the first
+    let mut cursor = tree.lower_bound_cursor(100);
+    assert!(cursor.insert_before(60));
+    assert!(cursor.insert_after(5));
+    assert!(!cursor.insert_after(55));
+    assert_eq!(cursor.peek(), None);
+    drop(cursor);
+    assert_eq!(tree.values(), vec![5, 10, 20, 25, 30, 35, 40, 50, 60]);
+    tree.check().unwrap();
+}
+
+#[test]
+fn avl_cursor_inserts_keep_the_order_and_the_position() {
+    inserts_keep_the_order_and_the_position::<AVLTree<i32>>();
+}
+
+#[test]
+fn red_black_cursor_inserts_keep_the_order_and_the_position() {
+    inserts_keep_the_order_and_the_position::<RedBlackTree<i32>>();
+}
+
+fn equal_keys_fit_in_a_multiset<Tree: CursorTree>() {
+    let mut tree = Tree::new(true);
+    for value in [10, 20, 30] {
+        tree.insert(value);
+    }
+    let mut cursor = tree.lower_bound_cursor(20);
+    assert!(cursor.insert_before(20));
+    assert!(cursor.insert_after(20));
+    assert!(cursor.insert_before(10));
+    assert!(!cursor.insert_after(31));
+    // the node goes with all of its occurrences
+    assert_eq!(cursor.remove_current(), Some(20));
+    assert_eq!(cursor.peek(), Some(30));
+    drop(cursor);
+    assert_eq!(tree.values(), vec![10, 10, 30]);
+    tree.check().unwrap();
+}
+
+#[test]
+fn avl_cursor_equal_keys_fit_in_a_multiset() {
+    equal_keys_fit_in_a_multiset::<AVLTree<i32>>();
+}
+
+#[test]
+fn red_black_cursor_equal_keys_fit_in_a_multiset() {
+    equal_keys_fit_in_a_multiset::<RedBlackTree<i32>>();
+}
+
+// remove every key the cursor passes that a random choice picks, checked against a vector
+fn removals_move_to_the_next_key<Tree: CursorTree>(seed: u64) {
+    let mut rng = XorShift64::new(seed);
+    let mut expected: Vec<i32> = (0..300).map(|value| value * 2).collect();
+    let mut tree: Tree = tree_of(&expected);
+    let start = (rng.next_u64() % 600) as i32;
+    let mut cursor = tree.lower_bound_cursor(start);
+    let mut index = expected.partition_point(|&value| value < start);
+    while index < expected.len() {
+        assert_eq!(cursor.peek(), Some(expected[index]));
+        if rng.next_u64().is_multiple_of(2) {
+            assert_eq!(cursor.remove_current(), Some(expected.remove(index)));
+        } else {
+            cursor.move_next();
+            index += 1;
+        }
+    }
+    assert_eq!(cursor.peek(), None);
+    assert_eq!(cursor.remove_current(), None);
+    drop(cursor);
+    assert_eq!(tree.values(), expected);
+    tree.check().unwrap();
+}
+
+#[test]
+fn avl_cursor_removals_move_to_the_next_key() {
+    for seed in 1..20 {
+        removals_move_to_the_next_key::<AVLTree<i32>>(seed);
+    }
+}
+
+#[test]
+fn red_black_cursor_removals_move_to_the_next_key() {
+    for seed in 1..20 {
+        removals_move_to_the_next_key::<RedBlackTree<i32>>(seed);
+    }
+}
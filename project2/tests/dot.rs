@@ -0,0 +1,82 @@
+use project::avl_tree::AVLTree;
+use project::dot::DotWriter;
+use project::redblack_tree::RedBlackTree;
+
+#[test]
+fn avl_nodes_show_height_balance_and_occurrences() {
+    let mut tree: AVLTree<i32> = AVLTree::new_multiset();
+    for value in [2, 1, 3, 3] {
+        tree.insert(value);
+    }
+    assert_eq!(tree.to_dot(), r#"digraph AVLTree {
+    graph [ordering=out];
+    node [shape=circle, fontname="Helvetica"];
+    n0 [label="2\nh=2 bf=0"];
+    n1 [label="1\nh=1 bf=0"];
+    n2 [shape=point, label=""];
+    n1 -> n2;
+    n3 [shape=point, label=""];
+    n1 -> n3;
+    n0 -> n1;
+    n4 [label="3*2\nh=1 bf=0"];
+    n5 [shape=point, label=""];
+    n4 -> n5;
+    n6 [shape=point, label=""];
+    n4 -> n6;
+    n0 -> n4;
+}
+"#);
+}
+
+#[test]
+fn red_black_nodes_are_filled_with_their_color_and_labels_are_escaped() {
+    let mut tree: RedBlackTree<String> = RedBlackTree::new();
+    for value in ["b", "a\"q", "c\\d"] {
+        tree.insert(value.to_string());
+    }
+    assert_eq!(tree.to_dot(), r#"digraph RedBlackTree {
+    graph [ordering=out];
+    node [shape=circle, fontname="Helvetica"];
+    n0 [label="\"b\"", style=filled, fillcolor=black, fontcolor=white];
+    n1 [label="\"a\\\"q\"", style=filled, fillcolor=red, fontcolor=white];
+    n2 [shape=point, label=""];
+    n1 -> n2;
+    n3 [shape=point, label=""];
+    n1 -> n3;
+    n0 -> n1;
+    n4 [label="\"c\\\\d\"", style=filled, fillcolor=red, fontcolor=white];
+    n5 [shape=point, label=""];
+    n4 -> n5;
+    n6 [shape=point, label=""];
+    n4 -> n6;
+    n0 -> n4;
+}
+"#);
+}
+
+#[test]
+fn an_empty_tree_is_an_empty_graph() {
+    let expected = "digraph AVLTree {\n    graph [ordering=out];\n    node [shape=circle, fontname=\"Helvetica\"];\n}\n";
+    assert_eq!(AVLTree::<i32>::new().to_dot(), expected);
+    assert!(RedBlackTree::<i32>::new().to_dot().starts_with("digraph RedBlackTree {\n"));
+}
+
+#[test]
+fn writer_numbers_nodes_and_breaks_label_lines() {
+    let mut dot = DotWriter::new("G");
+    let root = dot.node("top\nline", "");
+    let leaf = dot.node("x", "color=blue");
+    let nil = dot.nil();
+    dot.edge(&root, &leaf);
+    dot.edge(&root, &nil);
+    assert_eq!(dot.finish(), r#"digraph G {
+    graph [ordering=out];
+    node [shape=circle, fontname="Helvetica"];
+    n0 [label="top\nline"];
+    n1 [label="x", color=blue];
+    n2 [shape=point, label=""];
+    n0 -> n1;
+    n0 -> n2;
+}
+"#);
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use project::avl_tree::AVLTree;
+use project::durable::{DurableOptions, DurableTree, SnapshotTree};
+use project::redblack_tree::RedBlackTree;
+
+// an empty directory of its own for every test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("durable-tree-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn log_len(dir: &Path) -> u64 {
+    fs::metadata(dir.join("wal.log")).unwrap().len()
+}
+
+fn cut_log(dir: &Path, len: u64) {
+    OpenOptions::new().write(true).open(dir.join("wal.log")).unwrap().set_len(len).unwrap();
+}
+
+fn no_snapshots() -> DurableOptions {
+    DurableOptions { snapshot_every: 0, sync: false }
+}
+
+fn contents<Tree: SnapshotTree<i32>>(tree: &DurableTree<i32, Tree>) -> Vec<i32> {
+    let tree = tree.tree();
+    tree.check_invariants().unwrap();
+    tree.to_vec()
+}
+
+fn reopens_with_the_same_contents<Tree: SnapshotTree<i32>>(name: &str) {
+    let dir = test_dir(name);
+    {
+        let mut tree: DurableTree<i32, Tree> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+        for value in 0..100 {
+            assert!(tree.insert(value).unwrap());
+        }
+        for value in (0..100).step_by(3) {
+            assert!(tree.delete(value).unwrap());
+        }
+        assert!(!tree.insert(1).unwrap());
+        assert!(!tree.delete(0).unwrap());
+    }
+    let tree: DurableTree<i32, Tree> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+    let expected: Vec<i32> = (0..100).filter(|v| v % 3 != 0).collect();
+    assert_eq!(contents(&tree), expected);
+    // the calls that changed nothing were not logged
+    assert_eq!(tree.recovery().replayed, 134);
+    assert_eq!(tree.recovery().truncated_bytes, 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn avl_tree_reopens_with_the_same_contents() {
+    reopens_with_the_same_contents::<AVLTree<i32>>("avl-reopen");
+}
+
+#[test]
+fn red_black_tree_reopens_with_the_same_contents() {
+    reopens_with_the_same_contents::<RedBlackTree<i32>>("rb-reopen");
+}
+
+// cut the log at every byte of its last record, as a crash in the middle of the write would
+fn partial_last_record_is_dropped<Tree: SnapshotTree<i32>>(name: &str) {
+    let dir = test_dir(name);
+    let (before_last, full) = {
+        let mut tree: DurableTree<i32, Tree> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+        for value in [5, 3, 8, 1] {
+            tree.insert(value).unwrap();
+        }
+        let before_last = log_len(&dir);
+        tree.delete(3).unwrap();
+        (before_last, log_len(&dir))
+    };
+    let saved = fs::read(dir.join("wal.log")).unwrap();
+
+    for len in before_last..full {
+        fs::write(dir.join("wal.log"), &saved[..len as usize]).unwrap();
+        let tree: DurableTree<i32, Tree> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+        assert_eq!(contents(&tree), vec![1, 3, 5, 8], "log cut to {} bytes", len);
+        assert_eq!(tree.recovery().replayed, 4);
+        assert_eq!(tree.recovery().truncated_bytes, len - before_last);
+        // the partial record is gone from the file as well
+        assert_eq!(log_len(&dir), before_last);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn avl_tree_drops_a_partial_last_record() {
+    partial_last_record_is_dropped::<AVLTree<i32>>("avl-partial");
+}
+
+#[test]
+fn red_black_tree_drops_a_partial_last_record() {
+    partial_last_record_is_dropped::<RedBlackTree<i32>>("rb-partial");
+}
+
+#[test]
+fn a_record_with_a_bad_checksum_ends_the_log() {
+    let dir = test_dir("checksum");
+    {
+        let mut tree: DurableTree<i32, RedBlackTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+        for value in 1..=3 {
+            tree.insert(value).unwrap();
+        }
+    }
+    let mut bytes = fs::read(dir.join("wal.log")).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    fs::write(dir.join("wal.log"), &bytes).unwrap();
+
+    let tree: DurableTree<i32, RedBlackTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+    assert_eq!(contents(&tree), vec![1, 2]);
+    assert!(tree.recovery().truncated_bytes > 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn writes_after_recovering_from_a_partial_record_survive() {
+    let dir = test_dir("after-partial");
+    {
+        let mut tree: DurableTree<i32, AVLTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+        tree.insert(10).unwrap();
+        tree.insert(20).unwrap();
+    }
+    cut_log(&dir, log_len(&dir) - 2);
+    {
+        let mut tree: DurableTree<i32, AVLTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+        assert_eq!(contents(&tree), vec![10]);
+        tree.insert(30).unwrap();
+    }
+    let tree: DurableTree<i32, AVLTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+    assert_eq!(contents(&tree), vec![10, 30]);
+    assert_eq!(tree.recovery().truncated_bytes, 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn snapshots_are_taken_periodically_and_the_log_starts_over() {
+    let dir = test_dir("periodic");
+    let options = DurableOptions { snapshot_every: 10, sync: false };
+    {
+        let mut tree: DurableTree<i32, RedBlackTree<i32>> = DurableTree::open_with(&dir, options).unwrap();
+        for value in 0..25 {
+            tree.insert(value).unwrap();
+        }
+        assert_eq!(tree.generation(), 2);
+    }
+    let tree: DurableTree<i32, RedBlackTree<i32>> = DurableTree::open_with(&dir, options).unwrap();
+    assert_eq!(contents(&tree), (0..25).collect::<Vec<_>>());
+    assert_eq!(tree.recovery().generation, 2);
+    assert_eq!(tree.recovery().replayed, 5);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// the change that asked for a snapshot is kept even when the snapshot cannot be written
+#[test]
+fn a_failed_automatic_snapshot_does_not_fail_the_change() {
+    let dir = test_dir("snapshot-fails");
+    let options = DurableOptions { snapshot_every: 3, sync: false };
+    {
+        let mut tree: DurableTree<i32, AVLTree<i32>> = DurableTree::open_with(&dir, options).unwrap();
+        // a directory in the way of the temporary file stops the snapshot
+        fs::create_dir(dir.join("snapshot.bin.tmp")).unwrap();
+        for value in 0..4 {
+            assert!(tree.insert(value).unwrap());
+        }
+        assert!(tree.snapshot_error().unwrap().contains("snapshot.bin.tmp"));
+        assert_eq!(tree.generation(), 0);
+
+        fs::remove_dir(dir.join("snapshot.bin.tmp")).unwrap();
+        assert!(tree.delete(0).unwrap());
+        assert_eq!(tree.snapshot_error(), None);
+        assert_eq!(tree.generation(), 1);
+        assert!(tree.insert(10).unwrap());
+    }
+    let tree: DurableTree<i32, AVLTree<i32>> = DurableTree::open_with(&dir, options).unwrap();
+    assert_eq!(contents(&tree), vec![1, 2, 3, 10]);
+    assert_eq!(tree.recovery().replayed, 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// once the snapshot is written, records in the old log would be thrown away on the next open
+#[test]
+fn a_snapshot_without_a_new_log_stops_changes_until_one_succeeds() {
+    let dir = test_dir("log-fails");
+    let mut tree: DurableTree<i32, RedBlackTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+    tree.insert(1).unwrap();
+    fs::create_dir(dir.join("wal.log.tmp")).unwrap();
+    assert!(tree.snapshot().is_err());
+    let error = tree.insert(2).unwrap_err();
+    assert!(error.contains("until a snapshot succeeds"), "{}", error);
+    assert_eq!(contents(&tree), vec![1]);
+
+    fs::remove_dir(dir.join("wal.log.tmp")).unwrap();
+    tree.snapshot().unwrap();
+    assert!(tree.insert(2).unwrap());
+    drop(tree);
+    let tree: DurableTree<i32, RedBlackTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+    assert_eq!(contents(&tree), vec![1, 2]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// a crash after the new snapshot is in place but before its log replaced the old one
+#[test]
+fn a_log_older_than_the_snapshot_is_ignored() {
+    let dir = test_dir("stale-log");
+    {
+        let mut tree: DurableTree<i32, AVLTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+        for value in [1, 2, 3] {
+            tree.insert(value).unwrap();
+        }
+        tree.delete(2).unwrap();
+        let old_log = fs::read(dir.join("wal.log")).unwrap();
+        tree.snapshot().unwrap();
+        fs::write(dir.join("wal.log"), old_log).unwrap();
+        // and a temporary file the crash left behind
+        fs::write(dir.join("wal.log.tmp"), b"partial").unwrap();
+    }
+    let mut tree: DurableTree<i32, AVLTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+    assert_eq!(contents(&tree), vec![1, 3]);
+    assert!(tree.recovery().stale_log);
+    assert_eq!(tree.recovery().replayed, 0);
+    assert!(!dir.join("wal.log.tmp").exists());
+
+    tree.insert(4).unwrap();
+    drop(tree);
+    let tree: DurableTree<i32, AVLTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+    assert_eq!(contents(&tree), vec![1, 3, 4]);
+    assert!(!tree.recovery().stale_log);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_damaged_snapshot_is_reported() {
+    let dir = test_dir("damaged");
+    {
+        let mut tree: DurableTree<i32, RedBlackTree<i32>> = DurableTree::open_with(&dir, no_snapshots()).unwrap();
+        tree.insert(1).unwrap();
+        tree.snapshot().unwrap();
+    }
+    let mut bytes = fs::read(dir.join("snapshot.bin")).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    fs::write(dir.join("snapshot.bin"), bytes).unwrap();
+    let error = DurableTree::<i32, RedBlackTree<i32>>::open_with(&dir, no_snapshots()).err().unwrap();
+    assert!(error.contains("damaged"), "{}", error);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// random changes with a crash that cuts the log at a random point after each round
+#[test]
+fn random_crashes_lose_at_most_the_record_being_written() {
+    let dir = test_dir("random");
+    let options = DurableOptions { snapshot_every: 37, sync: false };
+    let mut model = BTreeSet::new();
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..20 {
+        let mut tree: DurableTree<i32, RedBlackTree<i32>> = DurableTree::open_with(&dir, options).unwrap();
+        assert_eq!(contents(&tree), model.iter().cloned().collect::<Vec<_>>());
+        let mut last_record_start = log_len(&dir);
+        let mut last_change = None;
+        for _ in 0..(next() % 50) {
+            let value = (next() % 100) as i32;
+            let start = log_len(&dir);
+            let changed = if next() % 3 == 0 { tree.delete(value).unwrap() } else { tree.insert(value).unwrap() };
+            // a snapshot right after the change empties the log and nothing can be lost
+            if changed && log_len(&dir) > start {
+                last_record_start = start;
+                last_change = Some(value);
+            } else if changed {
+                last_change = None;
+                last_record_start = log_len(&dir);
+            }
+            if changed {
+                if model.contains(&value) { model.remove(&value); } else { model.insert(value); }
+            }
+        }
+        drop(tree);
+
+        let len = log_len(&dir);
+        let cut = len - next() % (len - last_record_start + 1);
+        if cut < len {
+            cut_log(&dir, cut);
+            // the record was cut, so its change never happened
+            let value = last_change.unwrap();
+            if model.contains(&value) { model.remove(&value); } else { model.insert(value); }
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,108 @@
+use project::avl_tree::AVLTree;
+use project::interchange::Json;
+use project::pretty::Layout;
+use project::redblack_tree::RedBlackTree;
+
+fn avl_of(values: &[i32]) -> AVLTree<i32> {
+    let mut tree = AVLTree::new_multiset();
+    for value in values {
+        tree.insert(*value);
+    }
+    tree
+}
+
+fn rb_of(values: &[i32]) -> RedBlackTree<i32> {
+    let mut tree = RedBlackTree::new_multiset();
+    for value in values {
+        tree.insert(*value);
+    }
+    tree
+}
+
+const VALUES: [i32; 12] = [50, 20, 80, 10, 30, 20, 70, 90, 20, 60, 5, 85];
+
+#[test]
+fn json_arrays_round_trip() {
+    let avl = avl_of(&VALUES);
+    let text = avl.to_json_array();
+    assert_eq!(text, "[5, 10, 20, 20, 20, 30, 50, 60, 70, 80, 85, 90]\n");
+    let loaded = AVLTree::<i32>::from_json(&text).unwrap();
+    assert_eq!(loaded.iter().collect::<Vec<_>>(), avl.iter().collect::<Vec<_>>());
+    assert_eq!(loaded.count(20), 3);
+    loaded.check_invariants().unwrap();
+
+    let rb = rb_of(&VALUES);
+    let loaded = RedBlackTree::<i32>::from_json(&rb.to_json_array()).unwrap();
+    assert_eq!(loaded.iter().collect::<Vec<_>>(), rb.iter().collect::<Vec<_>>());
+    loaded.check_invariants().unwrap();
+}
+
+#[test]
+fn json_trees_keep_their_shape() {
+    let avl = avl_of(&VALUES);
+    let loaded = AVLTree::<i32>::from_json(&avl.to_json_tree()).unwrap();
+    assert_eq!(loaded.to_pretty_string(Layout::Bracket), avl.to_pretty_string(Layout::Bracket));
+    assert_eq!(loaded.count(20), 3);
+
+    let rb = rb_of(&VALUES);
+    let loaded = RedBlackTree::<i32>::from_json(&rb.to_json_tree()).unwrap();
+    assert_eq!(loaded.to_pretty_string(Layout::Bracket), rb.to_pretty_string(Layout::Bracket));
+    assert_eq!(loaded.count(20), 3);
+}
+
+#[test]
+fn csv_round_trips() {
+    let avl = avl_of(&VALUES);
+    let text = avl.to_csv();
+    assert!(text.starts_with("value,count\n5,1\n10,1\n20,3\n30,1\n"));
+    let loaded = AVLTree::<i32>::from_csv(&text).unwrap();
+    assert_eq!(loaded.iter().collect::<Vec<_>>(), avl.iter().collect::<Vec<_>>());
+
+    let mut words = RedBlackTree::new();
+    for word in ["plain", "with,comma", "say \"hi\"", " padded"] {
+        words.insert(word.to_string());
+    }
+    let loaded = RedBlackTree::<String>::from_csv(&words.to_csv()).unwrap();
+    assert_eq!(loaded.iter().collect::<Vec<_>>(), words.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn unsorted_and_malformed_input_is_rejected() {
+    assert_eq!(AVLTree::<i32>::from_json("[1, 3, 2]").err().unwrap(), "item 3 is smaller than the one before it");
+    assert_eq!(AVLTree::<i32>::from_csv("value,count\n1,1\n3,1\n2,1\n").err().unwrap(),
+        "line 4: the value is smaller than the one before it");
+    assert_eq!(AVLTree::<i32>::from_csv("value,count\n1,0\n").err().unwrap(), "line 2: `0` is not a valid count");
+    assert_eq!(AVLTree::<i32>::from_csv("key\n1\n").err().unwrap(), "line 1: expected a `value,count` header, found `key`");
+    assert_eq!(AVLTree::<i32>::from_json("[1, 2,]").err().unwrap(), "line 1: expected a value");
+    assert_eq!(AVLTree::<i32>::from_json("[1, \"x\"]").err().unwrap(), "`x` is not a valid value");
+
+    // a red-black document does not load into an AVL tree
+    let rb = rb_of(&[2, 1, 3]);
+    assert_eq!(AVLTree::<i32>::from_json(&rb.to_json_tree()).err().unwrap(), "the document holds a `red-black` tree, not `avl`");
+    // nor does a shape that breaks the invariants
+    let chain = r#"{"type": "avl", "root": {"value": 1, "right": {"value": 2, "right": {"value": 3}}}}"#;
+    assert!(AVLTree::<i32>::from_json(chain).is_err());
+}
+
+#[test]
+fn deep_nesting_is_an_error() {
+    // far past what the stack could take
+    let error = Json::parse(&"[".repeat(200_000)).unwrap_err();
+    assert_eq!(error, "line 1: arrays and objects nested deeper than 512 levels");
+    assert!(Json::parse(&"{\"a\":".repeat(100_000)).is_err());
+    assert!(AVLTree::<i32>::from_json(&"[".repeat(200_000)).is_err());
+
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(Json::parse(&nested(512)).is_ok());
+    assert!(Json::parse(&nested(513)).is_err());
+}
+
+#[test]
+fn json_values_print_back() {
+    let text = r#"{"a": [1, -2.5e3, true, null], "b": {"c": "x\"y\n"}, "d": []}"#;
+    let json = Json::parse(text).unwrap();
+    assert_eq!(json.to_compact_string(), r#"{"a":[1,-2.5e3,true,null],"b":{"c":"x\"y\n"},"d":[]}"#);
+    assert_eq!(Json::parse(&json.to_pretty_string()).unwrap(), json);
+    assert_eq!(Json::parse("\"\\ud83c\\udf33\"").unwrap(), Json::String("\u{1f333}".to_string()));
+    assert_eq!(Json::parse("[1,\n2 3]").unwrap_err(), "line 2: expected `,` or `]` in an array");
+}
@@ -0,0 +1,71 @@
+use project::interval_tree::IntervalTree;
+use project::rng::XorShift64;
+
+// the intervals of `stored` that `keep` picks, in the order the tree reports them
+fn scan(stored: &[(i32, i32, usize)], keep: impl Fn(i32, i32) -> bool) -> Vec<(i32, i32, usize)> {
+    let mut found: Vec<_> = stored.iter().copied().filter(|&(start, end, _)| keep(start, end)).collect();
+    found.sort_by_key(|&(start, end, _)| (start, end));
+    found
+}
+
+#[test]
+fn stores_and_looks_up_half_open_intervals() {
+    let mut tree = IntervalTree::new();
+    assert!(tree.insert(5, 10, "a"));
+    assert!(tree.insert(1, 4, "b"));
+    assert!(tree.insert(5, 7, "c"));
+    // empty, reversed and repeated intervals are turned down
+    assert!(!tree.insert(3, 3, "x"));
+    assert!(!tree.insert(9, 2, "x"));
+    assert!(!tree.insert(5, 10, "x"));
+    assert_eq!(tree.len(), 3);
+    assert_eq!(tree.get(5, 10), Some("a"));
+    assert_eq!(tree.get(5, 8), None);
+    assert_eq!(tree.intervals(), vec![(1, 4, "b"), (5, 7, "c"), (5, 10, "a")]);
+
+    // the end point is not part of the interval
+    assert_eq!(tree.containing(4), vec![]);
+    assert_eq!(tree.containing(7), vec![(5, 10, "a")]);
+    assert_eq!(tree.overlapping(3, 6), vec![(1, 4, "b"), (5, 7, "c"), (5, 10, "a")]);
+    assert_eq!(tree.overlapping(4, 5), vec![]);
+    assert_eq!(tree.overlapping(6, 6), vec![]);
+
+    assert_eq!(tree.remove(5, 7), Some("c"));
+    assert_eq!(tree.remove(5, 7), None);
+    assert_eq!(tree.len(), 2);
+    assert_eq!(tree.containing(6), vec![(5, 10, "a")]);
+    assert_eq!(tree.remove(1, 4), Some("b"));
+    assert_eq!(tree.remove(5, 10), Some("a"));
+    assert!(tree.is_empty());
+    assert_eq!(tree.overlapping(i32::MIN, i32::MAX), vec![]);
+}
+
+#[test]
+fn queries_match_a_linear_scan() {
+    let mut rng = XorShift64::new(31);
+    let mut tree = IntervalTree::new();
+    let mut stored: Vec<(i32, i32, usize)> = Vec::new();
+    for step in 0..3000 {
+        let start = (rng.next_u64() % 500) as i32;
+        let end = start + 1 + (rng.next_u64() % 60) as i32;
+        if rng.next_u64().is_multiple_of(4) && !stored.is_empty() {
+            let (start, end, value) = stored.swap_remove((rng.next_u64() % stored.len() as u64) as usize);
+            assert_eq!(tree.remove(start, end), Some(value), "step {}", step);
+        } else if !stored.iter().any(|&(s, e, _)| (s, e) == (start, end)) {
+            assert!(tree.insert(start, end, step));
+            stored.push((start, end, step));
+        } else {
+            assert!(!tree.insert(start, end, step));
+        }
+        assert_eq!(tree.len(), stored.len());
+
+        if step.is_multiple_of(10) {
+            let point = (rng.next_u64() % 600) as i32 - 20;
+            assert_eq!(tree.containing(point), scan(&stored, |s, e| s <= point && point < e), "step {}", step);
+            let low = (rng.next_u64() % 600) as i32 - 20;
+            let high = low + (rng.next_u64() % 80) as i32;
+            assert_eq!(tree.overlapping(low, high), scan(&stored, |s, e| low < high && s < high && low < e), "step {}", step);
+        }
+    }
+    assert_eq!(tree.intervals(), scan(&stored, |_, _| true));
+}
@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use project::avl_tree::AVLTree;
+use project::ordered_set::OrderedSet;
+use project::redblack_tree::RedBlackTree;
+use project::rng::XorShift64;
+
+// the multiset operations of both trees, so one test body checks both
+trait Multiset {
+    fn new_multiset() -> Self;
+    fn insert(&mut self, value: i32);
+    fn count(&self, value: i32) -> usize;
+    fn remove_one(&mut self, value: i32) -> bool;
+    fn remove_all(&mut self, value: i32) -> usize;
+    fn values(&self) -> Vec<i32>;
+    fn nodes(&self) -> usize;
+    fn check(&self) -> Result<(), String>;
+}
+
+impl Multiset for AVLTree<i32> {
+    fn new_multiset() -> Self {
+        AVLTree::new_multiset()
+    }
+
+    fn insert(&mut self, value: i32) {
+        AVLTree::insert(self, value);
+    }
+
+    fn count(&self, value: i32) -> usize {
+        AVLTree::count(self, value)
+    }
+
+    fn remove_one(&mut self, value: i32) -> bool {
+        AVLTree::remove_one(self, value)
+    }
+
+    fn remove_all(&mut self, value: i32) -> usize {
+        AVLTree::remove_all(self, value)
+    }
+
+    fn values(&self) -> Vec<i32> {
+        self.iter().collect()
+    }
+
+    fn nodes(&self) -> usize {
+        OrderedSet::len(self)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        self.check_invariants()
+    }
+}
+
+impl Multiset for RedBlackTree<i32> {
+    fn new_multiset() -> Self {
+        RedBlackTree::new_multiset()
+    }
+
+    fn insert(&mut self, value: i32) {
+        RedBlackTree::insert(self, value);
+    }
+
+    fn count(&self, value: i32) -> usize {
+        RedBlackTree::count(self, value)
+    }
+
+    fn remove_one(&mut self, value: i32) -> bool {
+        RedBlackTree::remove_one(self, value)
+    }
+
+    fn remove_all(&mut self, value: i32) -> usize {
+        RedBlackTree::remove_all(self, value)
+    }
+
+    fn values(&self) -> Vec<i32> {
+        self.iter().collect()
+    }
+
+    fn nodes(&self) -> usize {
+        OrderedSet::len(self)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        self.check_invariants()
+    }
+}
+
+fn duplicates_are_counted<Tree: Multiset>() {
+    let mut tree = Tree::new_multiset();
+    for value in [5, 3, 5, 8, 5, 3] {
+        tree.insert(value);
+    }
+    assert_eq!(tree.values(), vec![3, 3, 5, 5, 5, 8]);
+    assert_eq!((tree.count(3), tree.count(5), tree.count(8), tree.count(4)), (2, 3, 1, 0));
+    // one node per distinct value
+    assert_eq!(tree.nodes(), 3);
+
+    assert!(tree.remove_one(5));
+    assert_eq!(tree.values(), vec![3, 3, 5, 5, 8]);
+    assert_eq!(tree.nodes(), 3);
+    assert!(tree.remove_one(8));
+    assert_eq!(tree.count(8), 0);
+    assert_eq!(tree.nodes(), 2);
+    assert!(!tree.remove_one(8));
+
+    assert_eq!(tree.remove_all(5), 2);
+    assert_eq!(tree.remove_all(5), 0);
+    assert_eq!(tree.values(), vec![3, 3]);
+    assert_eq!(tree.nodes(), 1);
+    tree.check().unwrap();
+}
+
+#[test]
+fn avl_tree_counts_duplicates() {
+    duplicates_are_counted::<AVLTree<i32>>();
+}
+
+#[test]
+fn red_black_tree_counts_duplicates() {
+    duplicates_are_counted::<RedBlackTree<i32>>();
+}
+
+// random inserts and removals against a map from value to count
+fn matches_a_counting_map<Tree: Multiset>(seed: u64) {
+    let mut rng = XorShift64::new(seed);
+    let mut tree = Tree::new_multiset();
+    let mut model: BTreeMap<i32, usize> = BTreeMap::new();
+    for step in 0..3000 {
+        let value = (rng.next_u64() % 60) as i32;
+        match rng.next_u64() % 5 {
+            0..=2 => {
+                tree.insert(value);
+                *model.entry(value).or_default() += 1;
+            },
+            3 => {
+                let present = model.contains_key(&value);
+                assert_eq!(tree.remove_one(value), present, "step {}", step);
+                if present {
+                    let count = model.get_mut(&value).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        model.remove(&value);
+                    }
+                }
+            },
+            _ => {
+                let expected = model.remove(&value).unwrap_or(0);
+                assert_eq!(tree.remove_all(value), expected, "step {}", step);
+            },
+        }
+        assert_eq!(tree.count(value), model.get(&value).copied().unwrap_or(0), "step {}", step);
+        if step % 100 == 0 {
+            tree.check().unwrap();
+        }
+    }
+    let expected: Vec<i32> = model.iter().flat_map(|(value, count)| std::iter::repeat_n(*value, *count)).collect();
+    assert_eq!(tree.values(), expected);
+    assert_eq!(tree.nodes(), model.len());
+    tree.check().unwrap();
+}
+
+#[test]
+fn avl_tree_matches_a_counting_map() {
+    matches_a_counting_map::<AVLTree<i32>>(7);
+}
+
+#[test]
+fn red_black_tree_matches_a_counting_map() {
+    matches_a_counting_map::<RedBlackTree<i32>>(8);
+}
+
+#[test]
+fn sets_turn_duplicates_down() {
+    let mut avl = AVLTree::new();
+    let mut rb = RedBlackTree::new();
+    let inserted: Vec<(bool, bool)> = [2, 1, 2, 3, 2].into_iter()
+        .map(|value| (OrderedSet::insert(&mut avl, value), OrderedSet::insert(&mut rb, value)))
+        .collect();
+    assert_eq!(inserted, vec![(true, true), (true, true), (false, false), (true, true), (false, false)]);
+    assert_eq!(avl.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(rb.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!((avl.count(2), rb.count(2)), (1, 1));
+}
+
+#[test]
+fn rank_counts_every_smaller_occurrence() {
+    let mut rng = XorShift64::new(9);
+    let mut avl = AVLTree::new_multiset();
+    let mut rb = RedBlackTree::new_multiset();
+    let mut values = Vec::new();
+    for step in 0..2000 {
+        let value = (rng.next_u64() % 200) as i32;
+        if step % 4 == 3 {
+            let removed = avl.remove_one(value);
+            assert_eq!(rb.remove_one(value), removed);
+            if removed {
+                values.remove(values.iter().position(|v| *v == value).unwrap());
+            }
+        } else {
+            avl.insert(value);
+            rb.insert(value);
+            values.push(value);
+        }
+    }
+    avl.check_invariants().unwrap();
+    rb.check_invariants().unwrap();
+    for probe in -1..=201 {
+        let expected = values.iter().filter(|value| **value < probe).count();
+        assert_eq!(avl.rank(probe), expected, "AVL rank of {}", probe);
+        assert_eq!(rb.rank(probe), expected, "red-black rank of {}", probe);
+    }
+}
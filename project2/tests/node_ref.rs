@@ -0,0 +1,106 @@
+use project::augment::Sum;
+use project::avl_tree::{AVLTree, NodeRef as AvlNode};
+use project::redblack_tree::{NodeColor, NodeRef as RbNode, RedBlackTree};
+use project::rng::XorShift64;
+
+fn avl_keys(node: Option<AvlNode<i32, Sum<i32>>>) -> Vec<i32> {
+    match node {
+        Some(node) => {
+            let mut keys = avl_keys(node.left());
+            keys.push(node.key());
+            keys.extend(avl_keys(node.right()));
+            keys
+        },
+        None => Vec::new(),
+    }
+}
+
+fn rb_values(node: Option<RbNode<i32, Sum<i32>>>) -> Vec<i32> {
+    match node {
+        Some(node) => {
+            let mut values = rb_values(node.left());
+            values.push(node.value());
+            values.extend(rb_values(node.right()));
+            values
+        },
+        None => Vec::new(),
+    }
+}
+
+#[test]
+fn avl_handles_show_the_shape_of_the_tree() {
+    let mut tree: AVLTree<i32> = AVLTree::new();
+    for value in 1..=7 {
+        tree.insert(value);
+    }
+    let root = tree.root_node().unwrap();
+    assert_eq!(root.key(), 4);
+    assert!(root.parent().is_none());
+    let (left, right) = (root.left().unwrap(), root.right().unwrap());
+    assert_eq!((left.key(), right.key()), (2, 6));
+    assert_eq!(left.parent().unwrap().key(), 4);
+    let leaf = left.left().unwrap();
+    assert_eq!(leaf.key(), 1);
+    assert!(leaf.left().is_none() && leaf.right().is_none());
+    assert_eq!(root.height() - leaf.height(), 2);
+    assert_eq!((root.balance_factor(), left.balance_factor()), (0, 0));
+
+    let found = tree.search(5).unwrap();
+    assert_eq!((found.key(), found.count()), (5, 1));
+    assert_eq!(found.parent().unwrap().key(), 6);
+    assert!(tree.search(8).is_none());
+    assert!(AVLTree::<i32>::new().root_node().is_none());
+}
+
+#[test]
+fn red_black_handles_show_colors_and_links() {
+    let mut tree: RedBlackTree<i32> = RedBlackTree::new();
+    for value in 1..=7 {
+        tree.insert(value);
+    }
+    let root = tree.root_node().unwrap();
+    assert_eq!((root.value(), root.color()), (2, NodeColor::Black));
+    assert!(root.parent().is_none());
+    let right = root.right().unwrap();
+    assert_eq!((right.value(), right.color()), (4, NodeColor::Red));
+    let six = right.right().unwrap();
+    assert_eq!((six.value(), six.color()), (6, NodeColor::Black));
+    let seven = six.right().unwrap();
+    assert_eq!((seven.value(), seven.color()), (7, NodeColor::Red));
+    assert_eq!(seven.parent().unwrap().value(), 6);
+
+    let found = tree.try_get_node(3).unwrap();
+    assert_eq!((found.value(), found.color(), found.count()), (3, NodeColor::Black, 1));
+    assert_eq!(found.parent().unwrap().value(), 4);
+    assert!(tree.try_get_node(0).is_none());
+    assert!(RedBlackTree::<i32>::new().root_node().is_none());
+}
+
+#[test]
+fn handles_report_occurrences_and_subtree_summaries() {
+    let mut avl: AVLTree<i32, Sum<i32>> = AVLTree::multiset_with_summary();
+    let mut rb: RedBlackTree<i32, Sum<i32>> = RedBlackTree::multiset_with_summary();
+    let mut rng = XorShift64::new(7);
+    let mut values = Vec::new();
+    for _ in 0..200 {
+        let value = (rng.next_u64() % 50) as i32;
+        avl.insert(value);
+        rb.insert(value);
+        values.push(value);
+    }
+    values.sort();
+    let total: i32 = values.iter().sum();
+    let occurrences = values.iter().filter(|&&value| value == values[0]).count();
+
+    let root = avl.root_node().unwrap();
+    assert_eq!(root.summary(), Sum(total));
+    assert_eq!(avl.search(values[0]).unwrap().count(), occurrences);
+    let mut distinct = values.clone();
+    distinct.dedup();
+    assert_eq!(avl_keys(Some(root)), distinct);
+
+    let root = rb.root_node().unwrap();
+    assert_eq!(root.summary(), Sum(total));
+    assert_eq!(rb.try_get_node(values[0]).unwrap().count(), occurrences);
+    assert_eq!(rb_values(Some(root)), distinct);
+}
@@ -0,0 +1,209 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use project::avl_tree::AVLTree;
+use project::observer::{Direction, TreeObserver};
+use project::redblack_tree::RedBlackTree;
+
+// how often each key was reported inserted and deleted
+#[derive(Default)]
+struct Events {
+    inserted: BTreeMap<i32, usize>,
+    deleted: BTreeMap<i32, usize>,
+}
+
+impl TreeObserver<i32> for Events {
+    fn on_insert(&mut self, key: &i32) {
+        *self.inserted.entry(*key).or_default() += 1;
+    }
+
+    fn on_delete(&mut self, key: &i32) {
+        *self.deleted.entry(*key).or_default() += 1;
+    }
+}
+
+impl Events {
+    fn deleted(&self, key: i32) -> usize {
+        self.deleted.get(&key).copied().unwrap_or(0)
+    }
+
+    fn total_deleted(&self) -> usize {
+        self.deleted.values().sum()
+    }
+}
+
+// the removals both trees offer, so one test body checks both
+trait Multiset {
+    fn new_multiset() -> Self;
+    fn attach(&mut self, events: Rc<RefCell<Events>>);
+    fn insert(&mut self, value: i32);
+    fn delete(&mut self, value: i32);
+    fn remove_one(&mut self, value: i32) -> bool;
+    fn remove_all(&mut self, value: i32) -> usize;
+    // remove the node holding `value` through a cursor
+    fn remove_at_cursor(&mut self, value: i32) -> Option<i32>;
+}
+
+impl Multiset for AVLTree<i32> {
+    fn new_multiset() -> Self {
+        AVLTree::new_multiset()
+    }
+
+    fn attach(&mut self, events: Rc<RefCell<Events>>) {
+        self.set_observer(Some(events));
+    }
+
+    fn insert(&mut self, value: i32) {
+        AVLTree::insert(self, value);
+    }
+
+    fn delete(&mut self, value: i32) {
+        AVLTree::delete(self, value);
+    }
+
+    fn remove_one(&mut self, value: i32) -> bool {
+        AVLTree::remove_one(self, value)
+    }
+
+    fn remove_all(&mut self, value: i32) -> usize {
+        AVLTree::remove_all(self, value)
+    }
+
+    fn remove_at_cursor(&mut self, value: i32) -> Option<i32> {
+        self.lower_bound_cursor(value).remove_current()
+    }
+}
+
+impl Multiset for RedBlackTree<i32> {
+    fn new_multiset() -> Self {
+        RedBlackTree::new_multiset()
+    }
+
+    fn attach(&mut self, events: Rc<RefCell<Events>>) {
+        self.set_observer(Some(events));
+    }
+
+    fn insert(&mut self, value: i32) {
+        RedBlackTree::insert(self, value);
+    }
+
+    fn delete(&mut self, value: i32) {
+        RedBlackTree::delete(self, value);
+    }
+
+    fn remove_one(&mut self, value: i32) -> bool {
+        RedBlackTree::remove_one(self, value)
+    }
+
+    fn remove_all(&mut self, value: i32) -> usize {
+        RedBlackTree::remove_all(self, value)
+    }
+
+    fn remove_at_cursor(&mut self, value: i32) -> Option<i32> {
+        self.lower_bound_cursor(value).remove_current()
+    }
+}
+
+// 1 to 6 with i occurrences of i, 21 in all
+fn filled<Tree: Multiset>() -> (Tree, Rc<RefCell<Events>>) {
+    let mut tree = Tree::new_multiset();
+    let events = Rc::new(RefCell::new(Events::default()));
+    tree.attach(events.clone());
+    for value in 1..=6 {
+        for _ in 0..value {
+            tree.insert(value);
+        }
+    }
+    (tree, events)
+}
+
+fn every_occurrence_is_reported<Tree: Multiset>() {
+    let (mut tree, events) = filled::<Tree>();
+    let inserted: Vec<(i32, usize)> = events.borrow().inserted.clone().into_iter().collect();
+    assert_eq!(inserted, vec![(1, 1), (2, 2), (3, 3), (4, 4), (5, 5), (6, 6)]);
+    assert_eq!(events.borrow().total_deleted(), 0);
+
+    // one occurrence of a key that has more
+    assert!(tree.remove_one(4));
+    assert_eq!(events.borrow().deleted(4), 1);
+    // the last occurrence takes the node with it
+    assert!(tree.remove_one(1));
+    assert_eq!(events.borrow().deleted(1), 1);
+    // every occurrence, one event each
+    assert_eq!(tree.remove_all(3), 3);
+    assert_eq!(events.borrow().deleted(3), 3);
+    tree.delete(5);
+    assert_eq!(events.borrow().deleted(5), 5);
+    assert_eq!(tree.remove_at_cursor(6), Some(6));
+    assert_eq!(events.borrow().deleted(6), 6);
+    // nothing there, nothing reported
+    assert!(!tree.remove_one(1));
+    assert_eq!(tree.remove_all(3), 0);
+    assert_eq!(events.borrow().total_deleted(), 1 + 1 + 3 + 5 + 6);
+
+    // what is left: 2 twice and 4 three times
+    assert_eq!(tree.remove_all(2) + tree.remove_all(4), 5);
+    assert_eq!(events.borrow().total_deleted(), 21);
+    assert_eq!(events.borrow().deleted, events.borrow().inserted);
+}
+
+#[test]
+fn avl_tree_reports_every_occurrence() {
+    every_occurrence_is_reported::<AVLTree<i32>>();
+}
+
+#[test]
+fn red_black_tree_reports_every_occurrence() {
+    every_occurrence_is_reported::<RedBlackTree<i32>>();
+}
+
+#[derive(Default)]
+struct Structure {
+    rotations: usize,
+    recolors: usize,
+    height_changes: usize,
+}
+
+impl TreeObserver<i32> for Structure {
+    fn on_rotate(&mut self, _pivot: &i32, _direction: Direction) {
+        self.rotations += 1;
+    }
+
+    fn on_recolor(&mut self, _key: &i32, _red: bool) {
+        self.recolors += 1;
+    }
+
+    fn on_height_change(&mut self, _key: &i32, _old: i32, _new: i32) {
+        self.height_changes += 1;
+    }
+}
+
+#[test]
+fn rotations_match_the_counters() {
+    let structure = Rc::new(RefCell::new(Structure::default()));
+    let mut avl = AVLTree::new();
+    avl.set_observer(Some(structure.clone()));
+    avl.enable_stats();
+    for value in 0..100 {
+        avl.insert(value);
+    }
+    let stats = avl.stats();
+    assert_eq!(structure.borrow().rotations as u64, stats.single_rotations + 2 * stats.double_rotations);
+    assert!(structure.borrow().height_changes > 0);
+    assert_eq!(structure.borrow().recolors, 0);
+
+    let structure = Rc::new(RefCell::new(Structure::default()));
+    let mut rb = RedBlackTree::new();
+    rb.set_observer(Some(structure.clone()));
+    rb.enable_stats();
+    for value in 0..100 {
+        rb.insert(value);
+    }
+    for value in (0..100).step_by(3) {
+        rb.delete(value);
+    }
+    let stats = rb.stats();
+    assert_eq!(structure.borrow().rotations as u64, stats.single_rotations + 2 * stats.double_rotations);
+    assert_eq!(structure.borrow().recolors as u64, stats.recolors);
+    assert_eq!(structure.borrow().height_changes, 0);
+}
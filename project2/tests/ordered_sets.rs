@@ -0,0 +1,187 @@
+use std::collections::BTreeSet;
+use project::aa_tree::AATree;
+use project::btree::BTree;
+use project::llrb_tree::LLRBTree;
+use project::ordered_set::OrderedSet;
+use project::rng::XorShift64;
+use project::scapegoat_tree::ScapegoatTree;
+use project::skip_list::SkipList;
+use project::sorted_vec_set::SortedVecSet;
+use project::splay_tree::SplayTree;
+use project::treap::Treap;
+use project::wavl_tree::WAVLTree;
+
+// random inserts and deletes against a BTreeSet, with the invariants checked after every change
+fn matches_a_btree_set<S: OrderedSet<i32>>(seed: u64, range: u64, steps: usize) -> S {
+    let mut rng = XorShift64::new(seed);
+    let mut set = S::new();
+    let mut model = BTreeSet::new();
+    for step in 0..steps {
+        let value = (rng.next_u64() % range) as i32;
+        if rng.next_u64().is_multiple_of(3) {
+            assert_eq!(set.delete(value), model.remove(&value), "seed {} step {}", seed, step);
+        } else {
+            assert_eq!(set.insert(value), model.insert(value), "seed {} step {}", seed, step);
+        }
+        if let Err(error) = set.check_invariants() {
+            panic!("seed {} step {}: {}", seed, step, error);
+        }
+        assert_eq!(set.len(), model.len());
+    }
+    assert_eq!(set.to_vec(), model.iter().copied().collect::<Vec<_>>());
+    for value in 0..range as i32 {
+        assert_eq!(set.contains(value), model.contains(&value));
+    }
+    set
+}
+
+// sorted runs take the most rebalancing, then everything is deleted again
+fn survives_sorted_runs<S: OrderedSet<i32>>() {
+    let mut set = S::new();
+    for value in 0..500 {
+        assert!(set.insert(value));
+    }
+    for value in (-500..0).rev() {
+        assert!(set.insert(value));
+    }
+    set.check_invariants().unwrap();
+    assert_eq!(set.to_vec(), (-500..500).collect::<Vec<_>>());
+    for value in (-500..500).step_by(2) {
+        assert!(set.delete(value));
+    }
+    set.check_invariants().unwrap();
+    for value in (-499..500).step_by(2).rev() {
+        assert!(set.delete(value));
+        set.check_invariants().unwrap();
+    }
+    assert!(set.is_empty());
+    assert_eq!((set.height(), set.leaves()), (0, 0));
+}
+
+// at most `factor` log2(n + 1) levels for n values
+fn assert_logarithmic<S: OrderedSet<i32>>(set: &S, factor: f64) {
+    let bound = factor * ((set.len() + 1) as f64).log2();
+    assert!(set.height() as f64 <= bound, "{} values at height {}", set.len(), set.height());
+}
+
+#[test]
+fn llrb_tree_keeps_its_invariants() {
+    // like any red-black tree, at most twice the height of a perfectly balanced one
+    for seed in 1..10 {
+        let set: LLRBTree<i32> = matches_a_btree_set(seed, 200, 1500);
+        assert_logarithmic(&set, 2.0);
+    }
+    let set: LLRBTree<i32> = matches_a_btree_set(99, 100_000, 5000);
+    assert_logarithmic(&set, 2.0);
+    survives_sorted_runs::<LLRBTree<i32>>();
+}
+
+#[test]
+fn treap_keeps_its_invariants() {
+    for seed in 1..10 {
+        matches_a_btree_set::<Treap<i32>>(seed, 200, 1500);
+    }
+    matches_a_btree_set::<Treap<i32>>(99, 100_000, 5000);
+    survives_sorted_runs::<Treap<i32>>();
+}
+
+#[test]
+fn splay_tree_keeps_its_invariants() {
+    for seed in 1..10 {
+        matches_a_btree_set::<SplayTree<i32>>(seed, 200, 1500);
+    }
+    matches_a_btree_set::<SplayTree<i32>>(99, 100_000, 5000);
+    survives_sorted_runs::<SplayTree<i32>>();
+}
+
+#[test]
+fn scapegoat_tree_keeps_its_invariants() {
+    for seed in 1..10 {
+        matches_a_btree_set::<ScapegoatTree<i32>>(seed, 200, 1500);
+    }
+    matches_a_btree_set::<ScapegoatTree<i32>>(99, 100_000, 5000);
+    survives_sorted_runs::<ScapegoatTree<i32>>();
+}
+
+#[test]
+fn aa_tree_keeps_its_invariants() {
+    // an AA tree is a red-black tree whose red links all lean right
+    for seed in 1..10 {
+        let set: AATree<i32> = matches_a_btree_set(seed, 200, 1500);
+        assert_logarithmic(&set, 2.0);
+    }
+    let set: AATree<i32> = matches_a_btree_set(99, 100_000, 5000);
+    assert_logarithmic(&set, 2.0);
+    survives_sorted_runs::<AATree<i32>>();
+}
+
+#[test]
+fn wavl_tree_keeps_its_invariants() {
+    for seed in 1..10 {
+        let set: WAVLTree<i32> = matches_a_btree_set(seed, 200, 1500);
+        assert_logarithmic(&set, 2.0);
+    }
+    // without deletes a WAVL tree is an AVL tree
+    let mut set = WAVLTree::new();
+    for value in 0..4096 {
+        set.insert(value);
+    }
+    set.check_invariants().unwrap();
+    assert_logarithmic(&set, 1.45);
+    survives_sorted_runs::<WAVLTree<i32>>();
+}
+
+// every node below the root holds at least B - 1 keys, so n keys need at most
+// 1 + log_B((n + 1) / 2) levels
+fn btree_keeps_its_invariants<const B: usize>() {
+    let bound = |len: usize| 1.0 + ((len + 1) as f64 / 2.0).log(B as f64);
+    for seed in 1..6 {
+        let set: BTree<i32, B> = matches_a_btree_set(seed, 300, 2000);
+        assert!(set.height() as f64 <= bound(set.len()));
+    }
+    let set: BTree<i32, B> = matches_a_btree_set(99, 100_000, 5000);
+    assert!(set.height() as f64 <= bound(set.len()));
+    survives_sorted_runs::<BTree<i32, B>>();
+}
+
+#[test]
+fn btree_keeps_its_invariants_for_every_order() {
+    btree_keeps_its_invariants::<2>();
+    btree_keeps_its_invariants::<3>();
+    btree_keeps_its_invariants::<8>();
+    btree_keeps_its_invariants::<32>();
+}
+
+#[test]
+fn skip_list_keeps_its_invariants() {
+    for seed in 1..10 {
+        matches_a_btree_set::<SkipList<i32>>(seed, 200, 1500);
+    }
+    matches_a_btree_set::<SkipList<i32>>(99, 100_000, 5000);
+    survives_sorted_runs::<SkipList<i32>>();
+}
+
+#[test]
+fn skip_lists_with_the_same_seed_grow_the_same_towers() {
+    let (mut first, mut second, mut other) = (SkipList::with_seed(38), SkipList::with_seed(38), SkipList::with_seed(39));
+    for value in 0..2000 {
+        first.insert(value);
+        second.insert(value);
+        other.insert(value);
+    }
+    assert_eq!(OrderedSet::height(&first), OrderedSet::height(&second));
+    // about log2(n) levels, whatever the seed
+    for list in [&first, &other] {
+        let levels = OrderedSet::height(list);
+        assert!((6..=22).contains(&levels), "{} levels", levels);
+    }
+}
+
+#[test]
+fn sorted_vec_set_keeps_its_invariants() {
+    for seed in 1..10 {
+        matches_a_btree_set::<SortedVecSet<i32>>(seed, 200, 1500);
+    }
+    matches_a_btree_set::<SortedVecSet<i32>>(99, 100_000, 5000);
+    survives_sorted_runs::<SortedVecSet<i32>>();
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+use project::persistent_tree::PersistentAVLTree;
+use project::rng::XorShift64;
+
+fn values(tree: &PersistentAVLTree<i32>) -> Vec<i32> {
+    tree.iter().collect()
+}
+
+// an AVL tree of n keys is at most about 1.44 log2(n + 2) high
+fn assert_balanced(tree: &PersistentAVLTree<i32>) {
+    let bound = 1.45 * ((tree.len() + 2) as f64).log2();
+    assert!(f64::from(tree.tree_height()) <= bound, "{} keys at height {}", tree.len(), tree.tree_height());
+}
+
+#[test]
+fn every_version_keeps_its_own_keys() {
+    let mut rng = XorShift64::new(32);
+    let mut versions = vec![PersistentAVLTree::new()];
+    let mut models = vec![BTreeSet::new()];
+    for _ in 0..2000 {
+        let value = (rng.next_u64() % 300) as i32;
+        // branch off a random older version now and then, not only the latest one
+        let base = if rng.next_u64().is_multiple_of(5) { (rng.next_u64() % versions.len() as u64) as usize } else { versions.len() - 1 };
+        let mut model = models[base].clone();
+        let version = if rng.next_u64().is_multiple_of(3) {
+            model.remove(&value);
+            versions[base].remove(&value)
+        } else {
+            model.insert(value);
+            versions[base].insert(value)
+        };
+        versions.push(version);
+        models.push(model);
+    }
+    for (version, model) in versions.iter().zip(&models) {
+        assert_eq!(values(version), model.iter().copied().collect::<Vec<_>>());
+        assert_eq!(version.len(), model.len());
+        assert_balanced(version);
+        version.check_invariants().unwrap();
+    }
+}
+
+#[test]
+fn changes_that_change_nothing_return_the_same_version() {
+    let tree = [1, 3, 5, 7].into_iter().fold(PersistentAVLTree::new(), |tree, value| tree.insert(value));
+    assert!(tree.insert(3).shares_root_with(&tree));
+    assert!(tree.remove(&4).shares_root_with(&tree));
+    assert!(!tree.insert(4).shares_root_with(&tree));
+
+    let removed = tree.remove(&3);
+    assert_eq!(values(&removed), vec![1, 5, 7]);
+    assert_eq!(values(&tree), vec![1, 3, 5, 7]);
+    assert!(tree.contains(&3) && !removed.contains(&3));
+    assert!(PersistentAVLTree::<i32>::new().is_empty());
+}
+
+#[test]
+fn bulk_operations_leave_their_inputs_alone() {
+    let evens = PersistentAVLTree::from_sorted(&(0..100).map(|v| v * 2).collect::<Vec<_>>());
+    let threes = PersistentAVLTree::from_sorted(&(0..67).map(|v| v * 3).collect::<Vec<_>>());
+    let union = evens.union(&threes);
+    let both = evens.intersection(&threes);
+    assert_eq!(values(&union), (0..200).filter(|v| v % 2 == 0 || v % 3 == 0).collect::<Vec<_>>());
+    assert_eq!(values(&both), (0..200).filter(|v| v % 6 == 0).collect::<Vec<_>>());
+    assert_balanced(&union);
+    assert_balanced(&both);
+    union.check_invariants().unwrap();
+    both.check_invariants().unwrap();
+
+    let (below, found, above) = union.split(&60);
+    assert!(found);
+    assert_eq!(values(&below), values(&union).into_iter().filter(|&v| v < 60).collect::<Vec<_>>());
+    assert_eq!(values(&above), values(&union).into_iter().filter(|&v| v > 60).collect::<Vec<_>>());
+    below.check_invariants().unwrap();
+    above.check_invariants().unwrap();
+    assert!(!union.split(&61).1);
+
+    assert_eq!(evens.len(), 100);
+    assert_eq!(threes.len(), 67);
+    assert_eq!(evens.map(|v| v + 1), (0..100).map(|v| v * 2 + 1).collect::<Vec<_>>());
+}
+
+// the parallel versions split the work the same way, so even the heights agree
+#[test]
+fn parallel_bulk_operations_match_the_sequential_ones() {
+    let mut rng = XorShift64::new(34);
+    for size in [0, 1, 7, 1000, 20_000] {
+        let mut left: Vec<i32> = (0..size).map(|_| (rng.next_u64() % 50_000) as i32).collect();
+        left.sort();
+        left.dedup();
+        let mut right: Vec<i32> = (0..size).map(|_| (rng.next_u64() % 50_000) as i32).collect();
+        right.sort();
+        right.dedup();
+        let (a, b) = (PersistentAVLTree::from_sorted(&left), PersistentAVLTree::from_sorted(&right));
+        let union = a.union(&b);
+        let both = a.intersection(&b);
+        for threads in [0, 1, 2, 3, 8] {
+            let par_a = PersistentAVLTree::par_from_sorted(&left, threads);
+            assert_eq!(values(&par_a), left);
+            assert_eq!(par_a.tree_height(), a.tree_height());
+            par_a.check_invariants().unwrap();
+
+            let par_union = a.par_union(&b, threads);
+            assert_eq!(values(&par_union), values(&union));
+            assert_eq!(par_union.tree_height(), union.tree_height());
+            par_union.check_invariants().unwrap();
+            let par_both = a.par_intersection(&b, threads);
+            assert_eq!(values(&par_both), values(&both));
+            assert_eq!(par_both.tree_height(), both.tree_height());
+            par_both.check_invariants().unwrap();
+
+            assert_eq!(union.par_map(threads, |v| i64::from(*v) * 3), union.map(|v| i64::from(*v) * 3));
+        }
+    }
+}
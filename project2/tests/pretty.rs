@@ -0,0 +1,80 @@
+use project::avl_tree::AVLTree;
+use project::pretty::Layout;
+use project::redblack_tree::RedBlackTree;
+
+fn avl_multiset() -> AVLTree<i32> {
+    let mut tree = AVLTree::new_multiset();
+    for value in [4, 2, 6, 1, 3, 3] {
+        tree.insert(value);
+    }
+    tree
+}
+
+fn red_black() -> RedBlackTree<i32> {
+    let mut tree = RedBlackTree::new();
+    for value in 1..=5 {
+        tree.insert(value);
+    }
+    tree
+}
+
+#[test]
+fn horizontal_layout_indents_children_under_branches() {
+    assert_eq!(avl_multiset().to_pretty_string(Layout::Horizontal), "\
+4
+├── 2
+│   ├── 1
+│   └── 3*2
+└── 6
+");
+    assert_eq!(red_black().to_pretty_string(Layout::Horizontal), "\
+2:B
+├── 1:B
+└── 4:B
+    ├── 3:R
+    └── 5:R
+");
+    // Display is the horizontal layout
+    assert_eq!(avl_multiset().to_string(), avl_multiset().to_pretty_string(Layout::Horizontal));
+}
+
+#[test]
+fn vertical_layout_puts_the_root_on_top() {
+    assert_eq!(avl_multiset().to_pretty_string(Layout::Vertical), "
+ ┌───4┐
+┌2─┐  6
+1 3*2
+".trim_start_matches('\n'));
+    assert_eq!(red_black().to_pretty_string(Layout::Vertical), "
+ ┌─2:B────┐
+1:B    ┌─4:B─┐
+      3:R   5:R
+".trim_start_matches('\n'));
+}
+
+#[test]
+fn bracket_layout_is_one_line() {
+    assert_eq!(avl_multiset().to_pretty_string(Layout::Bracket), "(4 (2 1 3*2) 6)");
+    assert_eq!(red_black().to_pretty_string(Layout::Bracket), "(2:B 1:B (4:B 3:R 5:R))");
+    let mut out = Vec::new();
+    red_black().write_pretty_io(&mut out, Layout::Bracket).unwrap();
+    assert_eq!(out, b"(2:B 1:B (4:B 3:R 5:R))");
+}
+
+#[test]
+fn a_missing_child_keeps_its_place() {
+    let mut tree: RedBlackTree<String> = RedBlackTree::new();
+    tree.insert("b".to_string());
+    tree.insert("a b".to_string());
+    assert_eq!(tree.to_pretty_string(Layout::Horizontal), "\"b\":B\n├── \"a b\":R\n└── _\n");
+    assert_eq!(tree.to_pretty_string(Layout::Vertical), "   ┌───\"b\":B\n\"a b\":R\n");
+    assert_eq!(tree.to_pretty_string(Layout::Bracket), "(\"b\":B \"a b\":R _)");
+}
+
+#[test]
+fn empty_trees() {
+    let tree: AVLTree<i32> = AVLTree::new();
+    assert_eq!(tree.to_pretty_string(Layout::Horizontal), "(empty)\n");
+    assert_eq!(tree.to_pretty_string(Layout::Vertical), "(empty)\n");
+    assert_eq!(tree.to_pretty_string(Layout::Bracket), "_");
+}
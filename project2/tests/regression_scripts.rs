@@ -0,0 +1,72 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+// Runs the scripts under tests/scripts through the command-line mode: those in `common`
+// against both trees, those in `avl` and `rb` against that tree only.
+
+fn run_script(tree: &str, script: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_project"))
+        .args(["--tree", tree, "--script"])
+        .arg(script)
+        .output()
+        .unwrap()
+}
+
+fn run_stdin(tree: &str, script: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_project"))
+        .args(["--tree", tree, "--script", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn run_all(tree: &str, dirs: &[&str]) {
+    let mut ran = 0;
+    for dir in dirs {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scripts").join(dir);
+        let mut scripts: Vec<_> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        scripts.sort();
+        for script in scripts {
+            let output = run_script(tree, &script);
+            assert!(output.status.success(), "{} on the {} tree:\n{}",
+                script.display(), tree, String::from_utf8_lossy(&output.stderr));
+            ran += 1;
+        }
+    }
+    assert!(ran > 0, "no scripts for the {} tree", tree);
+}
+
+#[test]
+fn avl_tree_passes_its_scripts() {
+    run_all("avl", &["common", "avl"]);
+}
+
+#[test]
+fn red_black_tree_passes_its_scripts() {
+    run_all("rb", &["common", "rb"]);
+}
+
+#[test]
+fn a_failed_assertion_names_its_line() {
+    let output = run_stdin("avl", "insert 1 2 3\n# a comment\n\nassert-height 5\nlen\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("<stdin>:4: assert-height failed: expected height 5, found 2"), "{}", stderr);
+    // the commands before it ran, the ones after it did not
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 1);
+}
+
+#[test]
+fn a_bad_line_stops_the_script_before_it_starts() {
+    let output = run_stdin("rb", "insert 1\ninsert 2\ninsert three\n");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("<stdin>:3: `three` is not a valid value"), "{}", stderr);
+    assert!(output.stdout.is_empty());
+}
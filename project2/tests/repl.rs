@@ -0,0 +1,56 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Drives `project --repl` through its standard input.
+
+fn repl(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_project"))
+        .arg("--repl")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn commands_prefixes_and_aliases() {
+    let output = repl(&["--history", "none"], "i 5 3 8 1 4 7 9\nrange 3 7\nrank 6\nhei\nd 5\nc 5 7\n");
+    assert!(output.contains("Insert [5, 3, 8, 1, 4, 7, 9] successfully."), "{}", output);
+    assert!(output.contains("> 3 4 5 7\n"), "{}", output);
+    assert!(output.contains("4 value(s) in the tree are smaller than 6."), "{}", output);
+    assert!(output.contains("The height of the tree is: 3"), "{}", output);
+    assert!(output.contains("Delete 5 succeed!"), "{}", output);
+    assert!(output.contains("5 is not in the tree."), "{}", output);
+    assert!(output.contains("7 is in the tree."), "{}", output);
+}
+
+#[test]
+fn mistakes_are_explained_and_the_prompt_goes_on() {
+    let output = repl(&["--tree", "rb", "--history", "none"], "ra 1 2\nfrobnicate\ninsert x\nrange 1\nlen\n");
+    assert!(output.contains("error: `ra` could be range or rank"), "{}", output);
+    assert!(output.contains("error: unknown command `frobnicate`, type `help` for the list of commands"), "{}", output);
+    assert!(output.contains("error: `x` is not a valid value"), "{}", output);
+    assert!(output.contains("error: `range` needs two values, LOW and HIGH"), "{}", output);
+    assert!(output.contains("The tree holds 0 value(s)."), "{}", output);
+}
+
+#[test]
+fn history_is_kept_between_sessions() {
+    let path = std::env::temp_dir().join(format!("repl-history-{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let history = path.to_str().unwrap();
+    repl(&["--history", history], "insert 4 2 6\nlen\nquit\n");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "insert 4 2 6\nlen\nquit\n");
+
+    // `!1` runs the first line of the earlier session on the new tree
+    let output = repl(&["--history", history], "!1\nhistory 2\n");
+    assert!(output.contains("insert 4 2 6\nInsert [4, 2, 6] successfully."), "{}", output);
+    assert!(output.contains("    4  insert 4 2 6\n    5  history 2\n"), "{}", output);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use std::thread;
+use project::persistent_tree::PersistentAVLTree;
+use project::shared_tree::SharedTree;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn shared_tree_is_send_and_sync() {
+    assert_send_sync::<SharedTree<i32>>();
+    assert_send_sync::<PersistentAVLTree<i32>>();
+}
+
+#[test]
+fn readers_see_consistent_snapshots_while_writer_inserts() {
+    let tree = Arc::new(SharedTree::new());
+    for i in 0..1000 {
+        tree.insert(i * 2);
+    }
+
+    let writer = {
+        let tree = Arc::clone(&tree);
+        thread::spawn(move || {
+            for i in 0..1000 {
+                // odd values go in, multiples of four go out
+                assert!(tree.insert(i * 2 + 1));
+                if i % 2 == 0 {
+                    assert!(tree.remove(&(i * 2)));
+                }
+            }
+        })
+    };
+
+    let readers: Vec<_> = (0..4).map(|_| {
+        let tree = Arc::clone(&tree);
+        thread::spawn(move || {
+            for _ in 0..200 {
+                let snapshot = tree.snapshot();
+                let values: Vec<i32> = snapshot.iter().collect();
+                assert_eq!(values.len(), snapshot.len());
+                assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+                // values that are never removed stay visible in every version
+                assert!(snapshot.contains(&2) && snapshot.contains(&1998));
+            }
+        })
+    }).collect();
+
+    writer.join().unwrap();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+
+    let values: Vec<i32> = tree.snapshot().iter().collect();
+    let expected: Vec<i32> = (0..2000).filter(|v| v % 4 != 0).collect();
+    assert_eq!(values, expected);
+}
+
+#[test]
+fn snapshot_is_not_affected_by_later_writes() {
+    let tree = SharedTree::new();
+    for i in 0..100 {
+        tree.insert(i);
+    }
+    let before = tree.snapshot();
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for i in 0..50 {
+                tree.remove(&i);
+            }
+        });
+        scope.spawn(|| {
+            for i in 100..150 {
+                tree.insert(i);
+            }
+        });
+    });
+
+    assert_eq!(before.iter().collect::<Vec<_>>(), (0..100).collect::<Vec<_>>());
+    assert_eq!(tree.snapshot().iter().collect::<Vec<_>>(), (50..150).collect::<Vec<_>>());
+    assert_eq!(tree.len(), 100);
+}
+
+#[test]
+fn concurrent_writers_do_not_lose_updates() {
+    let tree = SharedTree::new();
+    thread::scope(|scope| {
+        for t in 0..4 {
+            let tree = &tree;
+            scope.spawn(move || {
+                for i in 0..250 {
+                    assert!(tree.insert(t * 250 + i));
+                }
+            });
+        }
+    });
+    assert_eq!(tree.len(), 1000);
+    assert!((0..1000).all(|v| tree.contains(&v)));
+}
@@ -0,0 +1,88 @@
+use project::avl_tree::AVLTree;
+use project::ordered_set::OrderedSet;
+use project::redblack_tree::RedBlackTree;
+use project::stats::{StatsCounter, TreeStats};
+
+#[test]
+fn counter_records_only_while_enabled() {
+    let counter = StatsCounter::default();
+    counter.record(|s| s.operations += 1);
+    assert!(counter.compare(true));
+    assert_eq!(counter.get(), TreeStats::default());
+
+    counter.enable();
+    counter.record(|s| s.operations += 1);
+    // the outcome passes through whichever way it went
+    assert!(counter.compare(true));
+    assert!(!counter.compare(false));
+    counter.disable();
+    counter.record(|s| s.operations += 1);
+    let stats = counter.get();
+    assert_eq!((stats.operations, stats.comparisons), (1, 2));
+
+    counter.reset();
+    assert_eq!(counter.get(), TreeStats::default());
+}
+
+#[test]
+fn averages_and_the_report() {
+    let stats = TreeStats { operations: 4, single_rotations: 3, double_rotations: 2, recolors: 0, comparisons: 10, nodes_visited: 6 };
+    assert_eq!(stats.rotations(), 5);
+    assert_eq!(stats.comparisons_per_operation(), 2.5);
+    assert_eq!(stats.visits_per_operation(), 1.5);
+    assert_eq!(TreeStats::default().comparisons_per_operation(), 0.0);
+    assert_eq!(stats.to_string(), "operations:       4\n\
+                                   single rotations: 3\n\
+                                   double rotations: 2\n\
+                                   recolors:         0\n\
+                                   comparisons:      10 (2.50 per operation)\n\
+                                   nodes visited:    6 (1.50 per operation)");
+}
+
+#[test]
+fn avl_tree_counts_its_rotations() {
+    let mut tree: AVLTree<i32> = AVLTree::new();
+    tree.insert(1);
+    tree.enable_stats();
+    // a sorted run rotates once at 3, 5, 6 and 7
+    for value in 2..=7 {
+        tree.insert(value);
+    }
+    let stats = tree.stats();
+    assert_eq!((stats.operations, stats.single_rotations, stats.double_rotations, stats.recolors), (6, 4, 0, 0));
+    assert!(stats.comparisons >= stats.nodes_visited && stats.nodes_visited > 0);
+
+    // switched off, the totals stay where they were
+    tree.disable_stats();
+    tree.insert(8);
+    assert_eq!(tree.stats(), stats);
+
+    // 2 lands between 1 and 3, which takes a double rotation
+    let mut zigzag: AVLTree<i32> = AVLTree::new();
+    zigzag.enable_stats();
+    for value in [3, 1, 2] {
+        zigzag.insert(value);
+    }
+    let stats = zigzag.stats();
+    assert_eq!((stats.single_rotations, stats.double_rotations), (0, 1));
+}
+
+#[test]
+fn red_black_tree_counts_rotations_and_recolors() {
+    let mut tree: RedBlackTree<i32> = RedBlackTree::new();
+    tree.insert(1);
+    tree.insert(2);
+    tree.enable_stats();
+    // 3 rotates and swaps two colors, 4 pushes the red up to the root and back to black
+    tree.insert(3);
+    let stats = tree.stats();
+    assert_eq!((stats.operations, stats.single_rotations, stats.recolors), (1, 1, 2));
+    tree.insert(4);
+    assert_eq!(tree.stats().recolors, 6);
+
+    tree.reset_stats();
+    assert!(OrderedSet::contains(&tree, 4));
+    let stats = tree.stats();
+    assert_eq!((stats.operations, stats.single_rotations, stats.recolors), (1, 0, 0));
+    assert_eq!(stats.nodes_visited, 3);
+}
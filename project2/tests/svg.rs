@@ -0,0 +1,124 @@
+use project::avl_tree::AVLTree;
+use project::redblack_tree::RedBlackTree;
+use project::rng::XorShift64;
+use project::svg::{self, SvgNode};
+
+// value of `name="..."` in `line`
+fn attribute(line: &str, name: &str) -> f64 {
+    let start = line.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3;
+    let end = start + line[start..].find('"').unwrap();
+    line[start..end].parse().unwrap()
+}
+
+// (x, y, radius) of every circle in drawing order
+fn circles(svg: &str) -> Vec<(f64, f64, f64)> {
+    svg.lines()
+        .filter(|line| line.trim_start().starts_with("<circle"))
+        .map(|line| (attribute(line, "cx"), attribute(line, "cy"), attribute(line, "r")))
+        .collect()
+}
+
+#[test]
+fn avl_drawing_of_a_small_tree() {
+    let mut tree: AVLTree<i32> = AVLTree::new();
+    for value in [2, 1, 3] {
+        tree.insert(value);
+    }
+    assert_eq!(tree.to_svg(), r##"<svg xmlns="http://www.w3.org/2000/svg" width="160" height="188" viewBox="0 0 160 188" font-family="Helvetica, Arial, sans-serif">
+  <title>AVL Tree</title>
+  <rect width="100%" height="100%" fill="white"/>
+  <g stroke="#555" stroke-width="1.5">
+    <line x1="80.0" y1="42.0" x2="48.0" y2="112.0"/>
+    <line x1="80.0" y1="42.0" x2="112.0" y2="112.0"/>
+  </g>
+  <g text-anchor="middle" dominant-baseline="central">
+    <circle cx="80.0" cy="42.0" r="18.0" fill="#cfe2f3" stroke="#222" stroke-width="1.5"/>
+    <text x="80.0" y="42.0" font-size="14" fill="#000000">2</text>
+    <text x="80.0" y="70.0" font-size="10" fill="#333">h=2 bf=0</text>
+    <circle cx="48.0" cy="112.0" r="18.0" fill="#cfe2f3" stroke="#222" stroke-width="1.5"/>
+    <text x="48.0" y="112.0" font-size="14" fill="#000000">1</text>
+    <text x="48.0" y="140.0" font-size="10" fill="#333">h=1 bf=0</text>
+    <circle cx="112.0" cy="112.0" r="18.0" fill="#cfe2f3" stroke="#222" stroke-width="1.5"/>
+    <text x="112.0" y="112.0" font-size="14" fill="#000000">3</text>
+    <text x="112.0" y="140.0" font-size="10" fill="#333">h=1 bf=0</text>
+  </g>
+</svg>
+"##);
+}
+
+#[test]
+fn red_black_drawing_fills_nodes_with_their_color() {
+    let mut tree: RedBlackTree<i32> = RedBlackTree::new();
+    for value in [2, 1] {
+        tree.insert(value);
+    }
+    assert_eq!(tree.to_svg(), r##"<svg xmlns="http://www.w3.org/2000/svg" width="110" height="188" viewBox="0 0 110 188" font-family="Helvetica, Arial, sans-serif">
+  <title>Red-Black Tree</title>
+  <rect width="100%" height="100%" fill="white"/>
+  <g stroke="#555" stroke-width="1.5">
+    <line x1="68.0" y1="42.0" x2="42.0" y2="112.0"/>
+  </g>
+  <g text-anchor="middle" dominant-baseline="central">
+    <circle cx="68.0" cy="42.0" r="18.0" fill="#222222" stroke="#222" stroke-width="1.5"/>
+    <text x="68.0" y="42.0" font-size="14" fill="#ffffff">2</text>
+    <circle cx="42.0" cy="112.0" r="18.0" fill="#d62728" stroke="#222" stroke-width="1.5"/>
+    <text x="42.0" y="112.0" font-size="14" fill="#ffffff">1</text>
+  </g>
+</svg>
+"##);
+}
+
+#[test]
+fn an_empty_tree_says_so() {
+    assert_eq!(RedBlackTree::<i32>::new().to_svg(), r##"<svg xmlns="http://www.w3.org/2000/svg" width="168" height="62" viewBox="0 0 168 62" font-family="Helvetica, Arial, sans-serif">
+  <title>Red-Black Tree</title>
+  <rect width="100%" height="100%" fill="white"/>
+  <text x="84" y="31" font-size="14" text-anchor="middle" dominant-baseline="central" fill="#555">empty tree</text>
+</svg>
+"##);
+}
+
+#[test]
+fn labels_and_titles_are_escaped() {
+    let node = SvgNode {
+        label: "<a&b>".to_string(),
+        detail: Some("\"x\"".to_string()),
+        fill: "#fff",
+        text_color: "#000",
+        left: None,
+        right: None,
+    };
+    let drawing = svg::render(Some(&node), "t<1>");
+    assert!(drawing.contains("<title>t&lt;1&gt;</title>"));
+    assert!(drawing.contains(">&lt;a&amp;b&gt;</text>"));
+    assert!(drawing.contains(">&quot;x&quot;</text>"));
+}
+
+// nodes on one level never overlap and keep the order of their keys from left to right
+#[test]
+fn nodes_of_a_level_do_not_overlap() {
+    let mut rng = XorShift64::new(42);
+    let mut tree: RedBlackTree<i32> = RedBlackTree::new();
+    for _ in 0..300 {
+        tree.insert((rng.next_u64() % 100_000) as i32);
+    }
+    let drawing = tree.to_svg();
+    let width = attribute(drawing.lines().next().unwrap(), "width");
+    let placed = circles(&drawing);
+    assert_eq!(placed.len(), tree.iter().count());
+    for &(x, _, radius) in &placed {
+        assert!(x - radius >= 0.0 && x + radius <= width);
+    }
+    // circles come in pre-order, which pairs them with their keys
+    let mut placed: Vec<(usize, f64, f64, i32)> = tree.pre_order().zip(placed)
+        .map(|((key, depth), (x, _, radius))| (depth, x, radius, key))
+        .collect();
+    placed.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    for pair in placed.windows(2) {
+        let ((depth1, x1, r1, key1), (depth2, x2, r2, key2)) = (pair[0], pair[1]);
+        if depth1 == depth2 {
+            assert!(x2 - x1 >= r1 + r2, "circles at {} and {} overlap", x1, x2);
+            assert!(key1 < key2, "{} is drawn left of {}", key2, key1);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use project::avl_tree::{AVLTree, NodeRef as AvlNode};
+use project::redblack_tree::{NodeRef as RbNode, RedBlackTree};
+use project::rng::XorShift64;
+
+// the orders a recursive walk gives, with the depth of every node
+#[derive(Default)]
+struct Walk {
+    pre: Vec<(i32, usize)>,
+    post: Vec<(i32, usize)>,
+    levels: Vec<Vec<i32>>,
+}
+
+impl Walk {
+    fn visit(&mut self, key: i32, depth: usize) {
+        self.pre.push((key, depth));
+        if self.levels.len() <= depth {
+            self.levels.push(Vec::new());
+        }
+        self.levels[depth].push(key);
+    }
+
+    fn level_order(&self) -> Vec<(i32, usize)> {
+        self.levels.iter().enumerate()
+            .flat_map(|(depth, keys)| keys.iter().map(move |&key| (key, depth)))
+            .collect()
+    }
+}
+
+fn walk_avl(node: Option<AvlNode<i32>>, depth: usize, walk: &mut Walk) {
+    if let Some(node) = node {
+        walk.visit(node.key(), depth);
+        walk_avl(node.left(), depth + 1, walk);
+        walk_avl(node.right(), depth + 1, walk);
+        walk.post.push((node.key(), depth));
+    }
+}
+
+fn walk_rb(node: Option<RbNode<i32>>, depth: usize, walk: &mut Walk) {
+    if let Some(node) = node {
+        walk.visit(node.value(), depth);
+        walk_rb(node.left(), depth + 1, walk);
+        walk_rb(node.right(), depth + 1, walk);
+        walk.post.push((node.value(), depth));
+    }
+}
+
+#[test]
+fn avl_orders_of_a_full_tree() {
+    let mut tree: AVLTree<i32> = AVLTree::new();
+    for value in 1..=7 {
+        tree.insert(value);
+    }
+    assert_eq!(tree.pre_order().collect::<Vec<_>>(), vec![(4, 0), (2, 1), (1, 2), (3, 2), (6, 1), (5, 2), (7, 2)]);
+    assert_eq!(tree.post_order().collect::<Vec<_>>(), vec![(1, 2), (3, 2), (2, 1), (5, 2), (7, 2), (6, 1), (4, 0)]);
+    assert_eq!(tree.level_order().collect::<Vec<_>>(), vec![(4, 0), (2, 1), (6, 1), (1, 2), (3, 2), (5, 2), (7, 2)]);
+}
+
+#[test]
+fn red_black_orders_of_a_lopsided_tree() {
+    // 2 at the root, 1 on its left, and 4 over 3 and 6, which holds 5 and 7
+    let mut tree: RedBlackTree<i32> = RedBlackTree::new();
+    for value in 1..=7 {
+        tree.insert(value);
+    }
+    assert_eq!(tree.pre_order().collect::<Vec<_>>(), vec![(2, 0), (1, 1), (4, 1), (3, 2), (6, 2), (5, 3), (7, 3)]);
+    assert_eq!(tree.post_order().collect::<Vec<_>>(), vec![(1, 1), (3, 2), (5, 3), (7, 3), (6, 2), (4, 1), (2, 0)]);
+    assert_eq!(tree.level_order().collect::<Vec<_>>(), vec![(2, 0), (1, 1), (4, 1), (3, 2), (6, 2), (5, 3), (7, 3)]);
+}
+
+#[test]
+fn empty_trees_have_no_nodes_to_visit() {
+    let avl: AVLTree<i32> = AVLTree::new();
+    assert_eq!((avl.pre_order().count(), avl.post_order().count(), avl.level_order().count()), (0, 0, 0));
+    let rb: RedBlackTree<i32> = RedBlackTree::new();
+    assert_eq!((rb.pre_order().count(), rb.post_order().count(), rb.level_order().count()), (0, 0, 0));
+}
+
+#[test]
+fn repeated_keys_are_visited_once_per_node() {
+    let mut avl: AVLTree<i32> = AVLTree::new_multiset();
+    let mut rb: RedBlackTree<i32> = RedBlackTree::new_multiset();
+    for value in [2, 1, 2, 3, 2] {
+        avl.insert(value);
+        rb.insert(value);
+    }
+    let expected = vec![(2, 0), (1, 1), (3, 1)];
+    assert_eq!(avl.pre_order().collect::<Vec<_>>(), expected);
+    assert_eq!(rb.level_order().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn orders_match_a_recursive_walk_on_random_trees() {
+    let mut rng = XorShift64::new(29);
+    for size in [1, 2, 10, 100, 500] {
+        let mut avl: AVLTree<i32> = AVLTree::new();
+        let mut rb: RedBlackTree<i32> = RedBlackTree::new();
+        for _ in 0..size {
+            let value = (rng.next_u64() % 1000) as i32;
+            avl.insert(value);
+            rb.insert(value);
+        }
+        for _ in 0..size / 3 {
+            let value = (rng.next_u64() % 1000) as i32;
+            avl.delete(value);
+            rb.delete(value);
+        }
+
+        let mut walk = Walk::default();
+        walk_avl(avl.root_node(), 0, &mut walk);
+        assert_eq!(avl.pre_order().collect::<Vec<_>>(), walk.pre);
+        assert_eq!(avl.post_order().collect::<Vec<_>>(), walk.post);
+        assert_eq!(avl.level_order().collect::<Vec<_>>(), walk.level_order());
+
+        let mut walk = Walk::default();
+        walk_rb(rb.root_node(), 0, &mut walk);
+        assert_eq!(rb.pre_order().collect::<Vec<_>>(), walk.pre);
+        assert_eq!(rb.post_order().collect::<Vec<_>>(), walk.post);
+        assert_eq!(rb.level_order().collect::<Vec<_>>(), walk.level_order());
+    }
+}